@@ -0,0 +1,43 @@
+use std::io::Write;
+
+use crate::theme::Themed;
+use anyhow::Result;
+
+/// An author of the entries in the reading list, along with its metadata
+#[derive(Debug)]
+pub struct Author {
+    pub name: String,
+    pub url: Option<String>,
+    pub notes: Option<String>,
+    /// The number of entries by this author
+    pub entries: i64,
+}
+
+impl Author {
+    /// Prints the author to `out`.
+    /// If `!long`, then it will only print `name (n entries)`,
+    /// otherwise, it will also print the url and the notes of the author
+    pub fn pretty_print(&self, out: &mut impl Write, long: bool) -> Result<()> {
+        let url_row = match &self.url {
+            Some(url) if long => format!("\nUrl: {}", url.link()),
+            _ => String::new(),
+        };
+        let notes_row = match &self.notes {
+            Some(notes) if long => format!("\nNotes: {notes}"),
+            _ => String::new(),
+        };
+
+        writeln!(
+            out,
+            "{} ({} {}){url_row}{notes_row}",
+            self.name.author_name(),
+            self.entries,
+            if self.entries == 1 {
+                "entry"
+            } else {
+                "entries"
+            }
+        )?;
+        Ok(())
+    }
+}
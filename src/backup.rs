@@ -0,0 +1,75 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// The prefix and the extension of the names of the automatic backups, which contain the time they were taken in between
+const BACKUP_PREFIX: &str = "rlist-";
+const BACKUP_EXTENSION: &str = "sqlite";
+
+/// The `backups` config option, as written in the config file
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct BackupConfig {
+    /// Where the backups are saved. Defaults to the `backups` folder next to the db file
+    pub dir: Option<PathBuf>,
+    /// How many backups are kept. Defaults to [`DEFAULT_KEEP`]
+    pub keep: Option<usize>,
+}
+
+/// How many automatic backups are kept when the `keep` config option is not set
+pub const DEFAULT_KEEP: usize = 10;
+
+/// Where the automatic backups of the reading list are saved, and how many of them are kept (see [`crate::RList::set_backups`])
+#[derive(Debug, Clone)]
+pub struct BackupPolicy {
+    pub dir: PathBuf,
+    pub keep: usize,
+}
+
+impl BackupPolicy {
+    /// Returns the path of a new backup in `dir` (see [`backup_file_name`])
+    pub fn new_backup_path(&self) -> PathBuf {
+        self.dir.join(backup_file_name())
+    }
+
+    /// Returns the paths of the automatic backups in `dir`, from the oldest to the newest
+    pub fn backups(&self) -> Result<Vec<PathBuf>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut backups = std::fs::read_dir(&self.dir)
+            .with_context(|| format!("Could not read the backups in {}", self.dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| is_backup(path))
+            .collect::<Vec<_>>();
+        backups.sort();
+        Ok(backups)
+    }
+
+    /// Removes the oldest backups, so that only the newest `keep` are left
+    pub fn rotate(&self) -> Result<()> {
+        let backups = self.backups()?;
+        let extra = backups.len().saturating_sub(self.keep);
+        for path in backups.iter().take(extra) {
+            std::fs::remove_file(path)
+                .with_context(|| format!("Could not remove the old backup {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of a new backup, after the current time (e.g. `rlist-20240131-093000123.sqlite`),
+/// so that the backups are sorted from the oldest to the newest by name
+pub fn backup_file_name() -> String {
+    let now = chrono::Local::now().format("%Y%m%d-%H%M%S%3f");
+    format!("{BACKUP_PREFIX}{now}.{BACKUP_EXTENSION}")
+}
+
+/// Returns whether `path` is named like an automatic backup
+fn is_backup(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == BACKUP_EXTENSION)
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(BACKUP_PREFIX))
+}
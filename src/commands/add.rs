@@ -0,0 +1,307 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+
+use super::AppContext;
+use rlist::{
+    entry::Entry,
+    hook::Hook,
+    rlist::AddOptions,
+    topic::Topic,
+    utils::{
+        canonicalize_url, normalize_name, now, parse_future_datetime, parse_key_value, validate_url,
+    },
+    web::Page,
+};
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// The name of the entry. If the url is omitted, this is used as the url and the name is taken from the title of the page
+    #[arg(value_name = "NAME|URL", required_unless_present_any = ["entries", "stdin"])]
+    name: Option<String>,
+
+    /// The content of the entry
+    url: Option<String>,
+
+    /// Another entry to add, as `name=<name>,url=<url>` (or just `url=<url>`, to name it like NAME|URL). Can be repeated:
+    /// all of the entries are added together, and get the same author, topics and other options
+    #[arg(short, long = "entry", value_name = "name=NAME,url=URL", value_parser = parse_entry)]
+    entries: Vec<(Option<String>, String)>,
+
+    /// Read the entries to add from stdin, one per line: either a url or a name and a url separated by a tab.
+    /// The lines that cannot be added are reported at the end, while all of the others are added
+    #[arg(long, conflicts_with_all = ["name", "entries"])]
+    stdin: bool,
+
+    /// When the name is omitted, do not download the page to get its title and use the url as name instead
+    #[arg(long)]
+    no_fetch: bool,
+
+    /// The author of the content
+    #[arg(short, long)]
+    author: Option<String>,
+
+    /// Topics related to the content of the entry
+    #[arg(short, long, num_args = 1..)]
+    topics: Vec<String>,
+
+    /// Metadata attached to the entry, as `key=value` pairs
+    #[arg(short, long, num_args = 1.., value_parser = parse_key_value)]
+    meta: Vec<(String, String)>,
+
+    /// The estimated reading time of the content, in minutes
+    #[arg(short, long, value_parser = clap::value_parser!(i64).range(1..))]
+    length: Option<i64>,
+
+    /// If an entry with the same name or url already exists, update it instead: the options that are set overwrite its fields
+    /// and the topics are added to its topics. Without a name, the existing entry keeps its name
+    #[arg(long)]
+    update: bool,
+
+    /// If an entry with the same name or url already exists, do nothing (and exit successfully) instead of failing
+    #[arg(long, conflicts_with = "update")]
+    if_not_exists: bool,
+
+    /// Add the entry even if its url is not a valid url
+    #[arg(long)]
+    allow_invalid_url: bool,
+
+    /// When you want to have read the entry by: a datetime, a day (e.g. `friday`, `tomorrow`) or a duration from now (e.g. `2w`)
+    #[arg(long, value_parser = |s: &str| parse_future_datetime(s))]
+    due: Option<String>,
+}
+
+pub fn run(ctx: &mut AppContext, args: AddArgs) -> Result<()> {
+    let mut targets = Vec::new();
+    match (args.name.clone(), args.url.clone()) {
+        (Some(name), Some(url)) => targets.push((Some(name), url)),
+        (Some(url), None) => targets.push((None, url)),
+        _ => {}
+    }
+    targets.extend(args.entries.iter().cloned());
+    // The number of the line each entry was read from, when reading from stdin
+    let mut lines = Vec::new();
+    if args.stdin {
+        for (i, line) in std::io::stdin().lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            targets.push(match line.split_once('\t') {
+                Some((name, url)) => (Some(name.to_string()), url.to_string()),
+                None => (None, line),
+            });
+            lines.push(i + 1);
+        }
+    }
+
+    // When reading from stdin, the entries that cannot be added are reported at the end instead of aborting the whole command
+    let mut failures = Vec::new();
+    let mut resolved = Vec::new();
+    for (i, (name, url)) in targets.into_iter().enumerate() {
+        match resolve(ctx, &args, name, url) {
+            Ok(Some(target)) => resolved.push((i, target)),
+            Ok(None) => {}
+            Err(err) if args.stdin => failures.push((lines[i], err)),
+            Err(err) => return Err(err),
+        }
+    }
+
+    let opts = AddOptions {
+        author: args.author,
+        topics: ctx.config.resolve_topic_aliases(args.topics),
+        meta: args.meta,
+        length: args.length,
+        due: args.due,
+    };
+    // The pre hooks run before anything is added, so that a failing hook skips its entry (or aborts the command)
+    let mut to_add = Vec::new();
+    for (i, (name, url)) in resolved {
+        let mut opts = opts.clone();
+        opts.topics
+            .extend(ctx.config.rule_topics(&url, &opts.topics));
+        match run_pre_hook(ctx, args.update, &name, &url, &opts) {
+            Ok(()) => to_add.push((i, name, url, opts)),
+            Err(err) if args.stdin => failures.push((lines[i], err)),
+            Err(err) => return Err(err),
+        }
+    }
+
+    let added = ctx.rlist.transaction(|rlist| {
+        let mut added = Vec::new();
+        for (i, name, url, opts) in to_add {
+            // Each entry is added in a nested transaction, so that a failure only rolls back its own changes
+            let res = rlist.transaction(|rlist| {
+                if args.update {
+                    rlist.add_or_update(name, url, opts)
+                } else {
+                    Ok((rlist.add(name, url, opts)?, true))
+                }
+            });
+            match res {
+                Ok(res) => added.push(res),
+                Err(err) if args.stdin => failures.push((lines[i], err)),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(added)
+    })?;
+
+    for (i, (entry, created)) in added.iter().enumerate() {
+        if i > 0 {
+            note!(ctx)?;
+        }
+        if *created {
+            note!(ctx, "Entry added to rlist:")?;
+        } else {
+            note!(ctx, "Entry updated:")?;
+        }
+        ctx.print_entry(entry, true)?;
+        ctx.run_hook(
+            if *created {
+                Hook::PostAdd
+            } else {
+                Hook::PostEdit
+            },
+            entry,
+        )?;
+    }
+
+    if !failures.is_empty() {
+        failures.sort_by_key(|(line, _err)| *line);
+        for (line, err) in failures.iter() {
+            eprintln!("{}: line {line}: {err}", "Error".bold().red());
+        }
+        return Err(anyhow::anyhow!(
+            "{} {} could not be added",
+            failures.len(),
+            if failures.len() == 1 { "line" } else { "lines" }
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the name and the (validated and canonicalized) url of an entry to add, given as `name` and `url`.
+/// Without a name, the name is taken from the page (or from the existing entry, with `--update`).
+/// Returns `None` if the entry must be skipped because of `--if-not-exists`
+fn resolve(
+    ctx: &AppContext,
+    args: &AddArgs,
+    name: Option<String>,
+    url: String,
+) -> Result<Option<(String, String)>> {
+    let url = if args.allow_invalid_url {
+        url
+    } else {
+        validate_url(&url)?
+    };
+    let canonical_url = canonicalize_url(&url, &ctx.config.url_canonicalization);
+    if args.if_not_exists && ctx.rlist.get_by_url(&canonical_url)?.is_some() {
+        return Ok(None);
+    }
+    let existing = match name {
+        None if args.update => ctx.rlist.get_by_url(&canonical_url)?,
+        _ => None,
+    };
+    let name = match (name, existing) {
+        (Some(name), _) => name,
+        (None, Some(existing)) => existing.name,
+        // Without a name, the name is the url itself (possibly completed by `validate_url`)
+        (None, None) if args.no_fetch => url.clone(),
+        (None, None) => fetch_title(&url),
+    };
+    if args.if_not_exists && ctx.rlist.contains(&name)? {
+        return Ok(None);
+    }
+    Ok(Some((name, canonical_url)))
+}
+
+/// Runs the pre_edit hook on the existing entry that `--update` would update, or else the pre_add hook on the entry about to be added
+fn run_pre_hook(
+    ctx: &AppContext,
+    update: bool,
+    name: &str,
+    url: &str,
+    opts: &AddOptions,
+) -> Result<()> {
+    if update {
+        let existing = match ctx.rlist.get_by_url(url)? {
+            Some(existing) => Some(existing),
+            None if ctx.rlist.contains(name)? => Some(ctx.rlist.get_by_name(name)?),
+            None => None,
+        };
+        if let Some(existing) = existing {
+            return ctx.run_hook(Hook::PreEdit, &existing);
+        }
+    }
+    if !ctx.has_hook(Hook::PreAdd) {
+        return Ok(());
+    }
+    let mut entry = Entry::new(
+        normalize_name(name),
+        url.to_string(),
+        opts.author.clone(),
+        opts.topics.iter().map(Topic::normalize).collect(),
+        Some(now()),
+    );
+    entry.length = opts.length;
+    entry.due = opts.due.clone();
+    entry.meta = opts.meta.iter().cloned().collect();
+    ctx.run_hook(Hook::PreAdd, &entry)
+}
+
+/// Parses an entry given with `--entry`, as `name=<name>,url=<url>` or `url=<url>`
+fn parse_entry(s: &str) -> Result<(Option<String>, String)> {
+    // The fields are only split at the commas followed by a key, since urls can contain commas too
+    let mut name = None;
+    let mut url = None;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (field, next) = match rest
+            .match_indices(',')
+            .find(|(i, _)| rest[i + 1..].starts_with("name=") || rest[i + 1..].starts_with("url="))
+        {
+            Some((i, _)) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+        match field.split_once('=') {
+            Some(("name", value)) => name = Some(value.to_string()),
+            Some(("url", value)) => url = Some(value.to_string()),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Invalid entry \"{s}\": expected `name=<name>,url=<url>`"
+                ))
+            }
+        }
+        rest = next;
+    }
+    match url {
+        Some(url) => Ok((name, url)),
+        None => Err(anyhow::anyhow!(
+            "Invalid entry \"{s}\": the url of the entry is missing"
+        )),
+    }
+}
+
+/// Returns the title of the page at `url`, or `url` itself if the title could not be fetched
+fn fetch_title(url: &str) -> String {
+    match Page::fetch(url).map(|page| page.title()) {
+        Ok(Some(title)) => title,
+        Ok(None) => {
+            eprintln!(
+                "{}: the page has no title, the url will be used as name",
+                "Warning".bold().yellow()
+            );
+            url.to_string()
+        }
+        Err(err) => {
+            eprintln!(
+                "{}: {err:#}, the url will be used as name",
+                "Warning".bold().yellow()
+            );
+            url.to_string()
+        }
+    }
+}
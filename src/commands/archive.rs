@@ -0,0 +1,58 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::web::archive;
+
+#[derive(Args, Debug)]
+pub struct ArchiveArgs {
+    /// The name of the entry
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    name: Option<String>,
+
+    /// Archive all of the entries that have not been archived yet
+    #[arg(long)]
+    all: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: ArchiveArgs) -> Result<()> {
+    if let Some(name) = args.name {
+        let name = ctx.resolve_name(name)?;
+        return archive_entry(ctx, &name);
+    }
+
+    let names = ctx.rlist.names_not_archived()?;
+    let mut archived = 0;
+    for name in names.iter() {
+        match archive_entry(ctx, name) {
+            Ok(()) => archived += 1,
+            Err(err) => eprintln!(
+                "{}: {}: {err:#}",
+                "Warning".bold().yellow(),
+                name.entry_name()
+            ),
+        }
+    }
+    note!(
+        ctx,
+        "Archived {archived} of {} {}",
+        names.len(),
+        if names.len() == 1 { "entry" } else { "entries" }
+    )?;
+
+    Ok(())
+}
+
+/// Archives the content of the entry with name = `name` and prints the updated entry
+fn archive_entry(ctx: &mut AppContext, name: &str) -> Result<()> {
+    let url = ctx.rlist.get_by_name(name)?.url;
+    let archive_url = archive(&url)?;
+    let entry = ctx.rlist.set_archive_url(name, &archive_url)?;
+    ctx.print_entry(&entry, true)?;
+    note!(ctx)?;
+    Ok(())
+}
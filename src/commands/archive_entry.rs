@@ -0,0 +1,32 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::{AppContext, EntrySelector};
+
+#[derive(Args, Debug)]
+pub struct ArchiveEntryArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+
+    /// Unarchive the entry, making it visible again
+    #[arg(long)]
+    undo: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: ArchiveEntryArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let entry = ctx.rlist.set_archived(name, !args.undo)?;
+    note!(
+        ctx,
+        "{}",
+        if args.undo {
+            "Unarchived entry:"
+        } else {
+            "Archived entry:"
+        }
+    )?;
+    ctx.print_entry(&entry, true)?;
+    Ok(())
+}
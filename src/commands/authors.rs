@@ -0,0 +1,90 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::rlist::AuthorOrder;
+
+#[derive(Args, Debug)]
+pub struct AuthorsArgs {
+    #[command(subcommand)]
+    action: Option<AuthorsAction>,
+
+    /// A substring that the name of the authors must contain
+    query: Option<String>,
+
+    /// The attribute used to sort the authors. Options are: name, count (descending)
+    #[arg(short, long, default_value = "name")]
+    sort: AuthorOrder,
+
+    /// If set, the result will also show the url and the notes of each author
+    #[arg(short, long)]
+    long: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AuthorsAction {
+    /// Rename an author in all of the entries
+    #[command(aliases=&["mv"])]
+    Rename {
+        /// The current name of the author
+        old: String,
+
+        /// The new name of the author
+        new: String,
+    },
+
+    /// Set the url and/or the notes of an author
+    #[command(aliases=&["e"])]
+    Edit {
+        /// The name of the author
+        name: String,
+
+        /// The url of the author, e.g. their website
+        #[arg(long)]
+        url: Option<String>,
+
+        /// Some notes about the author
+        #[arg(long)]
+        notes: Option<String>,
+    },
+}
+
+pub fn run(ctx: &mut AppContext, args: AuthorsArgs) -> Result<()> {
+    match args.action {
+        Some(AuthorsAction::Rename { old, new }) => {
+            let updated = ctx.rlist.rename_author(&old, &new)?;
+            writeln!(
+                ctx.out,
+                "Renamed author {} to {} in {updated} {}",
+                old.author_name(),
+                new.author_name(),
+                if updated == 1 { "entry" } else { "entries" }
+            )?;
+            return Ok(());
+        }
+        Some(AuthorsAction::Edit { name, url, notes }) => {
+            if url.is_none() && notes.is_none() {
+                return Err(anyhow::anyhow!("No edit options were given"));
+            }
+            ctx.rlist
+                .set_author_details(&name, url.as_deref(), notes.as_deref())?;
+            writeln!(ctx.out, "Updated author {}", name.author_name())?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let authors = ctx.rlist.authors(args.query.as_deref(), args.sort)?;
+
+    for author in authors.iter() {
+        author.pretty_print(&mut ctx.out, args.long)?;
+    }
+
+    if authors.is_empty() {
+        writeln!(ctx.out, "No authors matched your query")?;
+    }
+    Ok(())
+}
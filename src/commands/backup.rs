@@ -0,0 +1,30 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Result;
+use clap::Args;
+
+use super::AppContext;
+use rlist::{backup::backup_file_name, rlist::QueryOptions};
+
+#[derive(Args, Debug)]
+pub struct BackupArgs {
+    /// Where to save the backup. Defaults to a file named after the current time in the backups folder
+    /// (the `backups` folder next to the db file, unless set otherwise with `backups.dir` in the config)
+    path: Option<PathBuf>,
+}
+
+pub fn run(ctx: &mut AppContext, args: BackupArgs) -> Result<()> {
+    let path = args
+        .path
+        .unwrap_or_else(|| ctx.config.backups_dir().join(backup_file_name()));
+    ctx.rlist.backup(&path)?;
+
+    let count = ctx.rlist.count(&QueryOptions::default())?;
+    note!(
+        ctx,
+        "Saved a backup of your reading list ({count} {}) to {}",
+        if count == 1 { "entry" } else { "entries" },
+        path.display()
+    )?;
+    Ok(())
+}
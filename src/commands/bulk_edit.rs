@@ -0,0 +1,153 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::{list::FilterArgs, AppContext, EntryStatus};
+use rlist::{
+    hook::Hook,
+    rlist::{EditOptions, QueryOptions},
+    topic::Topic,
+};
+
+#[derive(Args, Debug)]
+pub struct BulkEditArgs {
+    /// A substring that the name of the entries must contain
+    query: Option<String>,
+
+    /// Only edit the entries that have urls that contain this substring
+    #[arg(long)]
+    url: Option<String>,
+
+    #[command(flatten)]
+    filters: FilterArgs,
+
+    /// The topics to add to the entries
+    #[arg(long, num_args = 1..)]
+    add_topics: Option<Vec<String>>,
+
+    /// The topics to remove the entries from
+    #[arg(long, num_args = 1..)]
+    remove_topics: Option<Vec<String>>,
+
+    /// The new author of the entries (`--author` selects the entries by their current author)
+    #[arg(long)]
+    set_author: Option<String>,
+
+    /// Mark the entries as read or unread, or archive or unarchive them.
+    /// Archived entries are only matched with `--archived` or `--include-archived`
+    #[arg(long)]
+    set_status: Option<EntryStatus>,
+
+    /// Only print the entries that would be edited, without editing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Do not ask for confirmation before editing more than one entry
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: BulkEditArgs) -> Result<()> {
+    let opts = EditOptions {
+        add_topics: args.add_topics.map(|t| ctx.config.resolve_topic_aliases(t)),
+        remove_topics: args
+            .remove_topics
+            .map(|t| ctx.config.resolve_topic_aliases(t)),
+        author: args.set_author,
+        read: match args.set_status {
+            Some(EntryStatus::Read) => Some(true),
+            Some(EntryStatus::Unread) => Some(false),
+            _ => None,
+        },
+        archived: match args.set_status {
+            Some(EntryStatus::Archived) => Some(true),
+            Some(EntryStatus::Unarchived) => Some(false),
+            _ => None,
+        },
+        ..Default::default()
+    };
+    if opts.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No edit options were given: pass at least one of --add-topics, --remove-topics, --set-author and --set-status"
+        ));
+    }
+
+    let query = QueryOptions {
+        query: args.query,
+        url: args.url,
+        ..args.filters.query_options(ctx)?
+    };
+    let entries = ctx.rlist.query_with(&query)?;
+    let names = entries.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+    if names.is_empty() {
+        note!(ctx, "No entries matched your query")?;
+        return Ok(());
+    }
+
+    for name in names.iter() {
+        writeln!(ctx.out, "{}", name.entry_name())?;
+    }
+    let changes = describe(&opts);
+    if args.dry_run {
+        note!(
+            ctx,
+            "{} {} would be edited: {changes}",
+            names.len(),
+            if names.len() == 1 { "entry" } else { "entries" }
+        )?;
+        return Ok(());
+    }
+    if names.len() > 1
+        && !args.yes
+        && !ctx.confirm(
+            format!(
+                "Do you want to edit these {} entries ({changes})?",
+                names.len()
+            ),
+            "Pass --yes to edit the entries",
+        )?
+    {
+        return Ok(());
+    }
+
+    for e in entries.iter() {
+        ctx.run_hook(Hook::PreEdit, e)?;
+    }
+    for e in ctx.rlist.edit_many(&names, opts)?.iter() {
+        ctx.run_hook(Hook::PostEdit, e)?;
+    }
+    note!(
+        ctx,
+        "Edited {} {}: {changes}",
+        names.len(),
+        if names.len() == 1 { "entry" } else { "entries" }
+    )?;
+    Ok(())
+}
+
+/// Returns a short description of the changes in `opts`, such as `+rust, -later, marked as read`
+fn describe(opts: &EditOptions) -> String {
+    let mut changes = Vec::new();
+    for t in opts.add_topics.iter().flatten() {
+        changes.push(format!("+{}", Topic::pretty_print(t.as_str())));
+    }
+    for t in opts.remove_topics.iter().flatten() {
+        changes.push(format!("-{}", Topic::pretty_print(t.as_str())));
+    }
+    if let Some(author) = opts.author.as_ref() {
+        changes.push(format!("author set to {author}"));
+    }
+    match opts.read {
+        Some(true) => changes.push("marked as read".to_string()),
+        Some(false) => changes.push("marked as unread".to_string()),
+        None => {}
+    }
+    match opts.archived {
+        Some(true) => changes.push("archived".to_string()),
+        Some(false) => changes.push("unarchived".to_string()),
+        None => {}
+    }
+    changes.join(", ")
+}
@@ -0,0 +1,98 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::{
+    rlist::{EditOptions, QueryOptions},
+    topic::Topic,
+    web::{check_links, LinkStatus},
+};
+
+/// The topic given to the entries with a broken link when `--tag` is set
+const DEAD_LINK_TOPIC: &str = "dead-link";
+
+#[derive(Args, Debug)]
+pub struct CheckLinksArgs {
+    /// Only check the entries that are in all of these topics
+    #[arg(short, long, num_args = 1..)]
+    topics: Option<Vec<String>>,
+
+    /// If set, check the entries that are in at least one of the topics specified with `--topics`
+    #[arg(long)]
+    or: bool,
+
+    /// Add the entries with a broken link to the `dead-link` topic
+    #[arg(long)]
+    tag: bool,
+
+    /// How many links to check at the same time
+    #[arg(short, long, default_value_t = 8, value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: u16,
+}
+
+pub fn run(ctx: &mut AppContext, args: CheckLinksArgs) -> Result<()> {
+    let entries = ctx.rlist.query_with(&QueryOptions {
+        topics: args.topics,
+        or: args.or,
+        ..Default::default()
+    })?;
+    let urls = entries.iter().map(|e| e.url.clone()).collect::<Vec<_>>();
+    let statuses = check_links(&urls, args.jobs as usize);
+
+    let mut broken = 0;
+    let mut redirected = 0;
+    for (entry, status) in entries.iter().zip(statuses) {
+        match status {
+            LinkStatus::Ok => {}
+            LinkStatus::Redirected(target) => {
+                redirected += 1;
+                writeln!(
+                    ctx.out,
+                    "{}: {} redirects to {}",
+                    entry.name.entry_name(),
+                    entry.url.link(),
+                    target.link()
+                )?;
+            }
+            LinkStatus::Broken(reason) => {
+                broken += 1;
+                writeln!(
+                    ctx.out,
+                    "{}: {} is broken ({})",
+                    entry.name.entry_name(),
+                    entry.url.link(),
+                    reason.red()
+                )?;
+                if args.tag {
+                    ctx.edit_entry(
+                        entry,
+                        EditOptions {
+                            add_topics: Some(vec![DEAD_LINK_TOPIC.to_string()]),
+                            ..Default::default()
+                        },
+                    )?;
+                }
+            }
+        }
+    }
+
+    writeln!(
+        ctx.out,
+        "Checked {} {}: {broken} broken, {redirected} redirected",
+        entries.len(),
+        if entries.len() == 1 { "link" } else { "links" }
+    )?;
+    if args.tag && broken > 0 {
+        writeln!(
+            ctx.out,
+            "The broken entries were added to the {} topic",
+            Topic::pretty_print(DEAD_LINK_TOPIC)
+        )?;
+    }
+
+    Ok(())
+}
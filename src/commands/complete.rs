@@ -0,0 +1,39 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Args, ValueEnum};
+
+use super::AppContext;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CompletionKind {
+    Entries,
+    Topics,
+}
+
+#[derive(Args, Debug)]
+pub struct CompleteArgs {
+    /// What to complete
+    kind: CompletionKind,
+
+    /// Only print the names that start with this prefix
+    #[arg(default_value = "")]
+    prefix: String,
+}
+
+pub fn run(ctx: &mut AppContext, args: CompleteArgs) -> Result<()> {
+    let names = match args.kind {
+        CompletionKind::Entries => ctx
+            .rlist
+            .query()
+            .run()?
+            .into_iter()
+            .map(|e| e.name)
+            .collect(),
+        CompletionKind::Topics => ctx.rlist.topic_names()?,
+    };
+    for name in names.iter().filter(|n| n.starts_with(&args.prefix)) {
+        writeln!(ctx.out, "{name}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Args, Command, CommandFactory};
+use clap_complete::Shell;
+
+/// The subcommands (given by their path, e.g. `queue add`) whose first argument is the name of an entry,
+/// completed with `rlist __complete entries`
+const ENTRY_COMMANDS: [&[&str]; 13] = [
+    &["remove"],
+    &["edit"],
+    &["open"],
+    &["show"],
+    &["mark-read"],
+    &["snooze"],
+    &["archive-entry"],
+    &["fetch"],
+    &["save"],
+    &["read"],
+    &["history"],
+    &["merge"],
+    &["queue", "add"],
+];
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// The shell to generate the completion script for
+    shell: Shell,
+}
+
+/// Prints the completion script to `out`. It does not need the reading list, so it works even before one is set up
+pub fn run(out: &mut dyn Write, args: CompletionsArgs) -> Result<()> {
+    let mut command = crate::Args::command();
+    // The script is generated in memory, since `generate` panics on write errors
+    let mut script = Vec::new();
+    clap_complete::generate(args.shell, &mut command, "rlist", &mut script);
+    out.write_all(&script)?;
+
+    // The names (and aliases) of each level of the path of each entry command
+    let entry_commands = ENTRY_COMMANDS
+        .iter()
+        .map(|path| {
+            let mut parent = &command;
+            path.iter()
+                .map(|name| {
+                    parent = parent
+                        .find_subcommand(name)
+                        .expect("entry commands are subcommands of rlist");
+                    std::iter::once(parent.get_name())
+                        .chain(parent.get_all_aliases())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // Every way of typing each entry command, quoted for the `case` of bash and zsh (e.g. `"queue add"`)
+    let entry_patterns = entry_commands
+        .iter()
+        .flat_map(|levels| {
+            levels.iter().fold(vec![String::new()], |prefixes, names| {
+                prefixes
+                    .iter()
+                    .flat_map(|prefix| {
+                        names.iter().map(move |name| match prefix.is_empty() {
+                            true => name.to_string(),
+                            false => format!("{prefix} {name}"),
+                        })
+                    })
+                    .collect()
+            })
+        })
+        .map(|words| format!("\"{words}\""))
+        .collect::<Vec<_>>();
+    let mut topic_flags = Vec::new();
+    collect_topic_flags(&command, &mut topic_flags);
+
+    // Completion of the names of the entries and of the topics, which are read from the reading list at each completion
+    let dynamic = match args.shell {
+        Shell::Bash => format!(
+            r#"
+_rlist_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" i=$COMP_CWORD IFS=$'\n'
+    while (( i > 2 )) && [[ "${{COMP_WORDS[i-1]}}" != -* ]]; do (( i-- )); done
+    case "${{COMP_WORDS[i-1]}}" in
+        {topic_flags})
+            COMPREPLY=($(compgen -W "$(rlist __complete topics 2>/dev/null)" -- "$cur"))
+            return 0
+            ;;
+    esac
+    if [[ "$cur" != -* ]]; then
+        local command="${{COMP_WORDS[*]:1:COMP_CWORD-1}}"
+        case "${{command//$'\n'/ }}" in
+            {entry_commands})
+                COMPREPLY=($(compgen -W "$(rlist __complete entries 2>/dev/null)" -- "$cur"))
+                return 0
+                ;;
+        esac
+    fi
+    _rlist "$@"
+}}
+complete -F _rlist_dynamic -o nosort -o bashdefault -o default rlist
+"#,
+            topic_flags = topic_flags.join("|"),
+            entry_commands = entry_patterns.join("|"),
+        ),
+        Shell::Zsh => format!(
+            r#"
+_rlist_dynamic() {{
+    local i=$((CURRENT - 1))
+    while (( i > 2 )) && [[ "${{words[i]}}" != -* ]]; do (( i-- )); done
+    case "${{words[i]}}" in
+        ({topic_flags})
+            local -a topics=("${{(@f)$(rlist __complete topics 2>/dev/null)}}")
+            compadd -a topics
+            return
+            ;;
+    esac
+    if [[ "${{words[CURRENT]}}" != -* ]]; then
+        case "${{words[2,CURRENT-1]}}" in
+            ({entry_commands})
+                local -a entries=("${{(@f)$(rlist __complete entries 2>/dev/null)}}")
+                compadd -a entries
+                return
+                ;;
+        esac
+    fi
+    _rlist "$@"
+}}
+compdef _rlist_dynamic rlist
+"#,
+            topic_flags = topic_flags.join("|"),
+            entry_commands = entry_patterns.join("|"),
+        ),
+        Shell::Fish => {
+            let mut lines = entry_commands
+                .iter()
+                .map(|levels| {
+                    let seen = levels
+                        .iter()
+                        .map(|names| format!("__fish_seen_subcommand_from {}; and ", names.join(" ")))
+                        .collect::<String>();
+                    format!(
+                        "complete -c rlist -n \"{seen}test (count (commandline -opc)) -eq {}\" -f -a \"(rlist __complete entries)\"",
+                        levels.len() + 1
+                    )
+                })
+                .collect::<Vec<_>>();
+            for flag in topic_flags.iter() {
+                let option = match flag.strip_prefix("--") {
+                    Some(long) => format!("-l {long}"),
+                    None => format!("-s {}", flag.trim_start_matches('-')),
+                };
+                lines.push(format!(
+                    "complete -c rlist {option} -r -f -a \"(rlist __complete topics)\""
+                ));
+            }
+            format!("\n{}\n", lines.join("\n"))
+        }
+        _ => String::new(),
+    };
+    write!(out, "{dynamic}")?;
+    Ok(())
+}
+
+/// Adds to `flags` the flags (e.g. `--add-topics`, `-t`) of `command` and of its subcommands that take topics
+fn collect_topic_flags(command: &Command, flags: &mut Vec<String>) {
+    for arg in command.get_arguments() {
+        let Some(long) = arg
+            .get_long()
+            .filter(|l| l.ends_with("topics") && arg.get_action().takes_values())
+        else {
+            continue;
+        };
+        let long = format!("--{long}");
+        if !flags.contains(&long) {
+            flags.push(long);
+        }
+        if let Some(short) = arg.get_short().map(|s| format!("-{s}")) {
+            if !flags.contains(&short) {
+                flags.push(short);
+            }
+        }
+    }
+    for subcommand in command.get_subcommands() {
+        collect_topic_flags(subcommand, flags);
+    }
+}
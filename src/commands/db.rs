@@ -0,0 +1,109 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Subcommand;
+use colored::Colorize;
+
+use super::AppContext;
+
+#[derive(Subcommand, Debug)]
+pub enum DbAction {
+    /// Check that the database is intact (e.g. after syncing it between machines), then compact it and update its statistics
+    Check {
+        /// Remove the rows that reference rows that do not exist and the orphan rows, if any are found
+        #[arg(long)]
+        fix: bool,
+
+        /// Only check the database, without running `VACUUM` and `ANALYZE` on it
+        #[arg(long)]
+        no_optimize: bool,
+    },
+}
+
+pub fn run(ctx: &mut AppContext, action: DbAction) -> Result<()> {
+    match action {
+        DbAction::Check { fix, no_optimize } => check(ctx, fix, no_optimize),
+    }
+}
+
+fn check(ctx: &mut AppContext, fix: bool, no_optimize: bool) -> Result<()> {
+    let check = ctx.rlist.check()?;
+
+    let status = |ok: bool| {
+        if ok {
+            "ok".green().to_string()
+        } else {
+            "problems found".bold().red().to_string()
+        }
+    };
+    writeln!(
+        ctx.out,
+        "Integrity check: {}",
+        status(check.integrity_errors.is_empty())
+    )?;
+    for err in check.integrity_errors.iter() {
+        writeln!(ctx.out, "  {err}")?;
+    }
+    writeln!(
+        ctx.out,
+        "Foreign keys: {}",
+        status(check.foreign_key_violations.is_empty())
+    )?;
+    for (table, count) in check.foreign_key_violations.iter() {
+        writeln!(
+            ctx.out,
+            "  {table}: {count} {} referencing rows that do not exist",
+            if *count == 1 { "row" } else { "rows" }
+        )?;
+    }
+    writeln!(ctx.out, "Orphan rows: {}", status(check.orphans.is_empty()))?;
+    for (kind, count) in check.orphans.iter() {
+        writeln!(ctx.out, "  {kind}: {count}")?;
+    }
+
+    if !check.is_repairable() {
+        // Rebuilding a corrupted database could lose even more data
+        return Err(anyhow::anyhow!(
+            "The database file is corrupted: restore a backup with `rlist restore`"
+        ));
+    }
+    if !check.is_ok() {
+        if fix {
+            ctx.rlist.repair()?;
+            writeln!(ctx.out, "Fixed the problems found")?;
+        } else {
+            writeln!(ctx.out, "Run `rlist db check --fix` to fix them")?;
+        }
+    }
+
+    if !no_optimize {
+        let (before, after) = ctx.rlist.optimize()?;
+        writeln!(
+            ctx.out,
+            "Vacuumed and analyzed the database: {} before, {} after",
+            format_size(before),
+            format_size(after)
+        )?;
+    }
+
+    if !check.is_ok() && !fix {
+        return Err(anyhow::anyhow!("Problems were found in the database"));
+    }
+    Ok(())
+}
+
+/// Formats a size in bytes, e.g. `12.3 KiB`
+fn format_size(bytes: i64) -> String {
+    let mut size = bytes as f64;
+    for unit in ["B", "KiB", "MiB"] {
+        if size < 1024.0 {
+            return if unit == "B" {
+                format!("{bytes} B")
+            } else {
+                format!("{size:.1} {unit}")
+            };
+        }
+        size /= 1024.0;
+    }
+    format!("{size:.1} GiB")
+}
@@ -0,0 +1,184 @@
+use std::{collections::HashMap, io::Write, str::FromStr};
+
+use anyhow::Result;
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::{entry::Entry, utils::canonicalize_url};
+
+#[derive(Debug, Clone)]
+pub enum DuplicateCriterion {
+    Url,
+    Name,
+}
+
+impl FromStr for DuplicateCriterion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "url" => Ok(Self::Url),
+            "name" => Ok(Self::Name),
+            other => Err(anyhow::anyhow!("Option \"{other}\" not recognized")),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct DedupeArgs {
+    /// How duplicates are detected. Options are: url (entries whose urls are the same once normalized),
+    /// name (entries with similar names, see `--threshold`)
+    #[arg(long, default_value = "url")]
+    by: DuplicateCriterion,
+
+    /// How similar two names must be for the entries to be considered duplicates, from 0 (anything) to 1 (identical names).
+    /// Only used with `--by name`
+    #[arg(long, default_value_t = 0.85, value_parser = parse_threshold)]
+    threshold: f64,
+
+    /// Merge each group of duplicates into its oldest entry, which gets the topics of the others
+    #[arg(long)]
+    merge: bool,
+
+    /// Do not ask for confirmation before merging each group
+    #[arg(short, long, requires = "merge")]
+    yes: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: DedupeArgs) -> Result<()> {
+    let entries = ctx.rlist.query().run()?;
+    let groups = match args.by {
+        DuplicateCriterion::Url => group_by_url(ctx, entries),
+        DuplicateCriterion::Name => group_by_name(entries, args.threshold),
+    };
+
+    if groups.is_empty() {
+        note!(ctx, "No duplicates were found")?;
+        return Ok(());
+    }
+
+    let mut merged = 0;
+    for (i, group) in groups.iter().enumerate() {
+        note!(ctx, "Group {}:", i + 1)?;
+        for (j, e) in group.iter().enumerate() {
+            if !ctx.porcelain {
+                write!(ctx.out, "{}", if j == 0 { "* " } else { "  " })?;
+            }
+            ctx.print_entry(e, false)?;
+        }
+
+        if args.merge
+            && (args.yes
+                || ctx.confirm(
+                    format!("Merge these entries into {}?", group[0].name.entry_name()),
+                    "Pass --yes to merge the duplicates",
+                )?)
+        {
+            ctx.merge_entries(&group[0], &group[1..])?;
+            merged += 1;
+        }
+        note!(ctx)?;
+    }
+
+    note!(
+        ctx,
+        "Found {} {} of duplicates (the entries marked with * are the oldest ones)",
+        groups.len(),
+        if groups.len() == 1 { "group" } else { "groups" }
+    )?;
+    if args.merge {
+        note!(
+            ctx,
+            "Merged {merged} {}",
+            if merged == 1 { "group" } else { "groups" }
+        )?;
+    }
+    Ok(())
+}
+
+fn parse_threshold(s: &str) -> Result<f64> {
+    let threshold = s.parse::<f64>()?;
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(anyhow::anyhow!("The threshold must be between 0 and 1"));
+    }
+    Ok(threshold)
+}
+
+/// Groups the entries whose names have a similarity (normalized Levenshtein, ignoring case) of at least `threshold`.
+/// Similarity is transitive: if `a` is similar to `b`, and `b` to `c`, then all three end up in the same group.
+/// Only groups with more than one entry are returned, each sorted from the oldest to the newest entry
+fn group_by_name(entries: Vec<Entry>, threshold: f64) -> Vec<Vec<Entry>> {
+    let names = entries
+        .iter()
+        .map(|e| {
+            e.name
+                .to_lowercase()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>();
+
+    // Union-find over the indices of the entries
+    let mut parent = (0..entries.len()).collect::<Vec<_>>();
+    for i in 0..names.len() {
+        for j in i + 1..names.len() {
+            if strsim::normalized_levenshtein(&names[i], &names[j]) >= threshold {
+                let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                parent[rj] = ri;
+            }
+        }
+    }
+
+    let mut groups: Vec<Vec<Entry>> = Vec::new();
+    let mut group_of_root: HashMap<usize, usize> = HashMap::new();
+    for (i, e) in entries.into_iter().enumerate() {
+        let r = root(&mut parent, i);
+        match group_of_root.get(&r) {
+            Some(&g) => groups[g].push(e),
+            None => {
+                group_of_root.insert(r, groups.len());
+                groups.push(vec![e]);
+            }
+        }
+    }
+
+    groups.retain(|g| g.len() > 1);
+    for g in groups.iter_mut() {
+        g.sort_by(|a, b| a.added.cmp(&b.added));
+    }
+    groups
+}
+
+/// Returns the representative of the set of `i` in the union-find forest `parent`
+fn root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Groups the entries whose urls are the same once canonicalized. Only groups with more than one entry are returned,
+/// each sorted from the oldest to the newest entry
+fn group_by_url(ctx: &AppContext, entries: Vec<Entry>) -> Vec<Vec<Entry>> {
+    let mut groups: Vec<Vec<Entry>> = Vec::new();
+    let mut group_of_url: HashMap<String, usize> = HashMap::new();
+    for e in entries {
+        let url = canonicalize_url(&e.url, &ctx.config.url_canonicalization);
+        match group_of_url.get(&url) {
+            Some(&i) => groups[i].push(e),
+            None => {
+                group_of_url.insert(url, groups.len());
+                groups.push(vec![e]);
+            }
+        }
+    }
+
+    groups.retain(|g| g.len() > 1);
+    for g in groups.iter_mut() {
+        g.sort_by(|a, b| a.added.cmp(&b.added));
+    }
+    groups
+}
@@ -0,0 +1,314 @@
+use std::{collections::BTreeMap, io::Write};
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use super::AppContext;
+use rlist::{
+    entry::Entry,
+    hook::Hook,
+    rlist::EditOptions,
+    topic::Topic,
+    utils::{
+        canonicalize_url, edit_in_editor, normalize_name, parse_future_datetime, parse_key_value,
+        validate_url,
+    },
+};
+
+#[derive(Args, Debug)]
+pub struct EditArgs {
+    /// The name of the entry you want to edit
+    #[arg(required_unless_present_any = ["by_url", "id"])]
+    old_name: Option<String>,
+
+    /// The url of the entry you want to edit, to select it instead of by its name (`--url` sets its new url).
+    /// With this option, the only name that can be given is the new name of the entry
+    #[arg(long, conflicts_with = "new_name")]
+    by_url: Option<String>,
+
+    /// The id of the entry you want to edit (shown by `list --long`), to select it instead of by its name.
+    /// With this option, the only name that can be given is the new name of the entry
+    #[arg(long, conflicts_with_all = ["new_name", "by_url"])]
+    id: Option<i64>,
+
+    /// The new name of the entry
+    new_name: Option<String>,
+
+    /// The new author of the entry
+    #[arg(short, long)]
+    author: Option<String>,
+
+    /// The new url of the entry
+    #[arg(long)]
+    url: Option<String>,
+
+    /// Set the new url even if it is not a valid url
+    #[arg(long, requires = "url")]
+    allow_invalid_url: bool,
+
+    /// The new estimated reading time of the entry, in minutes
+    #[arg(short, long, value_parser = clap::value_parser!(i64).range(1..))]
+    length: Option<i64>,
+
+    /// Sets the topics of the entry to this list.
+    /// Takes precedence over `--add-topics`. `--topics a b c` is the same as `--clear-topics --add-topics a b c`
+    #[arg(short, long, num_args = 1..)]
+    topics: Option<Vec<String>>,
+
+    /// The topics you want to add to the entry
+    #[arg(long, num_args = 1..)]
+    add_topics: Option<Vec<String>>,
+
+    /// If set, remove the entry from all of the topics
+    #[arg(long)]
+    clear_topics: bool,
+
+    /// The list of topics you want the entry to be removed from
+    #[arg(long, num_args = 1..)]
+    remove_topics: Option<Vec<String>>,
+
+    /// Metadata to set on the entry, as `key=value` pairs. Keys that are already set are overwritten
+    #[arg(short, long, num_args = 1.., value_parser = parse_key_value)]
+    meta: Vec<(String, String)>,
+
+    /// The keys of the metadata you want to remove from the entry
+    #[arg(long, num_args = 1..)]
+    remove_meta: Vec<String>,
+
+    /// The new due date of the entry: a datetime, a day (e.g. `friday`, `tomorrow`) or a duration from now (e.g. `2w`)
+    #[arg(long, value_parser = |s: &str| parse_future_datetime(s))]
+    due: Option<String>,
+
+    /// Remove the due date of the entry
+    #[arg(long, conflicts_with = "due")]
+    clear_due: bool,
+
+    /// Edit the entry as YAML in your editor (`$VISUAL` or `$EDITOR`), instead of with the other options
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "new_name", "author", "url", "length", "topics", "add_topics", "clear_topics",
+            "remove_topics", "meta", "remove_meta", "due", "clear_due"
+        ]
+    )]
+    editor: bool,
+}
+
+/// The fields of an entry that can be changed with `--editor`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct EditableEntry {
+    name: String,
+    url: String,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    topics: Vec<String>,
+    /// The estimated reading time, in minutes
+    #[serde(default)]
+    length: Option<i64>,
+    #[serde(default)]
+    due: Option<String>,
+    #[serde(default)]
+    meta: BTreeMap<String, String>,
+}
+
+impl From<&Entry> for EditableEntry {
+    fn from(entry: &Entry) -> Self {
+        Self {
+            name: entry.name.clone(),
+            url: entry.url.clone(),
+            author: entry.author.clone(),
+            topics: entry.topics.clone(),
+            length: entry.length,
+            due: entry.due.clone(),
+            meta: entry.meta.clone(),
+        }
+    }
+}
+
+/// The comment at the top of the file opened with `--editor`
+const EDITOR_HEADER: &str = "# Edit the entry, then save and close the file to apply the changes.
+# Set a field to null (or remove it) to clear it. The due date can also be a day (e.g. `friday`) or a duration from now (e.g. `2w`)
+";
+
+pub fn run(ctx: &mut AppContext, args: EditArgs) -> Result<()> {
+    let (old_name, new_name) = match (args.old_name, args.by_url, args.id) {
+        (old_name, Some(url), _) => (ctx.get_by_url(&url)?.name, old_name),
+        (old_name, None, Some(id)) => (ctx.rlist.get_by_id(id)?.name, old_name),
+        (Some(old_name), None, None) => (ctx.resolve_name(old_name)?, args.new_name),
+        (None, None, None) => return Err(anyhow::anyhow!("No entry was selected")),
+    };
+    if args.editor {
+        if new_name.is_some() {
+            return Err(anyhow::anyhow!(
+                "The new name cannot be given with --editor"
+            ));
+        }
+        return run_with_editor(ctx, old_name);
+    }
+    if ctx.has_hook(Hook::PreEdit) {
+        let old_entry = ctx.rlist.get_by_name(&old_name)?;
+        ctx.run_hook(Hook::PreEdit, &old_entry)?;
+    }
+    let url = match args.url {
+        Some(url) if !args.allow_invalid_url => Some(validate_url(&url)?),
+        url => url,
+    };
+    let new_entry = ctx.rlist.edit(
+        old_name,
+        EditOptions {
+            new_name,
+            author: args.author,
+            url: url.map(|url| canonicalize_url(&url, &ctx.config.url_canonicalization)),
+            length: args.length,
+            topics: args.topics.map(|t| ctx.config.resolve_topic_aliases(t)),
+            add_topics: args.add_topics.map(|t| ctx.config.resolve_topic_aliases(t)),
+            clear_topics: args.clear_topics,
+            remove_topics: args
+                .remove_topics
+                .map(|t| ctx.config.resolve_topic_aliases(t)),
+            meta: args.meta,
+            remove_meta: args.remove_meta,
+            due: args.due,
+            clear_due: args.clear_due,
+            ..Default::default()
+        },
+    )?;
+    print_edited(ctx, &new_entry)
+}
+
+fn print_edited(ctx: &mut AppContext, new_entry: &Entry) -> Result<()> {
+    note!(ctx, "Here's the edited entry:")?;
+    ctx.print_entry(new_entry, true)?;
+    note!(ctx)?;
+    ctx.run_hook(Hook::PostEdit, new_entry)?;
+    Ok(())
+}
+
+/// Opens the entry with name = `name` in the editor and applies the changes made to it.
+/// If the changes are invalid, the user is asked whether to fix them in the editor
+fn run_with_editor(ctx: &mut AppContext, name: String) -> Result<()> {
+    if ctx.no_input {
+        return Err(anyhow::anyhow!(
+            "Editing an entry in your editor requires input, but input is disabled. Pass the changes as options of `rlist edit` instead"
+        ));
+    }
+    let entry = ctx.rlist.get_by_name(&name)?;
+    ctx.run_hook(Hook::PreEdit, &entry)?;
+
+    let mut content = format!(
+        "{EDITOR_HEADER}{}",
+        serde_yaml::to_string(&EditableEntry::from(&entry))?
+    );
+    loop {
+        content = edit_in_editor(&content, "entry.yml")?;
+        let res = diff(ctx, &entry, &content).and_then(|opts| {
+            if opts.is_empty() {
+                Ok(None)
+            } else {
+                ctx.rlist.edit(entry.name.clone(), opts).map(Some)
+            }
+        });
+        match res {
+            Ok(Some(new_entry)) => return print_edited(ctx, &new_entry),
+            Ok(None) => {
+                note!(ctx, "The entry was not changed")?;
+                return Ok(());
+            }
+            Err(err) => {
+                eprintln!("{}: {err:#}", "Error".bold().red());
+                if !ctx.confirm(
+                    "Do you want to fix the entry in the editor?",
+                    "The entry was not changed",
+                )? {
+                    return Err(anyhow::anyhow!("The entry was not changed"));
+                }
+            }
+        }
+    }
+}
+
+/// Returns the changes between `entry` and its edited version `content`, validating them
+fn diff(ctx: &AppContext, entry: &Entry, content: &str) -> Result<EditOptions> {
+    let edited: EditableEntry = serde_yaml::from_str(content)
+        .map_err(|err| anyhow::anyhow!("The edited entry is not valid: {err}"))?;
+    let mut opts = EditOptions::default();
+
+    let name = normalize_name(&edited.name);
+    if name.is_empty() {
+        return Err(anyhow::anyhow!("The name of the entry cannot be empty"));
+    }
+    if name != entry.name {
+        opts.new_name = Some(name);
+    }
+
+    if edited.url.trim() != entry.url {
+        let url = validate_url(&edited.url)?;
+        opts.url = Some(canonicalize_url(&url, &ctx.config.url_canonicalization));
+    }
+
+    let author = edited
+        .author
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty());
+    if author != entry.author {
+        opts.clear_author = author.is_none();
+        opts.author = author;
+    }
+
+    if edited.length != entry.length {
+        if edited.length.is_some_and(|l| l < 1) {
+            return Err(anyhow::anyhow!(
+                "The length of the entry must be at least 1 minute"
+            ));
+        }
+        opts.clear_length = edited.length.is_none();
+        opts.length = edited.length;
+    }
+
+    let mut topics = ctx
+        .config
+        .resolve_topic_aliases(edited.topics)
+        .iter()
+        .map(Topic::normalize)
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>();
+    topics.sort();
+    topics.dedup();
+    let mut old_topics = entry.topics.clone();
+    old_topics.sort();
+    if topics != old_topics {
+        opts.topics = Some(topics);
+    }
+
+    let due = edited
+        .due
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty());
+    if due != entry.due {
+        match due {
+            Some(due) => opts.due = Some(parse_future_datetime(due)?),
+            None => opts.clear_due = true,
+        }
+    }
+
+    if edited.meta.keys().any(|k| k.trim().is_empty()) {
+        return Err(anyhow::anyhow!("Metadata keys cannot be empty"));
+    }
+    opts.remove_meta = entry
+        .meta
+        .keys()
+        .filter(|k| !edited.meta.contains_key(*k))
+        .cloned()
+        .collect();
+    opts.meta = edited
+        .meta
+        .into_iter()
+        .filter(|(k, v)| entry.meta.get(k) != Some(v))
+        .collect();
+
+    Ok(opts)
+}
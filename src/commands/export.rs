@@ -0,0 +1,167 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+
+use super::{list::FilterArgs, AppContext};
+use rlist::{entry::Entry, rlist::QueryOptions, utils::parse_past_datetime};
+
+/// The name of the file that gets the entries without topics, when splitting the export by topic
+const NO_TOPIC_FILE: &str = "_no_topic.yml";
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitBy {
+    /// One `<topic>.yml` file per topic, in which each entry appears once for each of its topics.
+    /// Nested topics go to subdirectories, such as `rust/async.yml`
+    Topic,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// The file to export your reading list to, or `-` to write it to stdout. With `--split-by`, the directory to write the files to
+    path: PathBuf,
+
+    /// Split the export into several files
+    #[arg(long)]
+    split_by: Option<SplitBy>,
+
+    /// Only export the entries added or changed after this datetime, or within this duration (e.g. `1d` for the last day).
+    /// Entries removed since then are not part of the export
+    #[arg(long)]
+    since: Option<String>,
+
+    /// Only export the entries whose name contains this substring
+    #[arg(long)]
+    query: Option<String>,
+
+    /// Only export the entries whose url contains this substring
+    #[arg(long)]
+    url: Option<String>,
+
+    /// Only export the entries that match these filters, which work like the ones of `list`.
+    /// The snoozed and the archived entries are exported as well, unless `--snoozed`, `--archived` or `--include-*` say otherwise
+    #[command(flatten)]
+    filters: FilterArgs,
+}
+
+pub fn run(ctx: &mut AppContext, args: ExportArgs) -> Result<()> {
+    let path = args.path;
+    let entries = if args.query.is_none()
+        && args.url.is_none()
+        && args.since.is_none()
+        && args.filters.is_empty()
+    {
+        ctx.rlist.dump_all()?
+    } else {
+        let picks_hidden = args.filters.picks_hidden();
+        let mut opts = QueryOptions {
+            query: args.query,
+            url: args.url,
+            changed_since: args.since.map(parse_past_datetime).transpose()?,
+            ..args.filters.query_options(ctx)?
+        };
+        // Unlike `list`, the export includes the snoozed and the archived entries unless the user asked otherwise
+        if !picks_hidden {
+            opts.snoozed = None;
+            opts.archived = None;
+        }
+        ctx.rlist.query_with(&opts)?
+    };
+    if args.split_by == Some(SplitBy::Topic) {
+        return export_by_topic(ctx, &path, &entries);
+    }
+
+    let content = serde_yaml::to_string(&entries)
+        .context("Could not export the content of your reading list")?;
+    if path == Path::new("-") {
+        write!(ctx.out, "{content}")?;
+        return Ok(());
+    }
+
+    fs::create_dir_all(
+        Path::new(&path)
+            .parent()
+            .ok_or(anyhow::anyhow!("Could not create the export file"))?,
+    )?;
+    fs::write(&path, content).context("Could not export the content of your reading list")?;
+
+    note!(
+        ctx,
+        "Exported {count} {word}{destination}",
+        count = entries.len(),
+        word = if entries.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        destination = path
+            .to_str()
+            .map(|p| format!(" to {p}"))
+            .unwrap_or_default()
+    )?;
+    Ok(())
+}
+
+/// Writes the entries of each topic to `dir/<topic>.yml`, and the entries without topics to `dir/_no_topic.yml`
+fn export_by_topic(ctx: &mut AppContext, dir: &Path, entries: &[Entry]) -> Result<()> {
+    if dir == Path::new("-") {
+        return Err(anyhow::anyhow!(
+            "Cannot write to stdout with --split-by, since the export is split into several files"
+        ));
+    }
+
+    let mut files: BTreeMap<PathBuf, Vec<&Entry>> = BTreeMap::new();
+    for e in entries.iter() {
+        if e.topics.is_empty() {
+            files
+                .entry(PathBuf::from(NO_TOPIC_FILE))
+                .or_default()
+                .push(e);
+        }
+        for topic in e.topics.iter() {
+            // Topics are nested with `/`, which also nests their files in subdirectories
+            let parts = topic
+                .split('/')
+                .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+                .collect::<Vec<_>>();
+            let Some((last, parents)) = parts.split_last() else {
+                continue;
+            };
+            let mut file = parents.iter().collect::<PathBuf>();
+            file.push(format!("{last}.yml"));
+            files.entry(file).or_default().push(e);
+        }
+    }
+
+    for (file, entries) in files.iter() {
+        let path = dir.join(file);
+        fs::create_dir_all(
+            path.parent()
+                .ok_or(anyhow::anyhow!("Could not create the export directory"))?,
+        )?;
+        let content = serde_yaml::to_string(entries)
+            .context("Could not export the content of your reading list")?;
+        fs::write(&path, content)
+            .with_context(|| format!("Could not write the export file {}", path.display()))?;
+    }
+
+    note!(
+        ctx,
+        "Exported {} {} to {} {} in {}",
+        entries.len(),
+        if entries.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        files.len(),
+        if files.len() == 1 { "file" } else { "files" },
+        dir.display()
+    )?;
+    Ok(())
+}
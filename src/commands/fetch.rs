@@ -0,0 +1,74 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::web::Page;
+
+#[derive(Args, Debug)]
+pub struct FetchArgs {
+    /// The name of the entry
+    #[arg(
+        required_unless_present = "all_missing",
+        conflicts_with = "all_missing"
+    )]
+    name: Option<String>,
+
+    /// Fetch all of the entries that are missing an author, a site name or a description
+    #[arg(long)]
+    all_missing: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: FetchArgs) -> Result<()> {
+    if let Some(name) = args.name {
+        let name = ctx.resolve_name(name)?;
+        if !fetch_entry(ctx, &name)? {
+            note!(ctx, "No new details were found for {}", name.entry_name())?;
+        }
+        return Ok(());
+    }
+
+    let names = ctx.rlist.names_missing_details()?;
+    let mut updated = 0;
+    for name in names.iter() {
+        match fetch_entry(ctx, name) {
+            Ok(true) => updated += 1,
+            Ok(false) => {}
+            Err(err) => eprintln!(
+                "{}: {}: {err:#}",
+                "Warning".bold().yellow(),
+                name.entry_name()
+            ),
+        }
+    }
+    note!(
+        ctx,
+        "Updated {updated} of {} {}",
+        names.len(),
+        if names.len() == 1 { "entry" } else { "entries" }
+    )?;
+
+    Ok(())
+}
+
+/// Downloads the page of the entry with name = `name` and fills in its missing details.
+/// Prints the entry if it was updated, and returns whether it was
+fn fetch_entry(ctx: &mut AppContext, name: &str) -> Result<bool> {
+    let url = ctx.rlist.get_by_name(name)?.url;
+    let details = Page::fetch(url)?.details();
+
+    let (entry, changed) = ctx.rlist.fill_details(
+        name,
+        details.author.as_deref(),
+        details.site_name.as_deref(),
+        details.description.as_deref(),
+    )?;
+    if changed {
+        ctx.print_entry(&entry, true)?;
+        note!(ctx)?;
+    }
+    Ok(changed)
+}
@@ -0,0 +1,124 @@
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{Datelike, Days, NaiveDate};
+use clap::Args;
+use colored::Colorize;
+
+use super::AppContext;
+
+/// The colors of the cells, from the days without entries to the busiest ones
+const LEVELS: [(u8, u8, u8); 5] = [
+    (60, 60, 60),
+    (14, 68, 41),
+    (0, 109, 50),
+    (38, 166, 65),
+    (57, 211, 83),
+];
+
+/// The cells of each level when colors are disabled, from the lightest to the densest
+const GLYPHS: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+#[derive(Args, Debug)]
+pub struct HeatmapArgs {
+    /// The year to show. Defaults to the current year
+    #[arg(long)]
+    year: Option<i32>,
+
+    /// Count the entries read on each day, instead of the entries added
+    #[arg(long)]
+    read: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: HeatmapArgs) -> Result<()> {
+    let year = args.year.unwrap_or_else(|| chrono::Local::now().year());
+    let first = NaiveDate::from_ymd_opt(year, 1, 1)
+        .ok_or_else(|| anyhow::anyhow!("Invalid year {year}"))?;
+    let counts = ctx.rlist.activity_per_day(year, args.read)?;
+    let max = counts.values().copied().max().unwrap_or_default();
+    let today = chrono::Local::now().date_naive();
+
+    // Each column is a week, starting on Monday
+    let start = first - Days::new(first.weekday().num_days_from_monday() as u64);
+    let weeks = (0..)
+        .take_while(|w| {
+            start + Days::new(7 * w) <= NaiveDate::from_ymd_opt(year, 12, 31).expect("valid date")
+        })
+        .count();
+
+    let mut month_row = vec![' '; weeks * 2];
+    for month in 1..=12 {
+        let day = NaiveDate::from_ymd_opt(year, month, 1).expect("valid date");
+        let column = ((day - start).num_days() / 7) as usize * 2;
+        // Labels that would overlap with the previous one are skipped
+        if month_row[column.saturating_sub(1)..]
+            .iter()
+            .take(4)
+            .all(|c| *c == ' ')
+        {
+            for (i, c) in MONTHS[month as usize - 1].chars().enumerate() {
+                if let Some(cell) = month_row.get_mut(column + i) {
+                    *cell = c;
+                }
+            }
+        }
+    }
+    writeln!(
+        ctx.out,
+        "    {}",
+        month_row.iter().collect::<String>().trim_end()
+    )?;
+
+    for weekday in 0..7 {
+        let label = match weekday {
+            0 => "Mon",
+            2 => "Wed",
+            4 => "Fri",
+            _ => "",
+        };
+        let mut row = format!("{label:<3} ");
+        for week in 0..weeks {
+            let day = start + Days::new(7 * week as u64 + weekday);
+            // Days outside of the year, or in the future, are left blank
+            if day.year() != year || day > today {
+                row.push_str("  ");
+                continue;
+            }
+            let count = counts.get(&day).copied().unwrap_or_default();
+            row.push_str(&format!("{} ", cell(level(count, max))));
+        }
+        writeln!(ctx.out, "{}", row.trim_end())?;
+    }
+
+    let total = counts.values().sum::<i64>();
+    writeln!(
+        ctx.out,
+        "\n{total} {} {} in {year}    Less {} More",
+        if total == 1 { "entry" } else { "entries" },
+        if args.read { "read" } else { "added" },
+        (0..LEVELS.len()).map(cell).collect::<Vec<_>>().join(" ")
+    )?;
+    Ok(())
+}
+
+/// Returns the index in `LEVELS` of the color of a day with `count` entries, where `max` is the busiest day
+fn level(count: i64, max: i64) -> usize {
+    if count == 0 || max == 0 {
+        return 0;
+    }
+    let busiest = (LEVELS.len() - 1) as i64;
+    ((count * busiest + max - 1) / max) as usize
+}
+
+/// Returns the cell of a day of `level`: a square in its color or, if colors are disabled, a glyph as dense as the level
+fn cell(level: usize) -> String {
+    if !colored::control::SHOULD_COLORIZE.should_colorize() {
+        return GLYPHS[level].to_string();
+    }
+    let (r, g, b) = LEVELS[level];
+    "■".truecolor(r, g, b).to_string()
+}
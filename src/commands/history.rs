@@ -0,0 +1,41 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+use rlist::theme::Themed;
+
+use super::{AppContext, EntrySelector};
+use rlist::utils::sql_string_to_dt;
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+}
+
+pub fn run(ctx: &mut AppContext, args: HistoryArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let entry = ctx.rlist.get_by_name(&name)?;
+    let history = ctx.rlist.history(&name)?;
+
+    let fmt = &ctx.config.datetime_format;
+    writeln!(
+        ctx.out,
+        "{}: added {}",
+        sql_string_to_dt(&entry.added)?.format(fmt),
+        entry.name.entry_name()
+    )?;
+    let show = |v: Option<String>| v.unwrap_or_else(|| "(none)".dimmed().to_string());
+    for record in history {
+        writeln!(
+            ctx.out,
+            "{}: {} {} -> {}",
+            sql_string_to_dt(&record.at)?.format(fmt),
+            record.field.bold(),
+            show(record.old_value),
+            show(record.new_value)
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use clap::{Args, ValueEnum};
+use colored::Colorize;
+use rlist::theme::Themed;
+use serde::Serialize;
+
+use super::AppContext;
+use rlist::{
+    entry::Entry,
+    markdown,
+    rlist::{ImportOptions, ImportOutcome, ImportResult, OnConflict},
+    utils::{canonicalize_url, now},
+    web,
+};
+use url::Url;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// A file written by `rlist export`
+    Yaml,
+    /// The `[title](url)` links of a markdown document. The `##` headings they are under become their topics
+    Markdown,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// The file to import, the http(s) url of a file to download and import, or `-` to read it from stdin
+    #[arg(value_name = "PATH|URL")]
+    path: PathBuf,
+
+    /// The format of the file. By default, `.md` and `.markdown` files (or urls) are imported as markdown, and all other files as yaml
+    #[arg(short, long)]
+    format: Option<ImportFormat>,
+
+    /// What to do with the entries whose name or url is already in your reading list. Options are: skip, overwrite,
+    /// merge (add their topics and metadata to the existing entry), rename (import them as `name (2)`)
+    #[arg(long, default_value = "skip")]
+    on_conflict: OnConflict,
+
+    /// Add the topics of the entries that are already in your reading list to the existing entries, even when they are skipped.
+    /// With `--on-conflict overwrite`, the existing entries keep their topics too
+    #[arg(long)]
+    merge_topics: bool,
+
+    /// Only print what would be imported, merged and skipped (and why), without changing your reading list
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Write the entries that are skipped, along with the reason, to this file: as JSON if its extension is `.json`,
+    /// and as YAML otherwise. Once the skipped entries have been fixed, the report can be imported again
+    #[arg(long, value_name = "PATH")]
+    report: Option<PathBuf>,
+}
+
+/// An entry that was skipped by the import, as written to the report
+#[derive(Serialize)]
+struct SkippedEntry<'a> {
+    #[serde(flatten)]
+    entry: &'a Entry,
+    /// Why the entry was skipped. The field is ignored when the report is imported
+    skip_reason: &'a str,
+}
+
+pub fn run(ctx: &mut AppContext, args: ImportArgs) -> Result<()> {
+    let path = args.path;
+    let url = path
+        .to_str()
+        .filter(|p| p.starts_with("http://") || p.starts_with("https://"))
+        .map(Url::parse)
+        .transpose()?;
+    // The format of a url is inferred from the extension of its path, ignoring the query
+    let extension = match url.as_ref() {
+        Some(url) => Path::new(url.path()).extension(),
+        None => path.extension(),
+    };
+    let format = args
+        .format
+        .unwrap_or_else(|| match extension.and_then(|ext| ext.to_str()) {
+            Some("md" | "markdown") => ImportFormat::Markdown,
+            _ => ImportFormat::Yaml,
+        });
+    let from_stdin = path == Path::new("-");
+    let content = match url.as_ref() {
+        Some(url) => web::fetch_text(url.as_str())?,
+        None if from_stdin => {
+            io::read_to_string(io::stdin()).context("Could not read the reading list from stdin")?
+        }
+        None => fs::read_to_string(&path).context("Could not import reading list from file")?,
+    };
+    let mut entries: Vec<Entry> = match format {
+        ImportFormat::Yaml => {
+            serde_yaml::from_str(&content).context("Could not import reading list from file")?
+        }
+        ImportFormat::Markdown => {
+            let added = now();
+            markdown::parse_links(&content)
+                .into_iter()
+                .map(|link| {
+                    Entry::new(
+                        link.title,
+                        link.url,
+                        None,
+                        link.topic.into_iter().collect(),
+                        Some(added.clone()),
+                    )
+                })
+                .collect()
+        }
+    };
+    for e in entries.iter_mut() {
+        e.url = canonicalize_url(&e.url, &ctx.config.url_canonicalization);
+        let rule_topics = ctx.config.rule_topics(&e.url, &e.topics);
+        e.topics.extend(rule_topics);
+    }
+    let opts = ImportOptions {
+        on_conflict: args.on_conflict,
+        merge_topics: args.merge_topics,
+        dry_run: args.dry_run,
+    };
+    let results = ctx.rlist.import(entries, &opts)?;
+
+    let dry_run = args.dry_run;
+    for r in results.iter() {
+        let name = r.entry.name.entry_name();
+        match &r.outcome {
+            ImportOutcome::Created if dry_run => {
+                note!(ctx, "{} {name}", verb(dry_run, "Imported", "import"))?
+            }
+            ImportOutcome::Created => {}
+            ImportOutcome::Overwritten(existing) => note!(
+                ctx,
+                "{} {}",
+                verb(dry_run, "Overwrote", "overwrite"),
+                both_names(existing, &r.entry.name, "with")
+            )?,
+            ImportOutcome::Merged(existing) => note!(
+                ctx,
+                "{} {}",
+                verb(dry_run, "Merged", "merge"),
+                both_names(&r.entry.name, existing, "into")
+            )?,
+            ImportOutcome::TopicsMerged(existing) => note!(
+                ctx,
+                "{} {name}, but {} its topics to {}",
+                verb(dry_run, "Skipped", "skip"),
+                if dry_run { "add" } else { "added" },
+                existing.entry_name()
+            )?,
+            ImportOutcome::Renamed(new_name) => note!(
+                ctx,
+                "{} {name} as {}",
+                verb(dry_run, "Imported", "import"),
+                new_name.entry_name()
+            )?,
+            ImportOutcome::Skipped(reason) if dry_run => {
+                note!(ctx, "{} {name}: {reason}", verb(dry_run, "Skipped", "skip"))?
+            }
+            ImportOutcome::Skipped(reason) => {
+                eprintln!("{}: skipped {name}: {reason}", "Warning".bold().yellow())
+            }
+        }
+    }
+    let imported_count = results
+        .iter()
+        .filter(|r| {
+            !matches!(
+                r.outcome,
+                ImportOutcome::Skipped(_) | ImportOutcome::TopicsMerged(_)
+            )
+        })
+        .count();
+    note!(
+        ctx,
+        "{} {imported_count} {word}{source}{summary}",
+        verb(dry_run, "Imported", "import"),
+        word = if imported_count == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        source = if from_stdin {
+            " from stdin".to_string()
+        } else {
+            path.to_str()
+                .map(|p| format!(" from {p}"))
+                .unwrap_or_default()
+        },
+        summary = summary(&results),
+    )?;
+
+    if let Some(report) = args.report {
+        write_report(ctx, &report, &results)?;
+    }
+    Ok(())
+}
+
+/// Writes the entries that were skipped to the file at `path`, as JSON or YAML depending on its extension
+fn write_report(ctx: &mut AppContext, path: &Path, results: &[ImportResult]) -> Result<()> {
+    let skipped = results
+        .iter()
+        .filter_map(|r| match &r.outcome {
+            ImportOutcome::Skipped(reason) => Some(SkippedEntry {
+                entry: &r.entry,
+                skip_reason: reason,
+            }),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let content = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::to_string_pretty(&skipped)?,
+        _ => serde_yaml::to_string(&skipped)?,
+    };
+    fs::write(path, content).context("Could not write the import report")?;
+    note!(
+        ctx,
+        "Wrote the {} skipped {} to {}",
+        skipped.len(),
+        if skipped.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        path.display()
+    )?;
+    Ok(())
+}
+
+/// Returns `past` (such as `Imported`), or `Would present` (such as `Would import`) for a dry run
+fn verb(dry_run: bool, past: &str, present: &str) -> String {
+    if dry_run {
+        format!("Would {present}")
+    } else {
+        past.to_string()
+    }
+}
+
+/// Returns `first preposition second`, such as `a into b`, or just `first` if the two names are the same
+fn both_names(first: &str, second: &str, preposition: &str) -> String {
+    let first_colored = first.entry_name();
+    if first == second {
+        first_colored.to_string()
+    } else {
+        format!("{first_colored} {preposition} {}", second.entry_name())
+    }
+}
+
+/// Returns how many entries had each outcome other than being created, such as ` (2 merged, 1 skipped)`
+fn summary(results: &[ImportResult]) -> String {
+    let mut counts = [
+        ("overwritten", 0),
+        ("merged", 0),
+        ("renamed", 0),
+        ("skipped", 0),
+        ("skipped with their topics merged", 0),
+    ];
+    for r in results.iter() {
+        let i = match r.outcome {
+            ImportOutcome::Created => continue,
+            ImportOutcome::Overwritten(_) => 0,
+            ImportOutcome::Merged(_) => 1,
+            ImportOutcome::Renamed(_) => 2,
+            ImportOutcome::Skipped(_) => 3,
+            ImportOutcome::TopicsMerged(_) => 4,
+        };
+        counts[i].1 += 1;
+    }
+    let counts = counts
+        .iter()
+        .filter(|(_label, n)| *n > 0)
+        .map(|(label, n)| format!("{n} {label}"))
+        .collect::<Vec<_>>();
+    if counts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", counts.join(", "))
+    }
+}
@@ -0,0 +1,265 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::{is_broken_pipe, write_entry, AppContext};
+use rlist::rlist::{OrderBy, QueryOptions};
+use rlist::utils::{
+    parse_future_datetime, parse_key_maybe_value, parse_past_datetime, parse_past_datetime_end,
+};
+
+#[derive(Args, Debug)]
+pub struct ListArgs {
+    /// A substring that the name of the entries must contain
+    query: Option<String>,
+
+    /// Only show the entries that have urls that contain this substring
+    #[arg(long)]
+    url: Option<String>,
+
+    #[command(flatten)]
+    filters: FilterArgs,
+
+    /// If set, the result will also show the `added` date and the topics for each entry
+    #[arg(short, long)]
+    long: bool,
+
+    /// Only show the name and the url of each entry, even if `long` is set in the `list` section of your config
+    #[arg(long, conflicts_with = "long")]
+    short: bool,
+
+    /// The attribute used to sort the entries. Options are: name, author, url, added, modified, length, opens, due.
+    /// Defaults to the `sort_by` of the `list` section of your config
+    #[arg(short, long)]
+    sort_by: Option<OrderBy>,
+
+    /// Whether to sort in ascending or descending order. Aliases: `--descending`
+    #[arg(short, long, aliases=&["descending"])]
+    desc: bool,
+
+    /// Sort in ascending order, even if `desc` is set in the `list` section of your config. Aliases: `--ascending`
+    #[arg(long, aliases=&["ascending"], conflicts_with = "desc")]
+    asc: bool,
+
+    /// If set, only print the number of entries that match the query
+    #[arg(short, long, conflicts_with = "long")]
+    count: bool,
+}
+
+/// The options that select entries from the reading list, shared by the commands that act on the entries matching a query
+#[derive(Args, Debug)]
+pub struct FilterArgs {
+    /// Only match the entries that are in all of these topics
+    #[arg(short, long, num_args = 1..)]
+    topics: Option<Vec<String>>,
+
+    /// If set, match all of the entries that are in at least one of the topics specified with `--topics`, instead of in all of them
+    #[arg(long)]
+    or: bool,
+
+    /// Only match the entries that have an author name that contains this substring
+    #[arg(short, long)]
+    author: Option<String>,
+
+    /// Match the query, `--author` and `--url` as SQL LIKE patterns that must match the whole value
+    /// (`%` matches any text and `_` any single character), instead of as substrings
+    #[arg(long)]
+    like: bool,
+
+    /// Only match the entries whose url belongs to this domain (or to one of its subdomains)
+    #[arg(long)]
+    domain: Option<String>,
+
+    /// Only match the entries whose saved snapshot (see `rlist save`) contains all of these words
+    #[arg(long)]
+    content: Option<String>,
+
+    /// Only match the entries that take at most this many minutes to read. Entries without a length are excluded
+    #[arg(long)]
+    max_length: Option<i64>,
+
+    /// Only match the entries added after this datetime, day (e.g. `yesterday` or `last monday`) or long ago (e.g. `2w` or `3 days ago`)
+    #[arg(long)]
+    from: Option<String>,
+
+    /// Only match the entries added before this datetime, the end of this day or period (e.g. `today` or `last month`) or long ago (e.g. `1y`)
+    #[arg(long)]
+    to: Option<String>,
+
+    /// Only match the entries last changed after this datetime, day or long ago, like `--from`
+    #[arg(long)]
+    modified_from: Option<String>,
+
+    /// Only match the entries last changed before this datetime, day or long ago, like `--to`
+    #[arg(long)]
+    modified_to: Option<String>,
+
+    /// Only match the entries that are currently snoozed
+    #[arg(long)]
+    snoozed: bool,
+
+    /// Also match the entries that are currently snoozed, which are skipped by default
+    #[arg(long, conflicts_with = "snoozed")]
+    include_snoozed: bool,
+
+    /// Only match the entries that are archived
+    #[arg(long)]
+    archived: bool,
+
+    /// Also match the entries that are archived, which are skipped by default
+    #[arg(long, conflicts_with = "archived")]
+    include_archived: bool,
+
+    /// Only match the entries that were marked as read
+    #[arg(long)]
+    read: bool,
+
+    /// Only match the entries that were not marked as read yet
+    #[arg(long, conflicts_with = "read")]
+    unread: bool,
+
+    /// Only match the entries read after this datetime, day or period (e.g. `last month`), or within this duration (e.g. `30d` for the last 30 days)
+    #[arg(long)]
+    read_after: Option<String>,
+
+    /// Only match the entries read before this datetime, the end of this day or period (e.g. `yesterday`), or before this long ago (e.g. `1y`)
+    #[arg(long)]
+    read_before: Option<String>,
+
+    /// Only match the entries due before this datetime, day (e.g. `friday`) or duration from now (e.g. `2w`)
+    #[arg(long, value_parser = |s: &str| parse_future_datetime(s))]
+    due_before: Option<String>,
+
+    /// Only match the entries whose due date has passed
+    #[arg(long)]
+    overdue: bool,
+
+    /// Only match the entries that have all of these metadata. Use `key=value` to match a value, or just `key` to match any value
+    #[arg(short, long, num_args = 1.., value_parser = parse_key_maybe_value)]
+    meta: Vec<(String, Option<String>)>,
+}
+
+impl FilterArgs {
+    /// Returns whether none of the filters is set. `--or` and `--like` only change how the other filters work,
+    /// so they alone do not count
+    pub fn is_empty(&self) -> bool {
+        !self.narrows() && !self.picks_hidden()
+    }
+
+    /// Returns whether any of the filters that narrow down the entries is set. `--or`, `--like` and the `--include-*` flags
+    /// only change how the other filters work, so they alone still match every entry
+    pub fn narrows(&self) -> bool {
+        self.topics.is_some()
+            || self.author.is_some()
+            || self.domain.is_some()
+            || self.content.is_some()
+            || self.max_length.is_some()
+            || self.from.is_some()
+            || self.to.is_some()
+            || self.modified_from.is_some()
+            || self.modified_to.is_some()
+            || self.snoozed
+            || self.archived
+            || self.read
+            || self.unread
+            || self.read_after.is_some()
+            || self.read_before.is_some()
+            || self.due_before.is_some()
+            || self.overdue
+            || !self.meta.is_empty()
+    }
+
+    /// Returns whether any of `--snoozed`, `--archived` and the `--include-*` flags is set, that is whether the user chose
+    /// how the snoozed and the archived entries are matched
+    pub fn picks_hidden(&self) -> bool {
+        self.snoozed || self.include_snoozed || self.archived || self.include_archived
+    }
+
+    /// Returns the query selecting the entries that match the filters. The name and the url of the entries are not filtered,
+    /// since each command takes their substrings in its own way
+    pub fn query_options(self, ctx: &AppContext) -> Result<QueryOptions> {
+        Ok(QueryOptions {
+            topics: self.topics.map(|t| ctx.config.resolve_topic_aliases(t)),
+            or: self.or,
+            author: self.author,
+            like: self.like,
+            domain: self.domain,
+            from: self.from.map(parse_past_datetime).transpose()?,
+            to: self.to.map(parse_past_datetime_end).transpose()?,
+            modified_from: self.modified_from.map(parse_past_datetime).transpose()?,
+            modified_to: self.modified_to.map(parse_past_datetime_end).transpose()?,
+            max_length: self.max_length,
+            content: self.content,
+            snoozed: if self.include_snoozed {
+                None
+            } else {
+                Some(self.snoozed)
+            },
+            archived: if self.include_archived {
+                None
+            } else {
+                Some(self.archived)
+            },
+            read: match (self.read, self.unread) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            },
+            read_after: self.read_after.map(parse_past_datetime).transpose()?,
+            due_before: self.due_before,
+            overdue: self.overdue,
+            read_before: self.read_before.map(parse_past_datetime_end).transpose()?,
+            meta: self.meta,
+            ..Default::default()
+        })
+    }
+}
+
+pub fn run(ctx: &mut AppContext, args: ListArgs) -> Result<()> {
+    // The flags that are not given fall back to the defaults in the config
+    let defaults = &ctx.config.list;
+    let long = args.long || (defaults.long && !args.short);
+    let opts = QueryOptions {
+        query: args.query,
+        url: args.url,
+        sort_by: args.sort_by.or_else(|| defaults.sort_by.clone()),
+        desc: args.desc || (defaults.desc && !args.asc),
+        ..args.filters.query_options(ctx)?
+    };
+
+    if args.count {
+        writeln!(ctx.out, "{}", ctx.rlist.count(&opts)?)?;
+        return Ok(());
+    }
+
+    // The entries are printed as they are read, so that long lists are not buffered in memory
+    let mut count = 0;
+    for e in ctx.rlist.query_iter(&opts)? {
+        let res = write_entry(
+            &mut ctx.out,
+            &e?,
+            long,
+            ctx.porcelain,
+            &ctx.config.datetime_format,
+        );
+        match res {
+            Err(e) if is_broken_pipe(&e) => return Err(e),
+            Err(e) => eprintln!("{}", e),
+            Ok(()) => {}
+        }
+        if !ctx.porcelain {
+            writeln!(ctx.out)?;
+        }
+        count += 1;
+    }
+
+    if count > 0 {
+        note!(
+            ctx,
+            "A total of {count} {} matched your query",
+            if count == 1 { "entry" } else { "entries" }
+        )?;
+    }
+    Ok(())
+}
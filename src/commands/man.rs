@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Command, CommandFactory};
+use clap_mangen::Man;
+
+/// Prints the man page to `out`. It does not need the reading list, so it works even before one is set up
+pub fn run(out: &mut dyn Write) -> Result<()> {
+    let mut command = crate::Args::command().disable_help_subcommand(true);
+    // Building the command sets the full name (e.g. `rlist topics rename`) of every subcommand
+    command.build();
+
+    // The subcommands are documented in the same page, instead of in a page each as `Man::render` expects
+    let man = Man::new(command.clone());
+    let mut page = Vec::new();
+    man.render_title(&mut page)?;
+    man.render_name_section(&mut page)?;
+    man.render_synopsis_section(&mut page)?;
+    man.render_description_section(&mut page)?;
+    man.render_options_section(&mut page)?;
+    render_subcommands(&command, &mut page)?;
+    man.render_version_section(&mut page)?;
+    man.render_authors_section(&mut page)?;
+    out.write_all(&page)?;
+    Ok(())
+}
+
+/// Renders a section with the synopsis, the description and the options of each of the (visible) subcommands of `command`,
+/// and of their subcommands
+fn render_subcommands(command: &Command, page: &mut Vec<u8>) -> Result<()> {
+    for subcommand in command.get_subcommands().filter(|s| !s.is_hide_set()) {
+        let name = subcommand
+            .get_bin_name()
+            .unwrap_or_else(|| subcommand.get_name());
+        writeln!(page, ".SH \"{}\"", name.to_uppercase())?;
+
+        let man = Man::new(subcommand.clone());
+        let mut section = Vec::new();
+        man.render_synopsis_section(&mut section)?;
+        man.render_description_section(&mut section)?;
+        if subcommand.get_arguments().any(|a| !a.is_hide_set()) {
+            man.render_options_section(&mut section)?;
+        }
+        // The sections of the subcommand become subsections of its own section
+        let section = String::from_utf8(section)?.replace(".SH ", ".SS ");
+        page.extend_from_slice(section.as_bytes());
+
+        render_subcommands(subcommand, page)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::{AppContext, EntrySelector};
+
+#[derive(Args, Debug)]
+pub struct MarkReadArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+
+    /// Mark the entry as unread again
+    #[arg(long)]
+    undo: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: MarkReadArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let entry = ctx.rlist.set_read(name, !args.undo)?;
+    note!(
+        ctx,
+        "{}",
+        if args.undo {
+            "Marked entry as unread:"
+        } else {
+            "Marked entry as read:"
+        }
+    )?;
+    ctx.print_entry(&entry, true)?;
+    Ok(())
+}
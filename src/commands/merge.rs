@@ -0,0 +1,38 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::AppContext;
+
+#[derive(Args, Debug)]
+pub struct MergeArgs {
+    /// The name of the entry to keep
+    keep: String,
+
+    /// The names of the duplicate entries, which are removed once merged
+    #[arg(required = true)]
+    remove: Vec<String>,
+}
+
+pub fn run(ctx: &mut AppContext, args: MergeArgs) -> Result<()> {
+    let keep = ctx.rlist.get_by_name(ctx.resolve_name(args.keep)?)?;
+    let remove = args
+        .remove
+        .into_iter()
+        .map(|name| ctx.rlist.get_by_name(ctx.resolve_name(name)?))
+        .collect::<Result<Vec<_>>>()?;
+    let entry = ctx.merge_entries(&keep, &remove)?;
+    note!(
+        ctx,
+        "Merged {} {} into:",
+        remove.len(),
+        if remove.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        }
+    )?;
+    ctx.print_entry(&entry, true)?;
+    Ok(())
+}
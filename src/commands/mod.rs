@@ -0,0 +1,473 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::{Args, Subcommand, ValueEnum};
+
+use colored::Colorize;
+use rlist::theme::Themed;
+
+use rlist::{
+    config::Config,
+    entry::Entry,
+    error::{Entity, ErrorCode, RListError},
+    hook::Hook,
+    rlist::{EditOptions, RList},
+    utils::{canonicalize_url, confirm, parse_short_id, validate_url},
+};
+
+/// Writes a line to the output of the command like `writeln!(ctx.out, ...)`, unless `--quiet` (or `--porcelain`) was passed.
+/// Used for the messages that only decorate the output, such as headers, totals and confirmations
+macro_rules! note {
+    ($ctx:expr) => {
+        if $ctx.quiet {
+            Ok(())
+        } else {
+            writeln!($ctx.out)
+        }
+    };
+    ($ctx:expr, $($arg:tt)*) => {
+        if $ctx.quiet {
+            Ok(())
+        } else {
+            writeln!($ctx.out, $($arg)*)
+        }
+    };
+}
+
+pub mod add;
+pub mod archive;
+pub mod archive_entry;
+pub mod authors;
+pub mod backup;
+pub mod bulk_edit;
+pub mod check_links;
+pub mod complete;
+pub mod completions;
+pub mod db;
+pub mod dedupe;
+pub mod edit;
+pub mod export;
+pub mod fetch;
+pub mod heatmap;
+pub mod history;
+pub mod import;
+pub mod list;
+pub mod man;
+pub mod mark_read;
+pub mod merge;
+pub mod next;
+pub mod normalize;
+pub mod open;
+pub mod plugin;
+pub mod prune;
+pub mod queue;
+pub mod read;
+pub mod redo;
+pub mod remove;
+pub mod restore;
+pub mod retag;
+pub mod save;
+pub mod show;
+pub mod snooze;
+pub mod stats;
+pub mod topics;
+pub mod transfer;
+pub mod trash;
+pub mod tree;
+pub mod undo;
+
+/// The state shared by all of the command handlers
+pub struct AppContext {
+    pub config: Config,
+    pub rlist: RList,
+    /// Where the output of the commands is written to. Warnings and errors are still written to stderr.
+    pub out: Box<dyn Write>,
+    /// If set, the handlers must never wait for user input
+    pub no_input: bool,
+    /// If set, only the essential output is printed: not the messages written with `note!`
+    pub quiet: bool,
+    /// If set, the entries are printed in the porcelain format (see [`Entry::porcelain`]). Implies `quiet`
+    pub porcelain: bool,
+}
+
+impl AppContext {
+    /// Asks the user to confirm an action. If input is disabled, returns an error explaining how to skip the confirmation (`hint`)
+    pub fn confirm(&self, question: impl AsRef<str>, hint: impl AsRef<str>) -> Result<bool> {
+        if self.no_input {
+            return Err(anyhow::anyhow!(
+                "This action requires confirmation, but input is disabled. {}",
+                hint.as_ref()
+            ));
+        }
+        confirm(question)
+    }
+
+    /// Returns whether the entries being removed are deleted permanently: if `permanent` was passed, or if the storage of the
+    /// reading list does not keep the trash, in which case a warning says so
+    pub fn removes_permanently(&self, permanent: bool) -> bool {
+        if !permanent && !self.rlist.keeps_trash() {
+            eprintln!(
+                "{}: the storage of your reading list does not keep the trash, so the entries are removed permanently and cannot be restored",
+                "Warning".bold().yellow()
+            );
+        }
+        permanent || !self.rlist.keeps_trash()
+    }
+
+    /// Prints `entry`, in full if `long`, or as a line of the porcelain format if `--porcelain` was passed
+    pub fn print_entry(&mut self, entry: &Entry, long: bool) -> Result<()> {
+        write_entry(
+            &mut self.out,
+            entry,
+            long,
+            self.porcelain,
+            &self.config.datetime_format,
+        )
+    }
+
+    /// Returns the name of the entry given as `name`, which can also be the short id of the entry (such as `@1z`).
+    /// An entry actually named like a short id takes precedence
+    pub fn resolve_name(&self, name: String) -> Result<String> {
+        match parse_short_id(&name) {
+            Some(entry_id) if !self.rlist.contains(&name)? => {
+                Ok(self.rlist.get_by_id(entry_id)?.name)
+            }
+            _ => Ok(name),
+        }
+    }
+
+    /// Returns the entry with the url `url`. The url is also looked for the way it would be saved by `add`,
+    /// that is completed with `https://` if it is a bare domain and canonicalized
+    pub fn get_by_url(&self, url: &str) -> Result<Entry> {
+        if let Some(entry) = self.rlist.get_by_url(url.trim())? {
+            return Ok(entry);
+        }
+        if let Ok(valid) = validate_url(url) {
+            let canonical = canonicalize_url(&valid, &self.config.url_canonicalization);
+            if let Some(entry) = self.rlist.get_by_url(&canonical)? {
+                return Ok(entry);
+            }
+        }
+        Err(RListError::new(
+            ErrorCode::NotFound,
+            Entity::new("entry", "url", url),
+            format!(
+                "Could not find any entry in your reading list with url {}",
+                url.link()
+            ),
+        )
+        .into())
+    }
+
+    /// Whether a command is set for `hook` in the config
+    pub fn has_hook(&self, hook: Hook) -> bool {
+        self.config.hooks.contains_key(&hook)
+    }
+
+    /// Runs the command set for `hook` in the config (if any) on `entry`.
+    /// A failing pre hook returns an error, so that the command is aborted, while a failing post hook only prints a warning
+    pub fn run_hook(&self, hook: Hook, entry: &Entry) -> Result<()> {
+        let Some(command) = self.config.hooks.get(&hook) else {
+            return Ok(());
+        };
+        match hook.run(command, entry, &self.config.db_file) {
+            Err(err) if !hook.is_pre() => {
+                eprintln!("{}: {err:#}", "Warning".bold().yellow());
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Edits `entry` like [`RList::edit`], running the pre_edit hook on it before and the post_edit hook on the edited entry after
+    pub fn edit_entry(&self, entry: &Entry, opts: EditOptions) -> Result<Entry> {
+        self.run_hook(Hook::PreEdit, entry)?;
+        let edited = self.rlist.edit(entry.name.clone(), opts)?;
+        self.run_hook(Hook::PostEdit, &edited)?;
+        Ok(edited)
+    }
+
+    /// Merges the entries `remove` into `keep` like [`RList::merge`]. Since `keep` is edited and the others are removed,
+    /// the edit hooks run on `keep` and the remove hooks on each of the others
+    pub fn merge_entries(&self, keep: &Entry, remove: &[Entry]) -> Result<Entry> {
+        self.run_hook(Hook::PreEdit, keep)?;
+        for e in remove.iter() {
+            self.run_hook(Hook::PreRemove, e)?;
+        }
+        let names = remove.iter().map(|e| &e.name).collect::<Vec<_>>();
+        let merged = self.rlist.merge(&keep.name, &names)?;
+        self.run_hook(Hook::PostEdit, &merged)?;
+        for e in remove.iter() {
+            self.run_hook(Hook::PostRemove, e)?;
+        }
+        Ok(merged)
+    }
+}
+
+/// Writes `entry` to `out` like [`AppContext::print_entry`], for the handlers that print the entries while borrowing the context
+pub fn write_entry(
+    out: &mut impl Write,
+    entry: &Entry,
+    long: bool,
+    porcelain: bool,
+    datetime_format: &str,
+) -> Result<()> {
+    if porcelain {
+        writeln!(out, "{}", entry.porcelain())?;
+        Ok(())
+    } else {
+        entry.pretty_print(out, long, datetime_format)
+    }
+}
+
+/// The read and archived status of an entry
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum EntryStatus {
+    Read,
+    Unread,
+    Archived,
+    Unarchived,
+}
+
+/// Returns whether `err` was caused by writing to a pipe whose reading end was closed (e.g. by `head`),
+/// after which there is no point in writing anything else
+pub fn is_broken_pipe(err: &anyhow::Error) -> bool {
+    err.chain().any(|e| {
+        e.downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe)
+    })
+}
+
+/// Selects the entry a command acts on, either by its name, by its url or by its id
+#[derive(Args, Debug)]
+#[group(required = true, multiple = false)]
+pub struct EntrySelector {
+    /// The name of the entry, or its short id (such as `@1z`)
+    name: Option<String>,
+
+    /// The url of the entry, to select it instead of by its name
+    #[arg(long)]
+    url: Option<String>,
+
+    /// The id of the entry (shown by `list --long`), to select it instead of by its name
+    #[arg(long)]
+    id: Option<i64>,
+}
+
+impl EntrySelector {
+    /// Returns the name of the selected entry
+    pub fn name(self, ctx: &AppContext) -> Result<String> {
+        match (self.name, self.url, self.id) {
+            (Some(name), _, _) => ctx.resolve_name(name),
+            (None, Some(url), _) => Ok(ctx.get_by_url(&url)?.name),
+            (None, None, Some(id)) => Ok(ctx.rlist.get_by_id(id)?.name),
+            (None, None, None) => Err(anyhow::anyhow!("No entry was selected")),
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Action {
+    /// Add an entry to the reading list
+    #[command(aliases=&["a", "create"])]
+    Add(add::AddArgs),
+
+    /// Remove one or more entries (given by name, or selected with the same filters as `list`) from the reading list, moving them to the trash.
+    /// Asks for confirmation before removing more than one entry (skip it with `--yes`), and `--dry-run` only shows what would be removed
+    #[command(aliases=&["rm", "r", "d", "delete"])]
+    Remove(remove::RemoveArgs),
+
+    /// Remove the entries added longer ago than a given duration, optionally only the ones in some topics or with some status
+    Prune(prune::PruneArgs),
+
+    /// Edit an entry
+    #[command(aliases=&["e", "mv"])]
+    Edit(edit::EditArgs),
+
+    /// Show the content of your reading list
+    #[command(aliases=&["ls", "l", "q", "query", "s", "search", "find", "f"])]
+    List(list::ListArgs),
+
+    /// Show all of the details of an entry
+    Show(show::ShowArgs),
+
+    /// Edit all of the entries that match a query (with the same filters as `list`) at once
+    BulkEdit(bulk_edit::BulkEditArgs),
+
+    /// Import the entries of a file written by `rlist export`, or the links of a markdown document.
+    /// Entries with the same name or url as an entry in your reading list are skipped, unless `--on-conflict` says otherwise,
+    /// and `--merge-topics` adds their topics to the existing entries
+    Import(import::ImportArgs),
+
+    /// Export your reading list to a yml file (or to stdout), or to a yml file per topic with `--split-by topic`.
+    /// All of the entries are exported, unless some are selected with the same filters as `list` or with `--since`
+    Export(export::ExportArgs),
+
+    /// Save a consistent copy of the whole reading list database, which can be restored with `rlist restore`
+    Backup(backup::BackupArgs),
+
+    /// Replace the whole reading list with a backup saved by `rlist backup` or by the automatic backups
+    Restore(restore::RestoreArgs),
+
+    /// Maintain the database the reading list is kept in
+    Db {
+        #[command(subcommand)]
+        action: db::DbAction,
+    },
+
+    /// Move entries, with all of their topics and data, to another reading list (a profile of your config or a db file)
+    MoveTo(transfer::TransferArgs),
+
+    /// Copy entries, with all of their topics and data, to another reading list (a profile of your config or a db file)
+    CopyTo(transfer::TransferArgs),
+
+    /// Download the page of an entry and fill in its missing author, site name and description
+    Fetch(fetch::FetchArgs),
+
+    /// Check whether the links of the entries still work, reporting broken links and redirects
+    CheckLinks(check_links::CheckLinksArgs),
+
+    /// Archive an entry: archived entries are hidden from the default listings, but are kept in the reading list
+    ArchiveEntry(archive_entry::ArchiveEntryArgs),
+
+    /// Save a snapshot of the content of an entry on the Wayback Machine, so that it survives link rot
+    Archive(archive::ArchiveArgs),
+
+    /// Download the content of an entry and save its readable text, so that it can be read offline
+    Save(save::SaveArgs),
+
+    /// Read the content of an entry saved with `rlist save` in a pager
+    Read(read::ReadArgs),
+
+    /// Open the url of an entry in the browser set in `$BROWSER` (or in the default one), counting how many times it was opened
+    Open(open::OpenArgs),
+
+    /// Mark an entry as read, which removes it from the queue and from the entries suggested by `next`
+    MarkRead(mark_read::MarkReadArgs),
+
+    /// Merge duplicate entries into one, which gets all of their topics and metadata and the oldest `added` date. The duplicates are moved to the trash
+    Merge(merge::MergeArgs),
+
+    /// Find the entries that are likely duplicates of each other, and optionally merge them
+    Dedupe(dedupe::DedupeArgs),
+
+    /// Manage the topics of your reading list
+    #[command(aliases=&["topic"])]
+    Topics {
+        #[command(subcommand)]
+        action: topics::TopicsAction,
+    },
+
+    /// Add the topics given by the tag rules of your config to the entries that are missing them
+    Retag(retag::RetagArgs),
+
+    /// Trim the names of the entries and of the topics, collapse the whitespace inside of them and convert them to Unicode NFC,
+    /// fixing the names saved by older versions of rlist
+    Normalize(normalize::NormalizeArgs),
+
+    /// Show the topics as a tree, where `a/b` is a subtopic of `a`, along with their number of entries
+    Tree(tree::TreeArgs),
+
+    /// Manage the removed entries, which are kept in the trash until it is emptied
+    Trash {
+        #[command(subcommand)]
+        action: trash::TrashAction,
+    },
+
+    /// Show the changes made to an entry over time
+    History(history::HistoryArgs),
+
+    /// Revert the last add, edit, removal or merge
+    Undo,
+
+    /// Apply again the last change that was reverted by `undo`
+    Redo,
+
+    /// Hide an entry from the default listings until the snooze expires
+    Snooze(snooze::SnoozeArgs),
+
+    /// Show the entry you should read next: the first entry of the queue or, if there is none, the oldest entry.
+    /// Snoozed, archived and read entries are skipped, even if they are in the queue
+    #[command(aliases=&["n"])]
+    Next,
+
+    /// Manage the queue, which holds the entries you want to read in a specific order
+    Queue {
+        #[command(subcommand)]
+        action: queue::QueueAction,
+    },
+
+    /// Show some statistics about your reading list
+    Stats(stats::StatsArgs),
+
+    /// Show a calendar heatmap of the entries added (or read) on each day of a year
+    Heatmap(heatmap::HeatmapArgs),
+
+    /// Show the authors of the entries in your reading list, or manage them
+    #[command(aliases=&["author"], args_conflicts_with_subcommands = true)]
+    Authors(authors::AuthorsArgs),
+
+    /// Print the tab completion script for a shell, e.g. `rlist completions bash > /etc/bash_completion.d/rlist`
+    Completions(completions::CompletionsArgs),
+
+    /// Print the man page of rlist, e.g. `rlist man > ~/.local/share/man/man1/rlist.1`
+    Man,
+
+    /// Print the names of the entries or of the topics, one per line, for the completion scripts
+    #[command(name = "__complete", hide = true)]
+    Complete(complete::CompleteArgs),
+
+    /// Run the `rlist-<subcommand>` plugin found in your PATH
+    #[command(external_subcommand)]
+    Plugin(Vec<String>),
+}
+
+impl Action {
+    /// Runs the handler of the selected subcommand
+    pub fn run(self, ctx: &mut AppContext) -> Result<()> {
+        match self {
+            Action::Add(args) => add::run(ctx, args),
+            Action::Remove(args) => remove::run(ctx, args),
+            Action::Edit(args) => edit::run(ctx, args),
+            Action::Prune(args) => prune::run(ctx, args),
+            Action::List(args) => list::run(ctx, args),
+            Action::Show(args) => show::run(ctx, args),
+            Action::BulkEdit(args) => bulk_edit::run(ctx, args),
+            Action::Import(args) => import::run(ctx, args),
+            Action::Export(args) => export::run(ctx, args),
+            Action::Backup(args) => backup::run(ctx, args),
+            Action::Restore(args) => restore::run(ctx, args),
+            Action::Db { action } => db::run(ctx, action),
+            Action::MoveTo(args) => transfer::run(ctx, args, true),
+            Action::CopyTo(args) => transfer::run(ctx, args, false),
+            Action::Fetch(args) => fetch::run(ctx, args),
+            Action::CheckLinks(args) => check_links::run(ctx, args),
+            Action::ArchiveEntry(args) => archive_entry::run(ctx, args),
+            Action::Archive(args) => archive::run(ctx, args),
+            Action::Save(args) => save::run(ctx, args),
+            Action::Read(args) => read::run(ctx, args),
+            Action::Open(args) => open::run(ctx, args),
+            Action::MarkRead(args) => mark_read::run(ctx, args),
+            Action::Merge(args) => merge::run(ctx, args),
+            Action::Dedupe(args) => dedupe::run(ctx, args),
+            Action::Topics { action } => topics::run(ctx, action),
+            Action::Retag(args) => retag::run(ctx, args),
+            Action::Normalize(args) => normalize::run(ctx, args),
+            Action::Tree(args) => tree::run(ctx, args),
+            Action::Trash { action } => trash::run(ctx, action),
+            Action::History(args) => history::run(ctx, args),
+            Action::Undo => undo::run(ctx),
+            Action::Redo => redo::run(ctx),
+            Action::Snooze(args) => snooze::run(ctx, args),
+            Action::Next => next::run(ctx),
+            Action::Queue { action } => queue::run(ctx, action),
+            Action::Stats(args) => stats::run(ctx, args),
+            Action::Heatmap(args) => heatmap::run(ctx, args),
+            Action::Authors(args) => authors::run(ctx, args),
+            Action::Completions(args) => completions::run(&mut ctx.out, args),
+            Action::Man => man::run(&mut ctx.out),
+            Action::Complete(args) => complete::run(ctx, args),
+            Action::Plugin(args) => plugin::run(ctx, args),
+        }
+    }
+}
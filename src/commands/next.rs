@@ -0,0 +1,14 @@
+use std::io::Write;
+
+use anyhow::Result;
+
+use super::AppContext;
+
+pub fn run(ctx: &mut AppContext) -> Result<()> {
+    let Some(entry) = ctx.rlist.next()? else {
+        note!(ctx, "There is nothing left to read")?;
+        return Ok(());
+    };
+
+    ctx.print_entry(&entry, true)
+}
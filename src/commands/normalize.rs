@@ -0,0 +1,58 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::topic::Topic;
+
+#[derive(Args, Debug)]
+pub struct NormalizeArgs {
+    /// Only show which entries and topics would be renamed, without changing anything
+    #[arg(long)]
+    dry_run: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: NormalizeArgs) -> Result<()> {
+    let plan = ctx.rlist.plan_normalize()?;
+    if plan.is_empty() {
+        writeln!(ctx.out, "All of the names are already normalized")?;
+        return Ok(());
+    }
+
+    for r in plan.entries.iter() {
+        writeln!(ctx.out, "{:?} -> {}", r.from, r.to.entry_name())?;
+    }
+    for r in plan.topics.iter() {
+        writeln!(
+            ctx.out,
+            "{:?} -> {}{}",
+            r.from,
+            Topic::pretty_print(r.to.as_str()),
+            if r.merge { " (merged)" } else { "" }
+        )?;
+    }
+
+    if args.dry_run {
+        return Ok(());
+    }
+    ctx.rlist.normalize(&plan)?;
+    writeln!(
+        ctx.out,
+        "Normalized {} {} and {} {}",
+        plan.entries.len(),
+        if plan.entries.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        plan.topics.len(),
+        if plan.topics.len() == 1 {
+            "topic"
+        } else {
+            "topics"
+        }
+    )?;
+    Ok(())
+}
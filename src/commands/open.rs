@@ -0,0 +1,55 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::{AppContext, EntrySelector};
+
+#[derive(Args, Debug)]
+pub struct OpenArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+}
+
+pub fn run(ctx: &mut AppContext, args: OpenArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let entry = ctx.rlist.get_by_name(&name)?;
+    open_in_browser(&entry.url)?;
+    ctx.rlist.record_open(&name)?;
+
+    note!(ctx, "Opened {}", entry.url.link())?;
+    Ok(())
+}
+
+/// Opens `url` with the browser set in `$BROWSER` or, if it is not set, with the default opener of the system.
+/// Does not wait for the browser to be closed
+fn open_in_browser(url: &str) -> Result<()> {
+    let mut command = match std::env::var("BROWSER") {
+        Ok(browser) if !browser.trim().is_empty() => {
+            let mut args = browser.split_whitespace();
+            let mut command = Command::new(args.next().expect("non empty"));
+            command.args(args);
+            command
+        }
+        _ if cfg!(target_os = "macos") => Command::new("open"),
+        _ if cfg!(target_os = "windows") => {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", ""]);
+            command
+        }
+        _ => Command::new("xdg-open"),
+    };
+
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Could not open the browser. Set $BROWSER to choose which browser to use")?;
+    Ok(())
+}
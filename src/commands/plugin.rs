@@ -0,0 +1,36 @@
+use std::{io::ErrorKind, process::Command};
+
+use anyhow::{Context, Result};
+
+use super::AppContext;
+
+/// Runs the `rlist-<name>` executable found in `$PATH` with the arguments that follow `name` in `args`, git-style.
+/// The reading list, the config file and the global flags are passed in the environment as `$RLIST_DB_FILE`,
+/// `$RLIST_CONFIG` and `$RLIST_NO_INPUT`. If the plugin fails, rlist exits with its exit code
+pub fn run(ctx: &mut AppContext, args: Vec<String>) -> Result<()> {
+    let (name, plugin_args) = args.split_first().context("No subcommand was given")?;
+    let executable = format!("rlist-{name}");
+
+    let mut command = Command::new(&executable);
+    command
+        .args(plugin_args)
+        .env("RLIST_DB_FILE", &ctx.config.db_file)
+        .env("RLIST_NO_INPUT", if ctx.no_input { "1" } else { "0" });
+    if let Some(path) = ctx.config.path.as_ref() {
+        command.env("RLIST_CONFIG", path);
+    }
+
+    let status = match command.status() {
+        Ok(status) => status,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return Err(anyhow::anyhow!(
+                "`{name}` is not an rlist command, and no `{executable}` plugin was found in your PATH. Run `rlist help` to see the available commands"
+            ));
+        }
+        Err(err) => return Err(err).with_context(|| format!("Could not run `{executable}`")),
+    };
+    if !status.success() {
+        std::process::exit(status.code().unwrap_or(1));
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use dateparser::DateTimeUtc;
+
+use super::{AppContext, EntryStatus};
+use rlist::{hook::Hook, rlist::QueryOptions, utils::parse_duration};
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// Remove the entries added longer ago than this duration (e.g. `1y`, `6m`)
+    #[arg(long, value_parser = |s: &str| parse_duration(s))]
+    older_than: chrono::Duration,
+
+    /// Only remove the entries in all of these topics
+    #[arg(short, long, num_args = 1..)]
+    topics: Option<Vec<String>>,
+
+    /// Only remove the entries with this status
+    #[arg(long)]
+    status: Option<EntryStatus>,
+
+    /// Delete the entries permanently instead of moving them to the trash
+    #[arg(long)]
+    permanent: bool,
+
+    /// Only show which entries would be removed, without removing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Do not ask for confirmation before removing the entries
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: PruneArgs) -> Result<()> {
+    let opts = QueryOptions {
+        topics: args.topics.map(|t| ctx.config.resolve_topic_aliases(t)),
+        to: Some(DateTimeUtc(chrono::Utc::now() - args.older_than)),
+        read: match args.status {
+            Some(EntryStatus::Read) => Some(true),
+            Some(EntryStatus::Unread) => Some(false),
+            _ => None,
+        },
+        archived: match args.status {
+            Some(EntryStatus::Archived) => Some(true),
+            Some(EntryStatus::Unarchived) => Some(false),
+            _ => None,
+        },
+        ..Default::default()
+    };
+    let entries = ctx.rlist.query_with(&opts)?;
+    if entries.is_empty() {
+        note!(ctx, "No entries to prune")?;
+        return Ok(());
+    }
+
+    for e in entries.iter() {
+        ctx.print_entry(e, false)?;
+    }
+    let count = format!(
+        "{} {}",
+        entries.len(),
+        if entries.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        }
+    );
+    let permanent = ctx.removes_permanently(args.permanent);
+    let action = if permanent {
+        "removed permanently"
+    } else {
+        "moved to the trash"
+    };
+    if args.dry_run {
+        note!(ctx, "{count} would be {action}")?;
+        return Ok(());
+    }
+    if !args.yes
+        && !ctx.confirm(
+            format!("{count} will be {action}. Do you want to continue?"),
+            "Pass --yes to prune the entries",
+        )?
+    {
+        return Ok(());
+    }
+
+    for e in entries.iter() {
+        ctx.run_hook(Hook::PreRemove, e)?;
+    }
+    let names = entries.into_iter().map(|e| e.name).collect::<Vec<_>>();
+    let old_entries = ctx.rlist.remove_many(&names, permanent)?;
+    note!(ctx, "Pruned {count}")?;
+    for e in old_entries.iter() {
+        ctx.run_hook(Hook::PostRemove, e)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use super::{is_broken_pipe, AppContext};
+
+#[derive(Subcommand, Debug)]
+pub enum QueueAction {
+    /// Append an entry to the end of the queue
+    #[command(aliases=&["a"])]
+    Add {
+        /// The name of the entry you want to enqueue
+        name: String,
+    },
+
+    /// Show the entries in the queue, in order
+    #[command(aliases=&["ls", "l"])]
+    List {
+        /// If set, the result will also show the `added` date and the topics for each entry
+        #[arg(short, long)]
+        long: bool,
+    },
+
+    /// Move an entry of the queue to another position
+    #[command(aliases=&["mv"])]
+    Move {
+        /// The name of the entry you want to move
+        name: String,
+
+        /// The new position of the entry in the queue, starting from 1
+        position: usize,
+    },
+
+    /// Remove the first entry from the queue and show it
+    Pop,
+
+    /// Remove an entry from the queue. The entry is kept in your reading list
+    #[command(aliases=&["rm"])]
+    Remove {
+        /// The name of the entry you want to remove from the queue
+        name: String,
+    },
+}
+
+pub fn run(ctx: &mut AppContext, action: QueueAction) -> Result<()> {
+    match action {
+        QueueAction::Add { name } => {
+            let name = ctx.resolve_name(name)?;
+            let position = ctx.rlist.queue_add(&name)?;
+            note!(
+                ctx,
+                "Entry {name} added to the queue at position {position}"
+            )?;
+        }
+        QueueAction::List { long } => {
+            let entries = ctx.rlist.queue_list()?;
+            if entries.is_empty() {
+                note!(ctx, "The queue is empty")?;
+            }
+            for (i, e) in entries.iter().enumerate() {
+                // In the porcelain format, the position is given by the order of the lines
+                if !ctx.porcelain {
+                    write!(ctx.out, "{}. ", i + 1)?;
+                }
+                match ctx.print_entry(e, long) {
+                    Err(e) if is_broken_pipe(&e) => return Err(e),
+                    Err(e) => eprintln!("{}", e),
+                    Ok(()) => {}
+                }
+                if long && !ctx.porcelain {
+                    writeln!(ctx.out)?;
+                }
+            }
+        }
+        QueueAction::Move { name, position } => {
+            let name = ctx.resolve_name(name)?;
+            ctx.rlist.queue_move(&name, position)?;
+            note!(ctx, "Entry {name} moved in the queue")?;
+        }
+        QueueAction::Pop => match ctx.rlist.queue_pop()? {
+            Some(entry) => {
+                note!(ctx, "Removed from the queue:")?;
+                ctx.print_entry(&entry, true)?;
+            }
+            None => note!(ctx, "The queue is empty")?,
+        },
+        QueueAction::Remove { name } => {
+            let name = ctx.resolve_name(name)?;
+            ctx.rlist.queue_remove(&name)?;
+            note!(ctx, "Entry {name} removed from the queue")?;
+        }
+    }
+    Ok(())
+}
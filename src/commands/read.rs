@@ -0,0 +1,57 @@
+use std::{
+    io::{IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::{AppContext, EntrySelector};
+
+#[derive(Args, Debug)]
+pub struct ReadArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+}
+
+pub fn run(ctx: &mut AppContext, args: ReadArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let entry = ctx.rlist.get_by_name(&name)?;
+    let snapshot = entry.snapshot.ok_or_else(|| {
+        anyhow::anyhow!(
+            "Entry {} has not been saved yet. Run `rlist save` to save it",
+            name.entry_name()
+        )
+    })?;
+    let content = std::fs::read_to_string(&snapshot)
+        .with_context(|| format!("Could not read the snapshot {snapshot}"))?;
+
+    if !std::io::stdout().is_terminal() || !page(&content) {
+        write!(ctx.out, "{content}")?;
+    }
+    Ok(())
+}
+
+/// Shows `content` in the pager set in `$PAGER` (`less` by default). Returns false if the pager could not be started
+fn page(content: &str) -> bool {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    let mut args = pager.split_whitespace();
+    let Some(program) = args.next() else {
+        return false;
+    };
+
+    let Ok(mut child) = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+    else {
+        return false;
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user may quit the pager before reading everything, which closes the pipe
+        let _ = stdin.write_all(content.as_bytes());
+    }
+    let _ = child.wait();
+    true
+}
@@ -0,0 +1,165 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+
+use super::{is_broken_pipe, list::FilterArgs, AppContext};
+use rlist::{entry::Entry, hook::Hook, rlist::QueryOptions};
+
+#[derive(Args, Debug)]
+pub struct RemoveArgs {
+    /// The names (or the short ids) of the entries you want to remove. The entries that do not exist are reported,
+    /// while all of the others are removed. Without names, all of the entries that match the filters are removed
+    #[arg(
+        value_name = "NAME",
+        required_unless_present_any = ["url", "id", "query", "url_contains", "FilterArgs"],
+        conflicts_with = "FilterArgs"
+    )]
+    names: Vec<String>,
+
+    /// The url of the entry you want to remove, to select it instead of by its name
+    #[arg(long, conflicts_with_all = ["names", "query", "url_contains", "FilterArgs"])]
+    url: Option<String>,
+
+    /// The id of the entry you want to remove (shown by `list --long`), to select it instead of by its name
+    #[arg(long, conflicts_with_all = ["names", "url", "query", "url_contains", "FilterArgs"])]
+    id: Option<i64>,
+
+    /// Remove the entries whose name contains this substring
+    #[arg(long, conflicts_with = "names")]
+    query: Option<String>,
+
+    /// Remove the entries whose url contains this substring (`--url` selects a single entry by its whole url)
+    #[arg(long, conflicts_with = "names")]
+    url_contains: Option<String>,
+
+    /// Remove all of the entries that match these filters, which work like the ones of `list`
+    #[command(flatten)]
+    filters: FilterArgs,
+
+    /// Delete the entries permanently instead of moving them to the trash
+    #[arg(long)]
+    permanent: bool,
+
+    /// Only show which entries would be removed, without removing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Do not ask for confirmation before removing more than one entry
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: RemoveArgs) -> Result<()> {
+    // The names that do not match any entry, along with the reason
+    let mut missing = Vec::new();
+    let entries = match (args.names.is_empty(), args.url, args.id) {
+        (false, _, _) => {
+            let mut entries = Vec::new();
+            for name in args.names {
+                match ctx
+                    .resolve_name(name.clone())
+                    .and_then(|name| ctx.rlist.get_by_name(name))
+                {
+                    // The same entry can be given twice, e.g. by name and by short id
+                    Ok(e) if entries.iter().any(|other: &Entry| other.id == e.id) => {}
+                    Ok(e) => entries.push(e),
+                    Err(err) => missing.push(err),
+                }
+            }
+            entries
+        }
+        (true, Some(url), _) => vec![ctx.get_by_url(&url)?],
+        (true, None, Some(id)) => vec![ctx.rlist.get_by_id(id)?],
+        (true, None, None) => {
+            if args.query.is_none() && args.url_contains.is_none() && !args.filters.narrows() {
+                return Err(anyhow::anyhow!(
+                    "No entries were selected: pass their names, or at least one filter such as --topics or --query"
+                ));
+            }
+            let opts = QueryOptions {
+                query: args.query,
+                url: args.url_contains,
+                ..args.filters.query_options(ctx)?
+            };
+            ctx.rlist.query_with(&opts)?
+        }
+    };
+    if entries.is_empty() && missing.len() == 1 {
+        return Err(missing.remove(0));
+    }
+    for err in missing.iter() {
+        eprintln!("{}: {err}", "Error".bold().red());
+    }
+    let not_found = || anyhow::anyhow!("{} of the given entries could not be found", missing.len());
+    if entries.is_empty() {
+        note!(ctx, "No entries were removed")?;
+        return if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(not_found())
+        };
+    }
+
+    let permanent = ctx.removes_permanently(args.permanent);
+    if args.dry_run || (entries.len() > 1 && !args.yes) {
+        note!(
+            ctx,
+            "The following entries {} {}:",
+            if args.dry_run { "would be" } else { "will be" },
+            if permanent {
+                "removed permanently"
+            } else {
+                "moved to the trash"
+            }
+        )?;
+        for e in entries.iter() {
+            ctx.print_entry(e, false)?;
+        }
+        if args.dry_run
+            || !ctx.confirm(
+                format!("Do you want to remove these {} entries?", entries.len()),
+                "Pass --yes to remove the entries",
+            )?
+        {
+            return Ok(());
+        }
+    }
+
+    for e in entries.iter() {
+        ctx.run_hook(Hook::PreRemove, e)?;
+    }
+    let names = entries.into_iter().map(|e| e.name).collect::<Vec<_>>();
+    let old_entries = ctx.rlist.remove_many(&names, permanent)?;
+    print_removed(ctx, &old_entries, permanent)?;
+    for e in old_entries.iter() {
+        ctx.run_hook(Hook::PostRemove, e)?;
+    }
+    if !missing.is_empty() {
+        return Err(not_found());
+    }
+    Ok(())
+}
+
+fn print_removed(ctx: &mut AppContext, old_entries: &[Entry], permanent: bool) -> Result<()> {
+    match (old_entries.len(), permanent) {
+        (1, true) => note!(ctx, "Removed entry: ")?,
+        (1, false) => note!(ctx, "Moved entry to the trash: ")?,
+        (_, true) => note!(ctx, "Removed these entries:")?,
+        (_, false) => note!(ctx, "Moved these entries to the trash:")?,
+    }
+    for e in old_entries.iter() {
+        match ctx.print_entry(e, true) {
+            Err(e) if is_broken_pipe(&e) => return Err(e),
+            Err(e) => eprintln!("{}", e),
+            Ok(()) => {}
+        }
+        note!(ctx)?;
+    }
+
+    if old_entries.len() > 1 {
+        note!(ctx, "Removed a total of {} entries", old_entries.len())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Result;
+use clap::Args;
+
+use super::AppContext;
+use rlist::{
+    error::{Entity, ErrorCode, RListError},
+    rlist::QueryOptions,
+};
+
+#[derive(Args, Debug)]
+pub struct RestoreArgs {
+    /// The backup to restore, saved by `rlist backup` or by the automatic backups
+    path: PathBuf,
+
+    /// Do not ask for confirmation before replacing your reading list
+    #[arg(short, long)]
+    yes: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: RestoreArgs) -> Result<()> {
+    if !args.path.is_file() {
+        return Err(RListError::new(
+            ErrorCode::NotFound,
+            Entity::new("backup", "path", args.path.display()),
+            format!("Could not find the backup {}", args.path.display()),
+        )
+        .into());
+    }
+
+    let count = ctx.rlist.count(&QueryOptions::default())?;
+    if !args.yes
+        && !ctx.confirm(
+            format!(
+                "Do you want to replace your reading list ({count} {}) with the backup {}?",
+                if count == 1 { "entry" } else { "entries" },
+                args.path.display()
+            ),
+            "Pass --yes to restore the backup",
+        )?
+    {
+        return Ok(());
+    }
+
+    let restored = ctx.rlist.restore(&args.path)?;
+    note!(
+        ctx,
+        "Restored the backup {} ({restored} {})",
+        args.path.display(),
+        if restored == 1 { "entry" } else { "entries" }
+    )?;
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::{rlist::EditOptions, topic::Topic};
+
+#[derive(Args, Debug)]
+pub struct RetagArgs {
+    /// Apply the tag rules of your config to all of the entries already in the reading list
+    #[arg(long, required = true)]
+    apply_rules: bool,
+}
+
+pub fn run(ctx: &mut AppContext, _args: RetagArgs) -> Result<()> {
+    let entries = ctx.rlist.query().run()?;
+    let mut retagged = 0;
+    for e in entries.iter() {
+        let topics = ctx.config.rule_topics(&e.url, &e.topics);
+        if topics.is_empty() {
+            continue;
+        }
+        writeln!(
+            ctx.out,
+            "{}: {}",
+            e.name.entry_name(),
+            topics
+                .iter()
+                .map(|t| format!("+{}", Topic::pretty_print(t.as_str())))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        ctx.edit_entry(
+            e,
+            EditOptions {
+                add_topics: Some(topics),
+                ..Default::default()
+            },
+        )?;
+        retagged += 1;
+    }
+    writeln!(
+        ctx.out,
+        "Retagged {retagged} {}",
+        if retagged == 1 { "entry" } else { "entries" }
+    )?;
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::{AppContext, EntrySelector};
+use rlist::web::Page;
+
+#[derive(Args, Debug)]
+pub struct SaveArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+}
+
+pub fn run(ctx: &mut AppContext, args: SaveArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let entry = ctx.rlist.get_by_name(&name)?;
+    let page = Page::fetch(&entry.url)?;
+
+    let content = format!(
+        "{title}\n{url}\nSaved on {saved}\n\n{text}\n",
+        title = page.title().unwrap_or(entry.name),
+        url = entry.url,
+        saved = chrono::Local::now().format(&ctx.config.datetime_format),
+        text = page.readable_text(),
+    );
+    let entry = ctx
+        .rlist
+        .save_snapshot(&name, &content, ctx.config.snapshots_dir())?;
+
+    note!(ctx, "Saved the content of the entry:")?;
+    ctx.print_entry(&entry, true)?;
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use anyhow::Result;
+use clap::Args;
+
+use super::{AppContext, EntrySelector};
+
+#[derive(Args, Debug)]
+pub struct ShowArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+}
+
+pub fn run(ctx: &mut AppContext, args: ShowArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let entry = ctx.rlist.get_by_name(&name)?;
+    ctx.print_entry(&entry, true)?;
+    Ok(())
+}
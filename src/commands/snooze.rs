@@ -0,0 +1,39 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+
+use super::{AppContext, EntrySelector};
+use rlist::utils::parse_future_datetime;
+
+#[derive(Args, Debug)]
+pub struct SnoozeArgs {
+    #[command(flatten)]
+    entry: EntrySelector,
+
+    /// For how long the entry should be snoozed (e.g. `3d`, `2w`, `1m`), or the day (e.g. `monday`) or datetime until which it should be snoozed
+    #[arg(required_unless_present = "clear")]
+    duration: Option<String>,
+
+    /// Wake the entry up, making it visible again
+    #[arg(long, conflicts_with = "duration")]
+    clear: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: SnoozeArgs) -> Result<()> {
+    let name = args.entry.name(ctx)?;
+    let until = if args.clear {
+        None
+    } else {
+        // `duration` is required unless `--clear` is set
+        args.duration.map(parse_future_datetime).transpose()?
+    };
+    let entry = ctx.rlist.snooze(name, until.as_deref())?;
+    if args.clear {
+        note!(ctx, "Entry woken up:")?;
+    } else {
+        note!(ctx, "Entry snoozed:")?;
+    }
+    ctx.print_entry(&entry, true)?;
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use colored::Colorize;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::topic::Topic;
+
+/// The width of the longest bar in the charts
+const BAR_WIDTH: i64 = 30;
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// The maximum number of topics and authors to show
+    #[arg(long, default_value_t = 5)]
+    top: i64,
+
+    /// Instead of the summary, show the number of entries, the oldest unread entry and the average age of the entries of each topic
+    #[arg(long, conflicts_with = "top")]
+    by_topic: bool,
+
+    /// Instead of the summary, show the number of entries of each domain
+    #[arg(long, conflicts_with_all = ["top", "by_topic"])]
+    by_domain: bool,
+}
+
+pub fn run(ctx: &mut AppContext, args: StatsArgs) -> Result<()> {
+    if args.by_topic {
+        return by_topic(ctx);
+    }
+    if args.by_domain {
+        return by_domain(ctx);
+    }
+
+    let stats = ctx.rlist.stats(args.top)?;
+
+    writeln!(
+        ctx.out,
+        "{} {} ({} read, {} unread)",
+        "Entries:".bold(),
+        stats.total,
+        stats.read,
+        stats.total - stats.read
+    )?;
+
+    if !ctx.config.goals.is_empty() {
+        writeln!(ctx.out, "\n{}", "Goals:".bold())?;
+        let read_dates = ctx.rlist.read_dates()?;
+        let today = chrono::Local::now().date_naive();
+        for goal in ctx.config.goals.iter() {
+            let progress = goal.progress(&read_dates, today);
+            let done = format!("{}/{} {}", progress.read, goal.entries, goal.per.current());
+            writeln!(
+                ctx.out,
+                "  {} {} per {}: {}, streak: {} (best: {})",
+                goal.entries,
+                if goal.entries == 1 {
+                    "entry"
+                } else {
+                    "entries"
+                },
+                goal.per.name(),
+                if progress.read >= goal.entries {
+                    done.green().to_string()
+                } else {
+                    done
+                },
+                plural(progress.streak, goal.per.name()),
+                plural(progress.best_streak, goal.per.name()),
+            )?;
+        }
+    }
+
+    if !stats.added_per_month.is_empty() {
+        writeln!(ctx.out, "\n{}", "Added per month:".bold())?;
+        let max = stats
+            .added_per_month
+            .iter()
+            .map(|(_m, c)| *c)
+            .max()
+            .unwrap_or_default();
+        for (month, count) in stats.added_per_month.iter() {
+            writeln!(ctx.out, "  {month} {} {count}", bar(*count, max))?;
+        }
+    }
+
+    if !stats.top_topics.is_empty() {
+        writeln!(ctx.out, "\n{}", "Top topics:".bold())?;
+        for (topic, count) in stats.top_topics.iter() {
+            writeln!(
+                ctx.out,
+                "  {} ({count})",
+                Topic::pretty_print(topic.as_str())
+            )?;
+        }
+    }
+
+    if !stats.top_authors.is_empty() {
+        writeln!(ctx.out, "\n{}", "Top authors:".bold())?;
+        for (author, count) in stats.top_authors.iter() {
+            writeln!(ctx.out, "  {} ({count})", author.author_name())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn by_topic(ctx: &mut AppContext) -> Result<()> {
+    let stats = ctx.rlist.topic_stats()?;
+    if stats.is_empty() {
+        writeln!(ctx.out, "There are no topics in your reading list")?;
+        return Ok(());
+    }
+
+    let rows = stats
+        .iter()
+        .map(|s| {
+            [
+                s.topic.clone(),
+                s.entries.to_string(),
+                s.oldest.clone().unwrap_or_else(|| "-".to_string()),
+                format!("{:.1}", s.average_age),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let header = [
+        "Topic",
+        "Entries",
+        "Oldest unread entry",
+        "Average age (days)",
+    ];
+
+    let mut widths = header.map(|h| h.chars().count());
+    for row in rows.iter() {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+
+    writeln!(
+        ctx.out,
+        "{}",
+        format!(
+            "{:<w0$}  {:>w1$}  {:<w2$}  {:>w3$}",
+            header[0],
+            header[1],
+            header[2],
+            header[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        )
+        .bold()
+    )?;
+    for row in rows.iter() {
+        // The padding is computed on the plain topic name, since the colored one contains escape sequences
+        let padding = " ".repeat(widths[0] - row[0].chars().count());
+        writeln!(
+            ctx.out,
+            "{}{padding}  {:>w1$}  {:<w2$}  {:>w3$}",
+            Topic::pretty_print(row[0].as_str()),
+            row[1],
+            row[2],
+            row[3],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3]
+        )?;
+    }
+
+    Ok(())
+}
+
+fn by_domain(ctx: &mut AppContext) -> Result<()> {
+    let stats = ctx.rlist.domain_stats()?;
+    if stats.is_empty() {
+        writeln!(ctx.out, "There are no entries in your reading list")?;
+        return Ok(());
+    }
+
+    let width = stats
+        .iter()
+        .map(|(d, _c)| d.chars().count())
+        .max()
+        .unwrap_or_default();
+    for (domain, count) in stats.iter() {
+        writeln!(
+            ctx.out,
+            "{}  {count}",
+            format!("{domain:<width$}").bright_blue()
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Returns `n` followed by `word`, pluralized if needed
+fn plural(n: i64, word: &str) -> String {
+    if n == 1 {
+        format!("{n} {word}")
+    } else {
+        format!("{n} {word}s")
+    }
+}
+
+/// Returns a bar proportional to `value`, where `max` is rendered as a bar of `BAR_WIDTH` characters
+fn bar(value: i64, max: i64) -> String {
+    let width = if max > 0 {
+        (value * BAR_WIDTH + max - 1) / max
+    } else {
+        0
+    };
+    "█".repeat(width as usize)
+}
@@ -0,0 +1,91 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use super::AppContext;
+use rlist::topic::Topic;
+
+#[derive(Subcommand, Debug)]
+pub enum TopicsAction {
+    /// Renames all of the topics whose name matches a regex. Topics that end up with the same name are merged into a single topic
+    #[command(aliases=&["mv"])]
+    Rename {
+        /// The regex that the whole name of a topic must match, e.g. `rust-.*`
+        #[arg(long = "match", value_name = "REGEX")]
+        pattern: String,
+
+        /// The new name of the matching topics. Capture groups of the regex can be referenced with `$1`, `${name}`, ...
+        #[arg(long)]
+        replace: String,
+
+        /// Only show which topics would be renamed, without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Do not ask for confirmation before renaming the topics
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+pub fn run(ctx: &mut AppContext, action: TopicsAction) -> Result<()> {
+    match action {
+        TopicsAction::Rename {
+            pattern,
+            replace,
+            dry_run,
+            yes,
+        } => rename(ctx, pattern, replace, dry_run, yes),
+    }
+}
+
+fn rename(
+    ctx: &mut AppContext,
+    pattern: String,
+    replace: String,
+    dry_run: bool,
+    yes: bool,
+) -> Result<()> {
+    let renames = ctx.rlist.plan_topics_rename(pattern, replace)?;
+    if renames.is_empty() {
+        writeln!(ctx.out, "No topics matched the pattern")?;
+        return Ok(());
+    }
+
+    writeln!(ctx.out, "The following topics will be renamed:")?;
+    for r in renames.iter() {
+        writeln!(
+            ctx.out,
+            "{} -> {} ({} {}{})",
+            Topic::pretty_print(r.from.as_str()),
+            Topic::pretty_print(r.to.as_str()),
+            r.entries,
+            if r.entries == 1 { "entry" } else { "entries" },
+            if r.merge { ", merged" } else { "" }
+        )?;
+    }
+
+    if dry_run
+        || !(yes
+            || ctx.confirm(
+                "Do you want to continue?",
+                "Pass --yes to rename the topics",
+            )?)
+    {
+        return Ok(());
+    }
+
+    ctx.rlist.rename_topics(&renames)?;
+    writeln!(
+        ctx.out,
+        "Renamed {} {}",
+        renames.len(),
+        if renames.len() == 1 {
+            "topic"
+        } else {
+            "topics"
+        }
+    )?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::{
+    error::{Entity, ErrorCode, RListError},
+    storage::local_storage,
+    RList,
+};
+
+#[derive(Args, Debug)]
+pub struct TransferArgs {
+    /// The reading list to transfer the entries to: the name of one of the profiles in your config, or the path of its db file
+    #[arg(value_name = "PROFILE|DB_FILE")]
+    target: String,
+
+    /// The names (or the short ids) of the entries to transfer
+    #[arg(value_name = "NAME", required = true)]
+    names: Vec<String>,
+}
+
+/// Copies the entries to another reading list, removing them from this one if `remove` is set (`move-to`)
+pub fn run(ctx: &mut AppContext, args: TransferArgs, remove: bool) -> Result<()> {
+    let db_file = ctx.config.profile_db_file(&args.target);
+    if !ctx.config.profiles.contains_key(&args.target) && !db_file.exists() {
+        return Err(RListError::new(
+            ErrorCode::NotFound,
+            Entity::new("profile", "name", &args.target),
+            format!(
+                "There is no profile named {} in your config, nor a reading list at that path",
+                args.target
+            ),
+        )
+        .into());
+    }
+    // A remote reading list is never the same as a local one
+    if !ctx.config.storage.is_remote()
+        && db_file.exists()
+        && db_file.canonicalize()? == ctx.config.db_file.canonicalize()?
+    {
+        return Err(anyhow::anyhow!(
+            "{} is the reading list the entries are already in",
+            args.target
+        ));
+    }
+    let names = args
+        .names
+        .into_iter()
+        .map(|name| ctx.resolve_name(name))
+        .collect::<Result<Vec<_>>>()?;
+
+    let target = RList::open(local_storage(&db_file))?;
+    let entries = ctx.rlist.transfer(&names, &target, remove)?;
+    note!(
+        ctx,
+        "{} {} {} to {}:",
+        if remove { "Moved" } else { "Copied" },
+        entries.len(),
+        if entries.len() == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        args.target
+    )?;
+    for e in entries.iter() {
+        writeln!(ctx.out, "{}", e.name.entry_name())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::io::Write;
+
+use anyhow::Result;
+use clap::Subcommand;
+
+use super::{is_broken_pipe, AppContext};
+use rlist::utils::{datetime_before_now, parse_duration, sql_string_to_dt};
+
+#[derive(Subcommand, Debug)]
+pub enum TrashAction {
+    /// Show the entries in the trash, most recently removed first
+    #[command(aliases=&["ls", "l"])]
+    List {
+        /// If set, the result will also show the `added` date and the topics for each entry
+        #[arg(short, long)]
+        long: bool,
+    },
+
+    /// Move an entry out of the trash and back into the reading list
+    Restore {
+        /// The name of the entry you want to restore. If the trash contains more entries with this name, the most recently removed one is restored
+        name: String,
+    },
+
+    /// Permanently delete the entries in the trash
+    Empty {
+        /// Only delete the entries that were removed more than this long ago (e.g. `30d`, `2w`, `6m`)
+        #[arg(long, value_parser = |s: &str| parse_duration(s))]
+        older_than: Option<chrono::Duration>,
+
+        /// Do not ask for confirmation before deleting the entries
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+pub fn run(ctx: &mut AppContext, action: TrashAction) -> Result<()> {
+    match action {
+        TrashAction::List { long } => {
+            let entries = ctx.rlist.trash_list()?;
+            if entries.is_empty() {
+                note!(ctx, "The trash is empty")?;
+            }
+            for (removed, e) in entries.iter() {
+                match ctx.print_entry(e, long) {
+                    Err(e) if is_broken_pipe(&e) => return Err(e),
+                    Err(e) => eprintln!("{}", e),
+                    Ok(()) => {}
+                }
+                if long && !ctx.porcelain {
+                    writeln!(
+                        ctx.out,
+                        "Removed on {}\n",
+                        sql_string_to_dt(removed)?.format(&ctx.config.datetime_format)
+                    )?;
+                }
+            }
+        }
+        TrashAction::Restore { name } => {
+            let entry = ctx.rlist.trash_restore(&name)?;
+            note!(ctx, "Restored entry:")?;
+            ctx.print_entry(&entry, true)?;
+        }
+        TrashAction::Empty { older_than, yes } => {
+            let question = match older_than {
+                Some(_) => "Permanently delete the entries that were removed before then?",
+                None => "Permanently delete all of the entries in the trash?",
+            };
+            if !(yes || ctx.confirm(question, "Pass --yes to empty the trash")?) {
+                return Ok(());
+            }
+
+            let before = older_than.map(datetime_before_now);
+            let deleted = ctx.rlist.trash_empty(before.as_deref())?;
+            note!(
+                ctx,
+                "Permanently deleted {deleted} {}",
+                if deleted == 1 { "entry" } else { "entries" }
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::Write,
+};
+
+use anyhow::Result;
+use clap::Args;
+use rlist::theme::Themed;
+
+use super::AppContext;
+use rlist::topic::{Topic, TOPIC_SEPARATOR};
+
+#[derive(Args, Debug)]
+pub struct TreeArgs {
+    /// Also show the entries, as leaves of their topics
+    #[arg(short, long)]
+    entries: bool,
+}
+
+/// A topic of the tree, along with its subtopics and the names of the entries directly related to it
+#[derive(Default)]
+struct TopicNode {
+    children: BTreeMap<String, TopicNode>,
+    entries: Vec<String>,
+}
+
+impl TopicNode {
+    /// Returns the names of the entries related to this topic or to any of its subtopics
+    fn all_entries(&self) -> HashSet<&str> {
+        let mut res = self
+            .entries
+            .iter()
+            .map(|e| e.as_str())
+            .collect::<HashSet<_>>();
+        for child in self.children.values() {
+            res.extend(child.all_entries());
+        }
+        res
+    }
+}
+
+pub fn run(ctx: &mut AppContext, args: TreeArgs) -> Result<()> {
+    let mut root = TopicNode::default();
+    for entry in ctx.rlist.query().run()? {
+        for topic in entry.topics.iter() {
+            let node = topic
+                .split(TOPIC_SEPARATOR)
+                .filter(|s| !s.is_empty())
+                .fold(&mut root, |node, segment| {
+                    node.children.entry(segment.to_string()).or_default()
+                });
+            node.entries.push(entry.name.clone());
+        }
+    }
+
+    if root.children.is_empty() {
+        writeln!(ctx.out, "There are no topics in your reading list")?;
+        return Ok(());
+    }
+    for (name, node) in root.children.iter() {
+        writeln!(
+            ctx.out,
+            "{} ({})",
+            Topic::pretty_print(name.as_str()),
+            node.all_entries().len()
+        )?;
+        print_children(ctx, node, "", args.entries)?;
+    }
+    Ok(())
+}
+
+/// Prints the subtopics (and the entries, if `entries`) of `node`, each line starting with `prefix`
+fn print_children(
+    ctx: &mut AppContext,
+    node: &TopicNode,
+    prefix: &str,
+    entries: bool,
+) -> Result<()> {
+    let shown_entries = if entries { node.entries.len() } else { 0 };
+    let count = node.children.len() + shown_entries;
+
+    for (i, (name, child)) in node.children.iter().enumerate() {
+        let last = i + 1 == count;
+        writeln!(
+            ctx.out,
+            "{prefix}{}{} ({})",
+            if last { "└── " } else { "├── " },
+            Topic::pretty_print(name.as_str()),
+            child.all_entries().len()
+        )?;
+        let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+        print_children(ctx, child, &prefix, entries)?;
+    }
+    for (i, entry) in node.entries.iter().take(shown_entries).enumerate() {
+        let last = node.children.len() + i + 1 == count;
+        writeln!(
+            ctx.out,
+            "{prefix}{}{}",
+            if last { "└── " } else { "├── " },
+            entry.entry_name()
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use std::io::Write;
+
+use anyhow::Result;
+use rlist::theme::Themed;
+
+use super::AppContext;
+
+pub fn run(ctx: &mut AppContext) -> Result<()> {
+    let Some((kind, names)) = ctx.rlist.undo()? else {
+        note!(ctx, "There is nothing to undo")?;
+        return Ok(());
+    };
+
+    note!(
+        ctx,
+        "Undid the {kind} of {}",
+        names
+            .iter()
+            .map(|n| n.entry_name().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    )?;
+    Ok(())
+}
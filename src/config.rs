@@ -1,22 +1,118 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
+use regex::Regex;
 use serde::Deserialize;
 use std::{
+    collections::HashMap,
     env,
     path::{Path, PathBuf},
 };
 
-use crate::utils::format_string_is_valid;
+use crate::{
+    backup::{BackupConfig, BackupPolicy, DEFAULT_KEEP},
+    db::{column_exists, snapshot::DBSnapshot},
+    goal::Goal,
+    hook::Hook,
+    rlist::OrderBy,
+    storage::{FileStorage, LibsqlStorage, SqliteStorage, Storage, StorageKind},
+    theme::{Theme, ThemeColor},
+    topic::Topic,
+    utils::format_string_is_valid,
+};
 
 #[derive(Deserialize, Debug)]
 pub struct ConfigContent {
+    pub storage: Option<StorageKind>,
+    pub db_url: Option<String>,
+    pub db_auth_token: Option<String>,
     pub db_file: Option<PathBuf>,
+    pub encrypted: Option<bool>,
+    pub remember_passphrase: Option<bool>,
     pub datetime_format: Option<String>,
+    pub snapshots_dir: Option<PathBuf>,
+    pub url_canonicalization: Option<UrlCanonicalization>,
+    pub goals: Option<Vec<Goal>>,
+    pub topic_aliases: Option<HashMap<String, String>>,
+    pub tag_rules: Option<HashMap<String, Vec<String>>>,
+    pub hooks: Option<HashMap<Hook, String>>,
+    pub backups: Option<BackupConfig>,
+    pub profiles: Option<HashMap<String, PathBuf>>,
+    pub list: Option<ListDefaults>,
+    pub aliases: Option<HashMap<String, String>>,
+    pub theme: Option<Theme>,
+    pub topic_colors: Option<HashMap<String, ThemeColor>>,
+}
+
+/// How the urls of new entries are normalized, so that different links to the same page are recognized as duplicates
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct UrlCanonicalization {
+    /// Remove `utm_*` and other tracking parameters from the query string
+    pub strip_tracking_params: bool,
+    /// Remove the trailing `/` from the path
+    pub strip_trailing_slash: bool,
+    /// Remove the `#fragment`
+    pub strip_fragment: bool,
+}
+
+impl Default for UrlCanonicalization {
+    fn default() -> Self {
+        Self {
+            strip_tracking_params: true,
+            strip_trailing_slash: true,
+            strip_fragment: true,
+        }
+    }
+}
+
+/// The defaults of the flags of `rlist list`, used when the flags are not given
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ListDefaults {
+    /// Same as `--sort-by`
+    pub sort_by: Option<OrderBy>,
+    /// Same as `--desc`. Can be overridden with `--asc`
+    pub desc: bool,
+    /// Same as `--long`. Can be overridden with `--short`
+    pub long: bool,
 }
 
 pub struct Config {
+    /// The path of the config file that was read, if any
+    pub path: Option<PathBuf>,
+    /// How the reading list is kept: in `db_file`, or in the remote database at `db_url`
+    pub storage: StorageKind,
+    /// The connection string of the remote database the reading list is kept in, if `storage` is a remote one
+    pub db_url: Option<String>,
+    /// The token used to authenticate to the remote database, if it needs one
+    db_auth_token: Option<String>,
     pub db_file: PathBuf,
+    /// Whether `db_file` is encrypted with SQLCipher. Only supported by the sqlite storage
+    pub encrypted: bool,
+    /// Whether to save the passphrase of the encrypted database in the keyring of the OS once it is entered
+    pub remember_passphrase: bool,
     pub datetime_format: String,
+    /// Where the snapshots saved with `rlist save` are stored. Defaults to the `snapshots` folder next to the db file
+    snapshots_dir: Option<PathBuf>,
+    pub url_canonicalization: UrlCanonicalization,
+    /// The reading goals whose progress is shown by `rlist stats`
+    pub goals: Vec<Goal>,
+    /// Maps each alias (e.g. `js`) to the topic it stands for (e.g. `javascript`)
+    topic_aliases: HashMap<String, String>,
+    /// The topics automatically given to the entries whose url matches each pattern
+    tag_rules: Vec<(Regex, Vec<String>)>,
+    /// The shell commands run at each hook
+    pub hooks: HashMap<Hook, String>,
+    /// Where the automatic backups are saved and how many are kept, if they are enabled
+    backups: Option<BackupConfig>,
+    /// Maps the name of each of your other reading lists (e.g. `work`) to its db file, so that commands such as `move-to` can refer to it by name
+    pub profiles: HashMap<String, PathBuf>,
+    /// The defaults of the flags of `rlist list`
+    pub list: ListDefaults,
+    /// Maps each command alias (e.g. `todo`) to the arguments it stands for (e.g. `list --unread`)
+    aliases: HashMap<String, Vec<String>>,
+    /// The colors used to print the entries
+    pub theme: Theme,
 }
 
 const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
@@ -24,17 +120,214 @@ const DEFAULT_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
 impl Config {
     fn maybe_default() -> Result<Self> {
         Ok(Self {
-            db_file: get_default_db_file_path()?.into(),
+            path: None,
+            storage: StorageKind::default(),
+            db_url: None,
+            db_auth_token: None,
+            db_file: get_default_db_file_path(StorageKind::default())?.into(),
+            encrypted: false,
+            remember_passphrase: false,
             datetime_format: DEFAULT_DATETIME_FORMAT.to_string(),
+            snapshots_dir: None,
+            url_canonicalization: UrlCanonicalization::default(),
+            goals: Vec::new(),
+            topic_aliases: HashMap::new(),
+            tag_rules: Vec::new(),
+            hooks: HashMap::new(),
+            backups: None,
+            profiles: HashMap::new(),
+            list: ListDefaults::default(),
+            aliases: HashMap::new(),
+            theme: Theme::default(),
+        })
+    }
+
+    /// Replaces the aliases in `topics` with the topics they stand for
+    pub fn resolve_topic_aliases(&self, topics: Vec<String>) -> Vec<String> {
+        topics
+            .into_iter()
+            .map(|t| self.topic_aliases.get(&t).cloned().unwrap_or(t))
+            .collect()
+    }
+
+    /// Returns the topics given by the tag rules to an entry with url = `url`, except for the ones already in `topics`
+    pub fn rule_topics(&self, url: &str, topics: &[String]) -> Vec<String> {
+        let (host, path) = match url::Url::parse(url) {
+            Ok(u) => (
+                u.host_str().unwrap_or_default().to_string(),
+                u.path().to_string(),
+            ),
+            Err(_e) => match url.split_once('/') {
+                Some((host, path)) => (host.to_string(), format!("/{path}")),
+                None => (url.to_string(), String::new()),
+            },
+        };
+        let with_path = format!("{host}{path}");
+
+        let mut res = Vec::<String>::new();
+        for (pattern, rule_topics) in self.tag_rules.iter() {
+            // Patterns without a path only need to match the host
+            let target = if pattern.as_str().contains('/') {
+                &with_path
+            } else {
+                &host
+            };
+            if pattern.is_match(target) {
+                for t in rule_topics.iter() {
+                    if !topics.contains(t) && !res.contains(t) {
+                        res.push(t.clone());
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// Returns the storage of the reading list
+    pub fn storage(&self) -> Result<Box<dyn Storage>> {
+        if self.encrypted {
+            return self.encrypted_storage();
+        }
+        Ok(match self.storage {
+            StorageKind::Sqlite => Box::new(SqliteStorage::new(&self.db_file)),
+            StorageKind::File => Box::new(FileStorage::new(&self.db_file)),
+            #[cfg(feature = "postgres")]
+            StorageKind::Postgres => Box::new(crate::storage::PostgresStorage::new(
+                self.db_url.clone().unwrap_or_default(),
+            )),
+            StorageKind::Libsql => Box::new(LibsqlStorage::new(
+                self.db_url.clone().unwrap_or_default(),
+                self.db_auth_token.clone(),
+            )),
+            #[cfg(not(feature = "postgres"))]
+            StorageKind::Postgres => {
+                return Err(anyhow::anyhow!(
+                    "This build of rlist does not support PostgreSQL: reinstall it with `cargo install rlist --features postgres`"
+                ))
+            }
         })
     }
+
+    /// Returns the storage of the reading list when `encrypted` is set
+    #[cfg(feature = "sqlcipher")]
+    fn encrypted_storage(&self) -> Result<Box<dyn Storage>> {
+        if self.storage != StorageKind::Sqlite {
+            return Err(anyhow::anyhow!("Only the sqlite storage can be encrypted"));
+        }
+        Ok(Box::new(crate::storage::EncryptedSqliteStorage::new(
+            &self.db_file,
+            self.remember_passphrase,
+        )))
+    }
+
+    /// Returns the storage of the reading list when `encrypted` is set
+    #[cfg(not(feature = "sqlcipher"))]
+    fn encrypted_storage(&self) -> Result<Box<dyn Storage>> {
+        Err(anyhow::anyhow!(
+            "This build of rlist does not support encrypted databases: reinstall it with `cargo install rlist --features sqlcipher`"
+        ))
+    }
+
+    /// Returns the arguments that the command alias `alias` stands for, if it is one of the aliases in the config
+    pub fn alias(&self, alias: &str) -> Option<&[String]> {
+        self.aliases.get(alias).map(|args| args.as_slice())
+    }
+
+    /// Moves the reading list from `~/rlist`, where older versions of rlist kept it, to the default location
+    /// (see [`migrate_legacy_data_dir`]). Only runs if the reading list is kept in a file and `db_file` is the default one:
+    /// a db file set in the config or with `--db-file`, like a remote storage, leaves `~/rlist` alone
+    pub fn migrate_legacy_data_dir(&self) -> Result<()> {
+        if !matches!(self.storage, StorageKind::Sqlite | StorageKind::File) {
+            return Ok(());
+        }
+        let rlist_dir = get_default_data_dir()?;
+        if self.db_file != rlist_dir.join(self.storage.default_file_name()) {
+            return Ok(());
+        }
+        migrate_legacy_data_dir(&rlist_dir, self.storage)
+    }
+
+    /// Returns the db file of the reading list `target`: the one of the profile named `target`, or else `target` itself as a path
+    pub fn profile_db_file(&self, target: &str) -> PathBuf {
+        self.profiles
+            .get(target)
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(target))
+    }
+
+    /// Returns where the automatic backups are saved and how many of them are kept, or `None` if they are not enabled.
+    /// The backups are saved in the `backups` folder next to the db file by default
+    pub fn backup_policy(&self) -> Option<BackupPolicy> {
+        let backups = self.backups.as_ref()?;
+        Some(BackupPolicy {
+            dir: self.backups_dir(),
+            keep: backups.keep.unwrap_or(DEFAULT_KEEP),
+        })
+    }
+
+    /// Returns the folder where the backups are saved, even if the automatic backups are not enabled.
+    /// Defaults to the `backups` folder next to the db file
+    pub fn backups_dir(&self) -> PathBuf {
+        self.backups
+            .as_ref()
+            .and_then(|b| b.dir.clone())
+            .unwrap_or_else(|| {
+                self.db_file
+                    .parent()
+                    .map(|p| p.join("backups"))
+                    .unwrap_or_else(|| PathBuf::from("backups"))
+            })
+    }
+
+    /// Returns the folder where the snapshots of the entries are stored
+    pub fn snapshots_dir(&self) -> PathBuf {
+        self.snapshots_dir.clone().unwrap_or_else(|| {
+            self.db_file
+                .parent()
+                .map(|p| p.join("snapshots"))
+                .unwrap_or_else(|| PathBuf::from("snapshots"))
+        })
+    }
+}
+
+/// Converts a pattern where `*` matches any sequence of characters (e.g. `*.arxiv.org`) into a regex matching the whole string
+fn glob_to_regex(pattern: &str) -> Result<Regex> {
+    let body = pattern
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+    Regex::new(&format!("^{body}$")).with_context(|| {
+        format!("The tag_rules config option contains an invalid pattern: `{pattern}`")
+    })
 }
 
-fn get_default_db_file_path() -> Result<String> {
+/// Returns the folder set in the environment variable `var` of the XDG base directory specification,
+/// or `fallback` (relative to the home folder) if it is not set. On Windows, returns `windows` instead
+fn xdg_dir(var: &str, fallback: &str, windows: Option<PathBuf>) -> Result<PathBuf> {
+    if cfg!(windows) {
+        return windows.ok_or(anyhow::anyhow!("Could not find the {var} folder"));
+    }
+    // The specification says that relative paths must be ignored
+    if let Some(dir) = env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+    {
+        return Ok(dir);
+    }
     let home_dir_path = dirs::home_dir().ok_or(anyhow::anyhow!("Could not find home folder"))?;
-    let rlist_dir = Path::new(home_dir_path.as_os_str()).join("rlist");
-    Ok(rlist_dir
-        .join("rlist.sqlite")
+    Ok(home_dir_path.join(fallback))
+}
+
+/// Returns the folder of the default db file, `$XDG_DATA_HOME/rlist` (`~/.local/share/rlist` by default)
+fn get_default_data_dir() -> Result<PathBuf> {
+    Ok(xdg_dir("XDG_DATA_HOME", ".local/share", dirs::data_dir())?.join("rlist"))
+}
+
+/// Returns the default db file, `$XDG_DATA_HOME/rlist/rlist.sqlite` (`~/.local/share/rlist/rlist.sqlite` by default)
+fn get_default_db_file_path(storage: StorageKind) -> Result<String> {
+    Ok(get_default_data_dir()?
+        .join(storage.default_file_name())
         .to_str()
         .ok_or(anyhow::anyhow!(
             "Could not get the default reading list location"
@@ -42,16 +335,103 @@ fn get_default_db_file_path() -> Result<String> {
         .to_string())
 }
 
-fn get_default_config_file_path() -> Result<String> {
-    let config_dir_path = if env::consts::OS == "macos" {
-        dirs::home_dir()
-            .ok_or(anyhow::anyhow!("Could not find config folder"))?
-            .join(".config")
-    } else {
-        dirs::config_dir().ok_or(anyhow::anyhow!("Could not find config folder"))?
+/// Moves the reading list that older versions of rlist kept in the `~/rlist` folder (the db file, along with the files
+/// SQLite keeps next to it, the snapshots and the backups) to `rlist_dir`, if there is one and `rlist_dir` does not contain
+/// a reading list yet. The other files in `~/rlist` are left where they are. The snapshots saved in the sqlite database are
+/// updated to point to their new path. See [`Config::migrate_legacy_data_dir`] for when it is run
+fn migrate_legacy_data_dir(rlist_dir: &Path, storage: StorageKind) -> Result<()> {
+    let Some(legacy_dir) = dirs::home_dir().map(|home| home.join("rlist")) else {
+        return Ok(());
     };
+    let file_name = storage.default_file_name();
+    if !legacy_dir.join(file_name).is_file()
+        || rlist_dir.join(file_name).exists()
+        || legacy_dir == rlist_dir
+    {
+        return Ok(());
+    }
+    std::fs::create_dir_all(rlist_dir)?;
+    let data_files = [
+        file_name.to_string(),
+        format!("{file_name}-wal"),
+        format!("{file_name}-shm"),
+        format!("{file_name}-journal"),
+        "snapshots".to_string(),
+        "backups".to_string(),
+    ];
+    let mut moved_snapshots = false;
+    for name in data_files.iter() {
+        let (from, to) = (legacy_dir.join(name), rlist_dir.join(name));
+        if !from.exists() || to.exists() {
+            continue;
+        }
+        moved_snapshots |= name == "snapshots";
+        std::fs::rename(&from, &to).with_context(|| {
+            format!(
+                "Could not move your reading list from {} to {}. Move it by hand, or set db_file in your config to keep it where it is",
+                from.display(),
+                to.display()
+            )
+        })?;
+    }
+    // Only succeeds if nothing else was in the folder
+    let _ = std::fs::remove_dir(&legacy_dir);
+    eprintln!(
+        "{}: moved your reading list from {} to {}, its new default location",
+        "Info".bold().cyan(),
+        legacy_dir.display(),
+        rlist_dir.display()
+    );
+
+    let db_file = rlist_dir.join(file_name);
+    if storage == StorageKind::Sqlite && moved_snapshots {
+        let relocated = rusqlite::Connection::open(&db_file)
+            .map_err(anyhow::Error::from)
+            .and_then(|conn| {
+                if !column_exists(&conn, "rlist", "snapshot")? {
+                    return Ok(0);
+                }
+                DBSnapshot::relocate(
+                    &conn,
+                    &legacy_dir.join("snapshots").to_string_lossy(),
+                    &rlist_dir.join("snapshots").to_string_lossy(),
+                )
+            });
+        if let Err(err) = relocated {
+            eprintln!(
+                "{}: could not update the paths of the snapshots saved in {}: {err}",
+                "Warning".bold().yellow(),
+                legacy_dir.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the default config file, `$XDG_CONFIG_HOME/rlist/config.yml` (`~/.config/rlist/config.yml` by default),
+/// after moving the config file there from `rlist.yml` in the config folder, where older versions of rlist looked for it
+fn get_default_config_file_path() -> Result<String> {
+    let config_dir = xdg_dir("XDG_CONFIG_HOME", ".config", dirs::config_dir())?;
+    let default_config_path = config_dir.join("rlist").join("config.yml");
+
+    let legacy_config_path = config_dir.join("rlist.yml");
+    if legacy_config_path.is_file() && !default_config_path.exists() {
+        std::fs::create_dir_all(config_dir.join("rlist"))?;
+        std::fs::rename(&legacy_config_path, &default_config_path).with_context(|| {
+            format!(
+                "Could not move your config file from {} to {}",
+                legacy_config_path.display(),
+                default_config_path.display()
+            )
+        })?;
+        eprintln!(
+            "{}: moved your config file from {} to {}, its new default location",
+            "Info".bold().cyan(),
+            legacy_config_path.display(),
+            default_config_path.display()
+        );
+    }
 
-    let default_config_path = Path::new(config_dir_path.as_os_str()).join("rlist.yml");
     Ok(default_config_path
         .to_str()
         .ok_or(anyhow::anyhow!(
@@ -73,6 +453,12 @@ impl Config {
             }
         }).unwrap_or(DEFAULT_DATETIME_FORMAT.to_string());
 
+        let storage = content.storage.unwrap_or_default();
+        if storage.is_remote() && content.db_url.is_none() {
+            return Err(anyhow::anyhow!(
+                "The db_url config option must contain the connection string of the database when storage is a remote database"
+            ));
+        }
         let db_file_path = if let Some(p) = content.db_file {
             let path = Path::new(&p);
             if path.is_relative() {
@@ -80,12 +466,101 @@ impl Config {
             }
             p
         } else {
-            get_default_db_file_path()?.into()
+            get_default_db_file_path(storage)?.into()
+        };
+
+        if let Some(p) = content.snapshots_dir.as_ref() {
+            if p.is_relative() {
+                return Err(anyhow::anyhow!("The snapshots_dir config option must contain an absolute path to the desired snapshots location"));
+            }
+        }
+
+        if content.goals.iter().flatten().any(|g| g.entries < 1) {
+            return Err(anyhow::anyhow!(
+                "The goals config option must only contain goals of at least 1 entry"
+            ));
+        }
+
+        let topic_aliases = content.topic_aliases.unwrap_or_default();
+        if let Some((alias, topic)) = topic_aliases
+            .iter()
+            .find(|(_alias, topic)| topic_aliases.contains_key(*topic))
+        {
+            return Err(anyhow::anyhow!("The topic_aliases config option must not map an alias to another alias, but `{alias}` is mapped to `{topic}`"));
+        }
+
+        let tag_rules = content
+            .tag_rules
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(pattern, topics)| Ok((glob_to_regex(&pattern)?, topics)))
+            .collect::<Result<Vec<_>>>()?;
+
+        if let Some(backups) = content.backups.as_ref() {
+            if backups.dir.as_ref().is_some_and(|dir| dir.is_relative()) {
+                return Err(anyhow::anyhow!("The backups.dir config option must contain an absolute path to the desired backups location"));
+            }
+            if backups.keep == Some(0) {
+                return Err(anyhow::anyhow!(
+                    "The backups.keep config option must keep at least 1 backup"
+                ));
+            }
+        }
+
+        let profiles = content.profiles.unwrap_or_default();
+        if let Some(name) = profiles
+            .iter()
+            .find(|(_name, db_file)| db_file.is_relative())
+            .map(|(name, _db_file)| name)
+        {
+            return Err(anyhow::anyhow!("The profiles config option must map each profile to the absolute path of its db file, but `{name}` is mapped to a relative path"));
+        }
+
+        let aliases = content
+            .aliases
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(alias, command)| match shlex::split(&command) {
+                Some(args) if !args.is_empty() => Ok((alias, args)),
+                _ => Err(anyhow::anyhow!("The aliases config option must map each alias to a command, quoted like in a shell, but `{alias}` is mapped to `{command}`")),
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let theme = Theme {
+            topic_colors: content
+                .topic_colors
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(topic, color)| (Topic::normalize(topic), color))
+                .collect(),
+            ..content.theme.unwrap_or_default()
         };
+        if theme.topics.is_empty() {
+            return Err(anyhow::anyhow!(
+                "The theme.topics config option must contain at least one color"
+            ));
+        }
 
         Ok(Self {
+            path: None,
+            storage,
+            db_url: content.db_url,
+            db_auth_token: content.db_auth_token,
             db_file: db_file_path,
+            encrypted: content.encrypted.unwrap_or_default(),
+            remember_passphrase: content.remember_passphrase.unwrap_or_default(),
             datetime_format: format,
+            snapshots_dir: content.snapshots_dir,
+            url_canonicalization: content.url_canonicalization.unwrap_or_default(),
+            goals: content.goals.unwrap_or_default(),
+            topic_aliases,
+            tag_rules,
+            hooks: content.hooks.unwrap_or_default(),
+            backups: content.backups,
+            profiles,
+            list: content.list.unwrap_or_default(),
+            aliases,
+            theme,
         })
     }
 
@@ -94,20 +569,26 @@ impl Config {
             // If a custom config path is provided, then read it
             Some(p) => {
                 let file_content =
-                    std::fs::read_to_string(p).context("Could not read rlist config file")?;
+                    std::fs::read_to_string(&p).context("Could not read rlist config file")?;
                 let config_content: ConfigContent = serde_yaml::from_str(&file_content)?;
-                Ok(Self::new_from_content(config_content)?)
+                Ok(Self {
+                    path: Some(p),
+                    ..Self::new_from_content(config_content)?
+                })
             }
             None => {
                 // Else, if no custom path is provided look in the default location.
                 let default_config_path = get_default_config_file_path()?;
                 let config = if Path::new(&default_config_path).exists() {
-                    let config_data = std::fs::read_to_string(default_config_path)
+                    let config_data = std::fs::read_to_string(&default_config_path)
                         .context("Could not read rlist config file")?;
 
                     let config_content: ConfigContent = serde_yaml::from_str(&config_data)?;
 
-                    Self::new_from_content(config_content)?
+                    Self {
+                        path: Some(default_config_path.into()),
+                        ..Self::new_from_content(config_content)?
+                    }
                 } else {
                     // If no file is found in the default location, then use defaults
                     Self::maybe_default()?
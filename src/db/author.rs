@@ -0,0 +1,154 @@
+use anyhow::Result;
+use rusqlite::{named_params, Connection, OptionalExtension};
+
+use crate::{author::Author, error::RListError, read_sql_response, utils::escape_like};
+
+pub(crate) struct DBAuthor {}
+
+impl DBAuthor {
+    /// Moves the authors stored in the `author` column of the `rlist` table, used by older versions of rlist, to the `authors` table
+    pub(crate) fn migrate_author_column(conn: &Connection) -> Result<()> {
+        // Missing authors used to be stored as the string "NULL"
+        conn.execute_batch(
+            "
+            INSERT OR IGNORE INTO authors (name)
+                SELECT DISTINCT author FROM rlist WHERE author IS NOT NULL AND author != 'NULL';
+            UPDATE rlist SET author_id = (
+                SELECT author_id FROM authors WHERE authors.name = rlist.author
+            );
+            ALTER TABLE rlist DROP COLUMN author;",
+        )?;
+        Ok(())
+    }
+
+    /// Creates the author with name = `name` if it does not exist yet. Returns its id
+    pub(crate) fn get_or_create(conn: &Connection, name: impl AsRef<str>) -> Result<i64> {
+        let q = "INSERT INTO authors (name)
+            VALUES (:name)
+            ON CONFLICT (name) DO UPDATE SET name=name
+            RETURNING author_id;";
+        let mut stmt = conn.prepare_cached(q)?;
+
+        Ok(
+            stmt.query_row(named_params! {":name": name.as_ref()}, |row| {
+                row.get("author_id")
+            })?,
+        )
+    }
+
+    /// Gets an author_id given a name.
+    /// Returns None if no author with that name was found.
+    pub(crate) fn get_id_from_name(
+        conn: &Connection,
+        name: impl AsRef<str>,
+    ) -> Result<Option<i64>> {
+        let q = "SELECT author_id FROM authors WHERE name = :name;";
+        let mut stmt = conn.prepare_cached(q)?;
+
+        Ok(stmt
+            .query_row(named_params! {":name": name.as_ref()}, |row| {
+                row.get("author_id")
+            })
+            .optional()?)
+    }
+
+    /// Returns the authors that have at least one entry, along with the number of their entries.
+    /// If `filter` is set, only authors whose name contains it are returned.
+    /// Authors are sorted by name, or by number of entries (descending) if `by_count` is set
+    pub(crate) fn get_all_with_counts(
+        conn: &Connection,
+        filter: Option<&str>,
+        by_count: bool,
+    ) -> Result<Vec<Author>> {
+        let q = format!(
+            "SELECT
+                a.name AS name,
+                a.url AS url,
+                a.notes AS notes,
+                COUNT(*) AS count
+            FROM authors AS a
+            JOIN rlist AS ls
+                ON ls.author_id = a.author_id
+            {}
+            GROUP BY a.author_id
+            ORDER BY {};",
+            if filter.is_some() {
+                "WHERE a.name LIKE '%' || :filter || '%' ESCAPE '\\'"
+            } else {
+                ""
+            },
+            if by_count {
+                "count DESC, a.name COLLATE NOCASE"
+            } else {
+                "a.name COLLATE NOCASE"
+            }
+        );
+        let mut stmt = conn.prepare(&q)?;
+        let mut rows = match filter {
+            Some(filter) => stmt.query(named_params! {":filter": escape_like(filter)})?,
+            None => stmt.query([])?,
+        };
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, name => String, url => Option<String>, notes => Option<String>, count => i64);
+            res.push(Author {
+                name,
+                url,
+                notes,
+                entries: count,
+            });
+        }
+
+        Ok(res)
+    }
+
+    /// Renames the author `old` to `new`. If an author named `new` already exists, the entries of `old` are moved to it and `old` is deleted.
+    /// Returns the number of entries by the renamed author. Should be run inside of a transaction.
+    pub(crate) fn rename(conn: &Connection, old: &str, new: &str) -> Result<usize> {
+        let old_id =
+            Self::get_id_from_name(conn, old)?.ok_or_else(|| RListError::author_not_found(old))?;
+
+        let q = "SELECT COUNT(*) AS count FROM rlist WHERE author_id = :author_id;";
+        let count = conn.query_row(q, named_params! {":author_id": old_id}, |row| {
+            row.get::<_, i64>("count")
+        })?;
+
+        match Self::get_id_from_name(conn, new)? {
+            Some(new_id) if new_id != old_id => {
+                let q = "UPDATE rlist SET author_id = :new_id WHERE author_id = :old_id;";
+                conn.execute(q, named_params! {":new_id": new_id, ":old_id": old_id})?;
+
+                let q = "DELETE FROM authors WHERE author_id = :old_id;";
+                conn.execute(q, named_params! {":old_id": old_id})?;
+            }
+            _ => {
+                let q = "UPDATE authors SET name = :new WHERE author_id = :old_id;";
+                conn.execute(q, named_params! {":new": new, ":old_id": old_id})?;
+            }
+        }
+
+        Ok(count as usize)
+    }
+
+    /// Sets the url and/or the notes of the author with name = `name`. `None` values are left untouched
+    pub(crate) fn set_details(
+        conn: &Connection,
+        name: &str,
+        url: Option<&str>,
+        notes: Option<&str>,
+    ) -> Result<()> {
+        let q = "UPDATE authors
+            SET url = COALESCE(:url, url), notes = COALESCE(:notes, notes)
+            WHERE name = :name;";
+        let changed = conn.execute(
+            q,
+            named_params! {":url": url, ":notes": notes, ":name": name},
+        )?;
+
+        if changed == 0 {
+            return Err(RListError::author_not_found(name).into());
+        }
+        Ok(())
+    }
+}
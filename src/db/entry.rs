@@ -1,17 +1,54 @@
+use crate::theme::Themed;
+use std::collections::HashMap;
+
 use anyhow::Result;
 use colored::Colorize;
+use rusqlite::{named_params, params_from_iter, types::Value, Connection, OptionalExtension, Row};
 
-use crate::db::topic::DBTopic;
+use crate::db::{author::DBAuthor, meta::DBMeta, topic::DBTopic};
 use crate::entry::Entry;
+use crate::error::{Entity, ErrorCode, RListError};
 use crate::read_sql_response;
-use crate::utils::{get_conflicting_column_name, opt_from_sql, ToSQL};
+use crate::topic::Topic;
+use crate::utils::{domain_of, get_conflicting_column_name, normalize_name};
+
+/// Selects the rows of the `rlist AS ls` table along with the name of their author, as expected by `DBEntry::from_row`
+pub(crate) const SELECT_ENTRY: &str = "SELECT ls.*, a.name AS author
+    FROM rlist AS ls
+    LEFT OUTER JOIN authors AS a
+        ON a.author_id = ls.author_id";
+
+/// The columns of the `rlist` table. Names and urls are unique regardless of their case
+pub(crate) const RLIST_COLUMNS: &str = "
+    entry_id INTEGER PRIMARY KEY,
+    name TEXT NON NULL UNIQUE COLLATE NOCASE,
+    url TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    author_id INTEGER REFERENCES authors (author_id) ON UPDATE CASCADE ON DELETE SET NULL,
+    added DATETIME NOT NULL DEFAULT (datetime('now', 'localtime')),
+    modified DATETIME,
+    snoozed_until DATETIME,
+    domain TEXT,
+    length INTEGER,
+    site_name TEXT,
+    description TEXT,
+    archive_url TEXT,
+    snapshot TEXT,
+    archived BOOLEAN NOT NULL DEFAULT 0,
+    read_at DATETIME,
+    opens INTEGER NOT NULL DEFAULT 0,
+    due DATETIME
+";
+
+/// Separates the names of the topics of an entry when they are aggregated in a single column.
+/// It is the ASCII unit separator, a control character that does not appear in the names of topics
+const TOPIC_SEPARATOR: char = '\x1f';
 
 pub struct DBEntry {}
 
 impl DBEntry {
     /// Associates the entry identified by `entry_id` to all of the topics identified by `topic_ids`
     pub(crate) fn associate_with_topics(
-        conn: &sqlite::Connection,
+        conn: &Connection,
         entry_id: i64,
         topic_ids: Vec<i64>,
     ) -> Result<()> {
@@ -25,153 +62,154 @@ impl DBEntry {
                 .join(", ")
         );
 
-        let mut stmt = conn.prepare(q)?;
-        let bindings = (0..2 * topic_ids.len())
-            .map(|idx| {
-                if idx % 2 == 0 {
-                    (idx + 1, entry_id)
-                } else {
-                    (idx + 1, topic_ids[(idx - 1) / 2])
-                }
-            })
-            .collect::<Vec<_>>();
-
-        stmt.bind(bindings.as_slice())?;
-        stmt.next()?;
+        let bindings = topic_ids.iter().flat_map(|topic_id| [entry_id, *topic_id]);
+        conn.execute(&q, params_from_iter(bindings))?;
 
         Ok(())
     }
 
     /// Creates a new entry in the db. Does not handle topics. Returns a tuple containing the entry_id and the entry
-    /// Expects added to be formatted like SQLITE_DATETIME_FORMAT
+    /// Expects added to be formatted like SQLITE_DATETIME_FORMAT.
+    /// The entry gets the id `entry_id` if it is set and no other entry has it, and a new id otherwise
     pub(crate) fn create(
-        conn: &sqlite::Connection,
+        conn: &Connection,
         name: &str,
         url: &str,
         author: Option<&str>,
+        length: Option<i64>,
         added: Option<&str>,
+        entry_id: Option<i64>,
     ) -> Result<(i64, Entry)> {
-        let q = if added.is_some() {
-            "INSERT INTO rlist (name, url, author, added) VALUES (:name, :url, :author, :added) RETURNING *"
-        } else {
-            "INSERT INTO rlist (name, url, author) VALUES (:name, :url, :author) RETURNING *"
-        };
-        let mut stmt = conn.prepare(q)?;
-
-        if added.is_some() {
-            stmt.bind(
-                &[
-                    (":name", name),
-                    (":url", url),
-                    (":author", author.to_sql().as_ref()),
-                    (":added", added.as_deref().unwrap()), // expected to be in the right format
-                ][..],
-            )?;
-        } else {
-            stmt.bind(
-                &[
-                    (":name", name),
-                    (":url", url),
-                    (":author", author.to_sql().as_ref()),
-                ][..],
-            )?;
-        }
+        let name = normalize_name(name);
+        let name = name.as_str();
+        // `added` is expected to be in the right format
+        let q = "INSERT INTO rlist (entry_id, name, url, author_id, added, modified, domain, length)
+            VALUES (
+                (SELECT :entry_id WHERE NOT EXISTS (SELECT 1 FROM rlist WHERE entry_id = :entry_id)),
+                :name,
+                :url,
+                :author_id,
+                COALESCE(:added, datetime('now', 'localtime')),
+                COALESCE(:added, datetime('now', 'localtime')),
+                :domain,
+                :length
+            )
+            RETURNING entry_id, added, modified;";
+
+        let author_id = author
+            .map(|a| DBAuthor::get_or_create(conn, a))
+            .transpose()?;
+        let domain = domain_of(url).unwrap_or_default();
+
+        let mut stmt = conn.prepare_cached(q)?;
+        let res = stmt.query_row(
+            named_params! {
+                ":entry_id": entry_id,
+                ":name": name,
+                ":url": url,
+                ":author_id": author_id,
+                ":added": added,
+                ":domain": domain,
+                ":length": length,
+            },
+            |row| {
+                read_sql_response!(row, entry_id => i64, added => String, modified => String);
+                Ok((entry_id, added, modified))
+            },
+        );
 
-        match stmt.next() {
-            Ok(sqlite::State::Done) => {
+        let (entry_id, added, modified) = match res {
+            Ok(res) => res,
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
                 return Err(anyhow::anyhow!(
                     "Could not insert entry because of an unknown error."
                 ));
             }
             Err(err) => {
-                if matches!(err.code, Some(19)) {
-                    if let Some(col) = get_conflicting_column_name(&err) {
-                        return match col.split_once(".") {
-                            Some((_, col_name)) => Err(anyhow::anyhow!("Could not create entry with name {} beacuase your reading list already contains an entry with the same value for {}", name.bold().truecolor(255, 165, 0), col_name.bold().red())),
-                            None => Err(anyhow::anyhow!("Could not create entry with name {} because your reading list already contains an entry that has the same value for name or url", name.bold().truecolor(255, 165, 0))), // Should be unreachable
-                        };
-                    }
+                if let Some(col) = get_conflicting_column_name(&err) {
+                    return match col.split_once(".") {
+                        Some((_, col_name)) => {
+                            let value = if col_name == "url" { url } else { name };
+                            Err(RListError::new(
+                                ErrorCode::AlreadyExists,
+                                Entity::new("entry", if col_name == "url" { "url" } else { "name" }, value),
+                                format!("Could not create entry with name {} beacuase your reading list already contains an entry with the same value for {}", name.entry_name(), col_name.bold().red()),
+                            ).into())
+                        }
+                        None => Err(anyhow::anyhow!("Could not create entry with name {} because your reading list already contains an entry that has the same value for name or url", name.entry_name())), // Should be unreachable
+                    };
                 }
                 return Err(err.into());
             }
-            _ => {}
-        }
-
-        read_sql_response!(stmt, entry_id => i64, added => String);
-        Ok((
-            entry_id,
-            Entry::new(
-                name.to_string(),
-                url.to_string(),
-                author.map(|s| s.into()),
-                vec![],
-                Some(added),
-            ),
-        ))
+        };
+        let mut entry = Entry::new(
+            name.to_string(),
+            url.to_string(),
+            author.map(|s| s.into()),
+            vec![],
+            Some(added),
+        );
+        entry.id = Some(entry_id);
+        entry.modified = Some(modified);
+        entry.length = length;
+        Ok((entry_id, entry))
     }
 
-    //? is it possible to write a subquery in the RETURNING clause to return all of the topics instead of doing 2 queries?
     /// Removes the entry with name = `name`.
-    /// Returns the old entry's data with all of its topic
-    pub(crate) fn remove_by_name(
-        conn: &sqlite::Connection,
-        name: impl AsRef<str>,
-    ) -> Result<Entry> {
-        let entry_id = Self::get_id_from_name(conn, name.as_ref())?;
-        let entry_id = entry_id.ok_or(anyhow::anyhow!(
-            "Could not find any entry with name {} in your reading list",
-            name.as_ref().bold().truecolor(255, 165, 0)
-        ))?;
-
-        let topics = DBTopic::get_related_to(conn, entry_id)?
-            .into_iter()
-            .map(|(_i, t)| t)
-            .collect::<Vec<_>>();
-
-        let q = "DELETE FROM rlist WHERE name = :entry_name RETURNING *;";
-        let mut stmt = conn.prepare(q)?;
-        stmt.bind((":entry_name", name.as_ref()))?;
-        // No need to check it is == State::Done since i already check that it exists with Self::get_id_from_name()
-        stmt.next()?;
+    /// Returns the old entry's data with all of its topics and metadata
+    pub(crate) fn remove_by_name(conn: &Connection, name: impl AsRef<str>) -> Result<Entry> {
+        let (entry_id, _entry) = Self::get_by_name_without_topics(conn, name.as_ref())?;
+        let entry = Self::get_complete_by_id(conn, entry_id)?;
 
-        read_sql_response!(stmt, name => String, url => String, added => String, author => String);
-        let author = opt_from_sql(author);
+        let q = "DELETE FROM rlist WHERE entry_id = :entry_id;";
+        conn.prepare_cached(q)?
+            .execute(named_params! {":entry_id": entry_id})?;
 
-        Ok(Entry::new(name, url, author, topics, Some(added)))
+        Ok(entry)
     }
 
     /// Gets an entry_id given a name.
     /// Returns None if no entry with that name was found.
     pub(crate) fn get_id_from_name(
-        conn: &sqlite::Connection,
+        conn: &Connection,
         name: impl AsRef<str>,
     ) -> Result<Option<i64>> {
         let q = "SELECT entry_id FROM rlist WHERE name=:name;";
-        let mut stmt = conn.prepare(q)?;
-        stmt.bind((":name", name.as_ref()))?;
-        if let sqlite::State::Done = stmt.next()? {
-            return Ok(None);
-        }
-        let entry_id = stmt.read::<i64, _>("entry_id")?;
-        Ok(Some(entry_id))
+        let mut stmt = conn.prepare_cached(q)?;
+        let entry_id = stmt
+            .query_row(
+                named_params! {":name": normalize_name(name.as_ref())},
+                |row| row.get("entry_id"),
+            )
+            .optional()?;
+        Ok(entry_id)
+    }
+
+    /// Gets an entry_id given a url.
+    /// Returns None if no entry with that url was found.
+    pub(crate) fn get_id_from_url(conn: &Connection, url: &str) -> Result<Option<i64>> {
+        let q = "SELECT entry_id FROM rlist WHERE url = :url;";
+        let mut stmt = conn.prepare_cached(q)?;
+        let entry_id = stmt
+            .query_row(named_params! {":url": url}, |row| row.get("entry_id"))
+            .optional()?;
+        Ok(entry_id)
     }
 
     /// Removes the entry with `entry_id` from all of its topics.
-    pub(crate) fn unlink_all_topics(conn: &sqlite::Connection, entry_id: i64) -> Result<()> {
+    pub(crate) fn unlink_all_topics(conn: &Connection, entry_id: i64) -> Result<()> {
         let q = "DELETE FROM rlist_has_topic 
                     WHERE entry_id = :entry_id;";
 
-        let mut stmt = conn.prepare(q)?;
-
-        stmt.bind((":entry_id", entry_id))?;
-        stmt.next()?;
+        conn.prepare_cached(q)?
+            .execute(named_params! {":entry_id": entry_id})?;
 
         Ok(())
     }
 
     /// Removes the entry with id = `entry_id` from all of the topics in `topics`
     pub(crate) fn unlink_topics_by_name(
-        conn: &sqlite::Connection,
+        conn: &Connection,
         entry_id: i64,
         topics: Vec<String>,
     ) -> Result<()> {
@@ -180,105 +218,469 @@ impl DBEntry {
                     WHERE entry_id = ?
                         AND topic_id IN (
                             SELECT topic_id FROM topics WHERE name IN ({})
-                    ) RETURNING *;",
+                    );",
             (0..topics.len())
                 .map(|_e| "?")
                 .collect::<Vec<_>>()
                 .join(", "),
         );
 
+        let bindings = [Value::from(entry_id)]
+            .into_iter()
+            .chain(topics.iter().map(|t| Value::from(Topic::normalize(t))));
+        conn.execute(&q, params_from_iter(bindings))?;
+
+        Ok(())
+    }
+
+    /// Returns the tuple (entry_id, Entry) containing the entry with name = `name`
+    pub(crate) fn get_by_name_without_topics(
+        conn: &Connection,
+        name: impl AsRef<str>,
+    ) -> Result<(i64, Entry)> {
+        let q = format!("{SELECT_ENTRY} WHERE ls.name = :name;");
+        let mut stmt = conn.prepare_cached(&q)?;
+        let mut rows = stmt.query(named_params! {":name": normalize_name(name.as_ref())})?;
+
+        match rows.next()? {
+            Some(row) => Self::from_row(row),
+            None => Err(RListError::entry_not_found(name).into()),
+        }
+    }
+
+    /// Rebuilds the `rlist` table of the databases created by older versions of rlist, where names and urls were case sensitive.
+    /// The table is left untouched (with a warning) while some entries only differ by the case of their name or url
+    pub(crate) fn make_name_and_url_case_insensitive(conn: &Connection) -> Result<()> {
+        let sql: String = conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'rlist';",
+            [],
+            |row| row.get("sql"),
+        )?;
+        if sql.contains("COLLATE NOCASE") {
+            return Ok(());
+        }
+
+        let q = "SELECT a.name AS name, b.name AS other
+            FROM rlist AS a
+            JOIN rlist AS b
+                ON a.entry_id < b.entry_id
+                AND (a.name = b.name COLLATE NOCASE OR a.url = b.url COLLATE NOCASE);";
         let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query([])?;
+        let mut clashes = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, name => String, other => String);
+            clashes.push(format!("{name} and {other}"));
+        }
+        if !clashes.is_empty() {
+            eprintln!(
+                "{}: these entries have the same name or url, except for the case: {}. Rename or merge them (e.g. with `rlist merge`), so that rlist can treat names and urls as case insensitive",
+                "Warning".bold().yellow(),
+                clashes.join(", ")
+            );
+            return Ok(());
+        }
 
-        let bindings = [(1, sqlite::Value::from(entry_id))].into_iter().chain(
-            topics
-                .iter()
-                .enumerate()
-                .map(|(i, t)| (i + 2, sqlite::Value::from(t.as_str()))),
+        let mut stmt = conn.prepare("PRAGMA table_info(rlist);")?;
+        let columns = stmt
+            .query_map([], |row| row.get::<_, String>("name"))?
+            .collect::<rusqlite::Result<Vec<_>>>()?
+            .join(", ");
+
+        // The foreign keys must be disabled (which can only be done outside of a transaction),
+        // otherwise dropping the old table would delete the topics, the metadata and the history of the entries
+        let q = format!(
+            "PRAGMA foreign_keys = OFF;
+            BEGIN TRANSACTION;
+            CREATE TABLE rlist_nocase ({RLIST_COLUMNS});
+            INSERT INTO rlist_nocase ({columns}) SELECT {columns} FROM rlist;
+            DROP TABLE rlist;
+            ALTER TABLE rlist_nocase RENAME TO rlist;
+            COMMIT;
+            PRAGMA foreign_keys = ON;"
         );
+        if let Err(err) = conn.execute_batch(&q) {
+            let _ = conn.execute_batch("ROLLBACK;");
+            conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+            return Err(err.into());
+        }
+        Ok(())
+    }
 
-        stmt.bind_iter(bindings)?;
-        stmt.next()?;
+    /// Returns the id and the name of every entry
+    pub(crate) fn get_all_names(conn: &Connection) -> Result<Vec<(i64, String)>> {
+        let mut stmt = conn.prepare("SELECT entry_id, name FROM rlist ORDER BY entry_id;")?;
+        let names = stmt.query_map([], |row| Ok((row.get("entry_id")?, row.get("name")?)))?;
+        Ok(names.collect::<rusqlite::Result<_>>()?)
+    }
 
+    /// Sets the name of the entry with id = `entry_id` to `name`
+    pub(crate) fn set_name(conn: &Connection, entry_id: i64, name: &str) -> Result<()> {
+        let q = "UPDATE rlist SET name = :name WHERE entry_id = :entry_id;";
+        conn.execute(q, named_params! {":name": name, ":entry_id": entry_id})?;
         Ok(())
     }
 
-    /// Returns the tuple (entry_id, Entry) containing the entry with name = `name`
-    pub(crate) fn get_by_name_without_topics(
-        conn: &sqlite::Connection,
-        name: impl AsRef<str>,
-    ) -> Result<(i64, crate::Entry)> {
-        let q = "SELECT * FROM rlist WHERE name = :name;";
+    /// Computes the `domain` column of the entries that were created before it existed
+    pub(crate) fn fill_missing_domains(conn: &Connection) -> Result<()> {
+        let q = "SELECT entry_id, url FROM rlist WHERE domain IS NULL;";
         let mut stmt = conn.prepare(q)?;
-        stmt.bind((":name", name.as_ref()))?;
+        let mut rows = stmt.query([])?;
+        let mut missing = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, entry_id => i64, url => String);
+            missing.push((entry_id, url));
+        }
 
-        if let sqlite::State::Done = stmt.next()? {
-            return Err(anyhow::anyhow!(
-                "Could not find any entry in your reading list with name {}",
-                name.as_ref().bold().truecolor(255, 165, 0)
-            ));
+        let q = "UPDATE rlist SET domain = :domain WHERE entry_id = :entry_id;";
+        let mut stmt = conn.prepare(q)?;
+        for (entry_id, url) in missing {
+            stmt.execute(named_params! {
+                ":domain": domain_of(&url).unwrap_or_default(),
+                ":entry_id": entry_id,
+            })?;
         }
 
-        read_sql_response!(stmt, entry_id => i64, name => String, url => String, added => String, author => String);
-        let author = opt_from_sql(author);
-
-        Ok((
-            entry_id,
-            Entry::new(name, url, author, Vec::new(), Some(added)),
-        ))
-    }
-
-    /// Returns all entries with all of their topics
-    pub(crate) fn get_all_complete(conn: &sqlite::Connection) -> Result<Vec<Entry>> {
-        let q = "
-        SELECT 
-            ls.name AS name, 
-            ls.url AS url, 
-            ls.author AS author, 
-            ls.added AS added, 
-            t.name AS topic 
-        FROM rlist AS ls 
-        LEFT OUTER JOIN rlist_has_topic AS rht 
-            ON ls.entry_id = rht.entry_id 
-        LEFT OUTER JOIN topics AS t 
-            ON t.topic_id = rht.topic_id;";
+        Ok(())
+    }
 
+    /// Returns the entry with id = `entry_id`, without its topics
+    pub(crate) fn get_by_id_without_topics(conn: &Connection, entry_id: i64) -> Result<Entry> {
+        let q = format!("{SELECT_ENTRY} WHERE ls.entry_id = :entry_id;");
+        let mut stmt = conn.prepare_cached(&q)?;
+        let mut rows = stmt.query(named_params! {":entry_id": entry_id})?;
+
+        match rows.next()? {
+            Some(row) => Ok(Self::from_row(row)?.1),
+            None => Err(RListError::entry_id_not_found(entry_id).into()),
+        }
+    }
+
+    /// Returns the entry with id = `entry_id` along with its topics and metadata
+    pub(crate) fn get_complete_by_id(conn: &Connection, entry_id: i64) -> Result<Entry> {
+        let mut entry = Self::get_by_id_without_topics(conn, entry_id)?;
+        entry.topics = DBTopic::get_related_to(conn, entry_id)?
+            .into_iter()
+            .map(|(_i, t)| t)
+            .collect();
+        entry.meta = DBMeta::get_related_to(conn, entry_id)?;
+
+        Ok(entry)
+    }
+
+    /// Reads an entry (without its topics) from `row`, which must contain all of the columns of the `rlist` table
+    /// and the name of the author as `author` (see `SELECT_ENTRY`).
+    /// Returns the tuple (entry_id, Entry)
+    pub(crate) fn from_row(row: &Row) -> Result<(i64, Entry)> {
+        read_sql_response!(row, entry_id => i64, name => String, url => String, added => String, author => Option<String>, modified => Option<String>, snoozed_until => Option<String>, length => Option<i64>, site_name => Option<String>, description => Option<String>, archive_url => Option<String>, snapshot => Option<String>, archived => bool, read_at => Option<String>, opens => i64, due => Option<String>);
+
+        let mut entry = Entry::new(name, url, author, Vec::new(), Some(added));
+        entry.id = Some(entry_id);
+        entry.modified = modified;
+        entry.snoozed_until = snoozed_until;
+        entry.length = length;
+        entry.site_name = site_name;
+        entry.description = description;
+        entry.archive_url = archive_url;
+        entry.snapshot = snapshot;
+        entry.archived = archived;
+        entry.read_at = read_at;
+        entry.opens = opens;
+        entry.due = due;
+        Ok((entry_id, entry))
+    }
+
+    /// Sets the `added` date of the entry with id = `entry_id` to `added`, if `added` is older than the current one
+    pub(crate) fn keep_oldest_added(conn: &Connection, entry_id: i64, added: &str) -> Result<()> {
+        let q = "UPDATE rlist SET added = MIN(added, :added) WHERE entry_id = :entry_id;";
+        conn.execute(q, named_params! {":added": added, ":entry_id": entry_id})?;
+        Ok(())
+    }
+
+    /// Sets the path of the snapshot of the entry with id = `entry_id`
+    pub(crate) fn set_snapshot(conn: &Connection, entry_id: i64, snapshot: &str) -> Result<()> {
+        let q = "UPDATE rlist SET snapshot = :snapshot WHERE entry_id = :entry_id;";
+        conn.execute(
+            q,
+            named_params! {":snapshot": snapshot, ":entry_id": entry_id},
+        )?;
+        Ok(())
+    }
+
+    /// Sets the url of the snapshot of the entry with id = `entry_id`
+    pub(crate) fn set_archive_url(
+        conn: &Connection,
+        entry_id: i64,
+        archive_url: &str,
+    ) -> Result<()> {
+        let q = "UPDATE rlist SET archive_url = :archive_url WHERE entry_id = :entry_id;";
+        conn.execute(
+            q,
+            named_params! {":archive_url": archive_url, ":entry_id": entry_id},
+        )?;
+        Ok(())
+    }
+
+    /// Returns the names of the entries that have not been archived yet, oldest first
+    pub(crate) fn names_not_archived(conn: &Connection) -> Result<Vec<String>> {
+        let q = "SELECT name FROM rlist WHERE archive_url IS NULL ORDER BY added ASC;";
         let mut stmt = conn.prepare(q)?;
+        let names = stmt.query_map([], |row| row.get("name"))?;
+        Ok(names.collect::<rusqlite::Result<_>>()?)
+    }
 
-        let mut res: Vec<Entry> = Vec::new();
+    /// Sets the author, the site name and the description of the entry with id = `entry_id`, unless they are already set
+    pub(crate) fn fill_details(
+        conn: &Connection,
+        entry_id: i64,
+        author_id: Option<i64>,
+        site_name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<()> {
+        let q = "UPDATE rlist
+            SET author_id = COALESCE(author_id, :author_id),
+                site_name = COALESCE(site_name, :site_name),
+                description = COALESCE(description, :description)
+            WHERE entry_id = :entry_id;";
+        conn.execute(
+            q,
+            named_params! {
+                ":author_id": author_id,
+                ":site_name": site_name,
+                ":description": description,
+                ":entry_id": entry_id,
+            },
+        )?;
+        Ok(())
+    }
 
-        while let sqlite::State::Row = stmt.next()? {
-            let name = stmt.read::<String, _>("name")?;
-            let topic = stmt.read::<String, _>("topic").ok();
+    /// Returns the names of the entries that have no author, site name or description
+    pub(crate) fn names_missing_details(conn: &Connection) -> Result<Vec<String>> {
+        let q = "SELECT name FROM rlist
+            WHERE author_id IS NULL OR site_name IS NULL OR description IS NULL
+            ORDER BY added ASC;";
+        let mut stmt = conn.prepare(q)?;
+        let names = stmt.query_map([], |row| row.get("name"))?;
+        Ok(names.collect::<rusqlite::Result<_>>()?)
+    }
 
-            if let Some(pos) = res.iter().position(|e| e.name == name) {
-                if topic.is_some() {
-                    res[pos].topics.push(topic.unwrap());
-                }
-            } else {
-                read_sql_response!(stmt, url => String, added => String, author => String);
-                let author = opt_from_sql(author);
+    /// Sets the date until which the entry with id = `entry_id` is snoozed. `None` wakes the entry up
+    pub(crate) fn set_snoozed_until(
+        conn: &Connection,
+        entry_id: i64,
+        until: Option<&str>,
+    ) -> Result<()> {
+        let q = "UPDATE rlist SET snoozed_until = :until WHERE entry_id = :entry_id;";
+        conn.execute(q, named_params! {":until": until, ":entry_id": entry_id})?;
+        Ok(())
+    }
 
-                let topics = topic.map(|t| vec![t]).unwrap_or_default();
+    /// Sets the `modified` date of the entry with id = `entry_id` to now
+    pub(crate) fn touch(conn: &Connection, entry_id: i64) -> Result<()> {
+        let q =
+            "UPDATE rlist SET modified = datetime('now', 'localtime') WHERE entry_id = :entry_id;";
+        conn.prepare_cached(q)?
+            .execute(named_params! {":entry_id": entry_id})?;
+        Ok(())
+    }
 
-                let entry = Entry::new(name.clone(), url, author, topics, Some(added));
-                res.push(entry);
-            }
+    /// Overwrites the entry with id = `entry_id` with the data in `entry`: every column, its topics and its metadata
+    pub(crate) fn overwrite(conn: &Connection, entry_id: i64, entry: &Entry) -> Result<()> {
+        let author_id = entry
+            .author
+            .as_deref()
+            .map(|a| DBAuthor::get_or_create(conn, a))
+            .transpose()?;
+
+        let q = "UPDATE rlist
+            SET name = :name,
+                url = :url,
+                domain = :domain,
+                author_id = :author_id,
+                length = :length,
+                added = :added
+            WHERE entry_id = :entry_id;";
+        conn.execute(
+            q,
+            named_params! {
+                ":name": normalize_name(&entry.name),
+                ":url": entry.url,
+                ":domain": domain_of(&entry.url).unwrap_or_default(),
+                ":author_id": author_id,
+                ":length": entry.length,
+                ":added": entry.added,
+                ":entry_id": entry_id,
+            },
+        )?;
+        Self::restore_details(conn, entry_id, entry)?;
+
+        Self::unlink_all_topics(conn, entry_id)?;
+        if !entry.topics.is_empty() {
+            let topic_ids = DBTopic::create_many(conn, &entry.topics)?;
+            Self::associate_with_topics(conn, entry_id, topic_ids)?;
         }
-        Ok(res)
+
+        DBMeta::remove_all(conn, entry_id)?;
+        DBMeta::set_many(conn, entry_id, &entry.meta.iter().collect::<Vec<_>>())?;
+        Ok(())
     }
 
-    pub(crate) fn remove_related_to(conn: &sqlite::Connection, topic_id: i64) -> Result<()> {
-        let q = "DELETE FROM rlist 
-        WHERE entry_id IN (
-            SELECT entry_id 
-            FROM rlist_has_topic 
-            WHERE topic_id = :topic_id
-        );";
+    /// Sets the columns of the entry with id = `entry_id` that are not set by [`DBEntry::create`] to the values in `entry`:
+    /// `snoozed_until`, `site_name`, `description`, `archive_url`, `snapshot`, `archived`, `read_at`, `opens`, `due` and `modified` (if set)
+    pub(crate) fn restore_details(conn: &Connection, entry_id: i64, entry: &Entry) -> Result<()> {
+        let q = "UPDATE rlist
+            SET snoozed_until = :snoozed_until,
+                site_name = :site_name,
+                description = :description,
+                archive_url = :archive_url,
+                snapshot = :snapshot,
+                archived = :archived,
+                read_at = :read_at,
+                opens = :opens,
+                due = :due,
+                modified = COALESCE(:modified, modified)
+            WHERE entry_id = :entry_id;";
+        conn.prepare_cached(q)?.execute(named_params! {
+            ":snoozed_until": entry.snoozed_until,
+            ":site_name": entry.site_name,
+            ":description": entry.description,
+            ":archive_url": entry.archive_url,
+            ":snapshot": entry.snapshot,
+            ":archived": entry.archived,
+            ":read_at": entry.read_at,
+            ":opens": entry.opens,
+            ":due": entry.due,
+            ":modified": entry.modified,
+            ":entry_id": entry_id,
+        })?;
+        Ok(())
+    }
 
-        let mut stmt = conn.prepare(q)?;
-        stmt.bind((":topic_id", topic_id))?;
-        stmt.next()?;
+    /// Archives (or unarchives, if `!archived`) the entry with id = `entry_id`
+    pub(crate) fn set_archived(conn: &Connection, entry_id: i64, archived: bool) -> Result<()> {
+        let q = "UPDATE rlist SET archived = :archived WHERE entry_id = :entry_id;";
+        conn.execute(
+            q,
+            named_params! {":archived": archived, ":entry_id": entry_id},
+        )?;
+        Ok(())
+    }
+
+    /// Marks the entry with id = `entry_id` as read now (or as unread, if `!read`).
+    /// Entries that were already read keep their original date
+    pub(crate) fn set_read(conn: &Connection, entry_id: i64, read: bool) -> Result<()> {
+        let q = "UPDATE rlist
+            SET read_at = CASE WHEN :read THEN COALESCE(read_at, datetime('now', 'localtime')) ELSE NULL END
+            WHERE entry_id = :entry_id;";
+        conn.execute(q, named_params! {":read": read, ":entry_id": entry_id})?;
+        Ok(())
+    }
 
+    /// Sets the due date of the entry with id = `entry_id` to `due` (formatted like SQLITE_DATETIME_FORMAT), or removes it if `None`
+    pub(crate) fn set_due(conn: &Connection, entry_id: i64, due: Option<&str>) -> Result<()> {
+        let q = "UPDATE rlist SET due = :due WHERE entry_id = :entry_id;";
+        conn.execute(q, named_params! {":due": due, ":entry_id": entry_id})?;
         Ok(())
     }
+
+    /// Increments the number of times the entry with name = `name` was opened. Returns the id of the updated entry
+    pub(crate) fn increment_opens(conn: &Connection, name: impl AsRef<str>) -> Result<i64> {
+        let q = "UPDATE rlist SET opens = opens + 1 WHERE name = :name RETURNING entry_id;";
+        let entry_id = conn
+            .query_row(
+                q,
+                named_params! {":name": normalize_name(name.as_ref())},
+                |row| row.get("entry_id"),
+            )
+            .optional()?;
+
+        Ok(entry_id.ok_or_else(|| RListError::entry_not_found(name))?)
+    }
+
+    /// Returns all entries with all of their topics and metadata
+    pub(crate) fn get_all_complete(conn: &Connection) -> Result<Vec<Entry>> {
+        Self::get_matching_complete(conn, "", "ORDER BY ls.entry_id", Vec::new())
+    }
+
+    /// Returns the entries of the `rlist AS ls` table selected by `where_clause`, with all of their topics and metadata,
+    /// sorted by `order_clause`. Both clauses can refer to the author of the entries as `a`.
+    /// `bindings` are bound to the positional parameters of `where_clause`
+    pub(crate) fn get_matching_complete(
+        conn: &Connection,
+        where_clause: &str,
+        order_clause: &str,
+        bindings: Vec<Value>,
+    ) -> Result<Vec<Entry>> {
+        // The topics are aggregated in a single column, so that each entry is a single row and keeps its place in the order
+        let q = format!(
+            "
+            SELECT 
+                ls.*, 
+                a.name AS author,
+                GROUP_CONCAT(t.name, char({})) AS topics
+            FROM rlist AS ls 
+            LEFT OUTER JOIN authors AS a
+                ON a.author_id = ls.author_id
+            LEFT OUTER JOIN rlist_has_topic AS rht 
+                ON ls.entry_id = rht.entry_id 
+            LEFT OUTER JOIN topics AS t 
+                ON t.topic_id = rht.topic_id
+            {where_clause}
+            GROUP BY ls.entry_id
+            {order_clause};",
+            TOPIC_SEPARATOR as u32
+        );
+
+        let mut stmt = conn.prepare(&q)?;
+        let mut rows = stmt.query(params_from_iter(bindings.iter()))?;
+
+        let mut res: Vec<(i64, Entry)> = Vec::new();
+        while let Some(row) = rows.next()? {
+            let (entry_id, mut entry) = Self::from_row(row)?;
+            read_sql_response!(row, topics => Option<String>);
+            entry.topics = topics
+                .map(|t| t.split(TOPIC_SEPARATOR).map(String::from).collect())
+                .unwrap_or_default();
+            res.push((entry_id, entry));
+        }
+
+        let mut meta = DBMeta::get_matching(conn, where_clause, bindings)?;
+        Ok(res
+            .into_iter()
+            .map(|(entry_id, mut entry)| {
+                entry.meta = meta.remove(&entry_id).unwrap_or_default();
+                entry
+            })
+            .collect())
+    }
+
+    /// Returns the entries with id in `entry_ids`, with all of their topics and metadata, in the same order as `entry_ids`.
+    /// The ids that do not belong to any entry are skipped
+    pub(crate) fn get_complete_by_ids(conn: &Connection, entry_ids: &[i64]) -> Result<Vec<Entry>> {
+        let placeholders = vec!["?"; entry_ids.len()].join(", ");
+        let bindings = entry_ids.iter().map(|id| Value::Integer(*id)).collect();
+        let mut entries: HashMap<i64, Entry> = Self::get_matching_complete(
+            conn,
+            &format!("WHERE ls.entry_id IN ({placeholders})"),
+            "",
+            bindings,
+        )?
+        .into_iter()
+        .filter_map(|e| Some((e.id?, e)))
+        .collect();
+        Ok(entry_ids
+            .iter()
+            .filter_map(|id| entries.remove(id))
+            .collect())
+    }
+
+    /// Returns the ids of the entries related to the topic with id = `topic_id`
+    pub(crate) fn get_ids_related_to(conn: &Connection, topic_id: i64) -> Result<Vec<i64>> {
+        let q = "SELECT entry_id FROM rlist_has_topic WHERE topic_id = :topic_id;";
+        let mut stmt = conn.prepare(q)?;
+        let ids = stmt.query_map(named_params! {":topic_id": topic_id}, |row| {
+            row.get("entry_id")
+        })?;
+        Ok(ids.collect::<rusqlite::Result<_>>()?)
+    }
 }
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use rusqlite::{named_params, Connection};
+
+use crate::entry::Entry;
+use crate::read_sql_response;
+use crate::rlist::HistoryRecord;
+
+pub(crate) struct DBHistory {}
+
+impl DBHistory {
+    /// Records every field that differs between `before` and `after` as a change of the entry with id = `entry_id`.
+    /// Returns whether any field changed
+    pub(crate) fn record(
+        conn: &Connection,
+        entry_id: i64,
+        before: &Entry,
+        after: &Entry,
+    ) -> Result<bool> {
+        let mut changed = false;
+        let q = "INSERT INTO history (entry_id, field, old_value, new_value)
+            VALUES (:entry_id, :field, :old_value, :new_value);";
+        let mut stmt = conn.prepare_cached(q)?;
+        for ((field, old), (_field, new)) in
+            Self::fields(before).into_iter().zip(Self::fields(after))
+        {
+            if old == new {
+                continue;
+            }
+            changed = true;
+            stmt.execute(named_params! {
+                ":entry_id": entry_id,
+                ":field": field,
+                ":old_value": old,
+                ":new_value": new,
+            })?;
+        }
+        Ok(changed)
+    }
+
+    /// Returns the changes of the entry with id = `entry_id`, oldest first
+    pub(crate) fn get_related_to(conn: &Connection, entry_id: i64) -> Result<Vec<HistoryRecord>> {
+        let q = "SELECT at, field, old_value, new_value
+            FROM history
+            WHERE entry_id = :entry_id
+            ORDER BY history_id ASC;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query(named_params! {":entry_id": entry_id})?;
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, at => String, field => String, old_value => Option<String>, new_value => Option<String>);
+            res.push(HistoryRecord {
+                at,
+                field,
+                old_value,
+                new_value,
+            });
+        }
+        Ok(res)
+    }
+
+    /// Returns the tracked fields of `entry` along with their values as shown in the history
+    fn fields(entry: &Entry) -> Vec<(&'static str, Option<String>)> {
+        let mut topics = entry.topics.clone();
+        topics.sort();
+        let meta = entry
+            .meta
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>();
+        let non_empty = |s: String| if s.is_empty() { None } else { Some(s) };
+
+        vec![
+            ("name", Some(entry.name.clone())),
+            ("url", Some(entry.url.clone())),
+            ("author", entry.author.clone()),
+            ("topics", non_empty(topics.join(", "))),
+            ("meta", non_empty(meta.join(", "))),
+            ("added", Some(entry.added.clone())),
+            ("length", entry.length.map(|l| l.to_string())),
+            ("site_name", entry.site_name.clone()),
+            ("description", entry.description.clone()),
+            ("archive_url", entry.archive_url.clone()),
+            ("snapshot", entry.snapshot.clone()),
+            ("snoozed_until", entry.snoozed_until.clone()),
+            ("due", entry.due.clone()),
+            ("archived", Some(entry.archived.to_string())),
+            ("read_at", entry.read_at.clone()),
+        ]
+    }
+}
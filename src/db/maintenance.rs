@@ -0,0 +1,98 @@
+use anyhow::Result;
+use rusqlite::Connection;
+
+/// The rows that are not referenced by a foreign key but only make sense along with another row, as
+/// (description, query selecting their rowid, table to delete them from)
+const ORPHANS: [(&str, &str, &str); 1] = [(
+    "snapshot texts of entries that do not exist",
+    "SELECT rowid FROM snapshots_fts WHERE rowid NOT IN (SELECT entry_id FROM rlist)",
+    "snapshots_fts",
+)];
+
+pub(crate) struct DBMaintenance {}
+
+impl DBMaintenance {
+    /// Runs `PRAGMA integrity_check`, returning the problems it found. An empty list means that the database is intact
+    pub(crate) fn integrity_errors(conn: &Connection) -> Result<Vec<String>> {
+        let mut stmt = conn.prepare("PRAGMA integrity_check;")?;
+        let messages = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(messages.into_iter().filter(|m| m != "ok").collect())
+    }
+
+    /// Runs `PRAGMA foreign_key_check`, returning for each table the number of its rows that reference a row that does not exist
+    pub(crate) fn foreign_key_violations(conn: &Connection) -> Result<Vec<(String, i64)>> {
+        // A row that violates several foreign keys is reported once for each of them
+        let q = "SELECT DISTINCT \"table\", rowid FROM pragma_foreign_key_check();";
+        let mut stmt = conn.prepare(q)?;
+        let tables = stmt
+            .query_map([], |row| row.get::<_, String>("table"))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let mut counts: Vec<(String, i64)> = Vec::new();
+        for table in tables {
+            match counts.iter_mut().find(|(t, _count)| *t == table) {
+                Some((_table, count)) => *count += 1,
+                None => counts.push((table, 1)),
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Returns how many orphan rows of each kind there are, only including the kinds that have some
+    pub(crate) fn orphans(conn: &Connection) -> Result<Vec<(String, i64)>> {
+        let mut counts = Vec::new();
+        for (description, query, _table) in ORPHANS {
+            let count: i64 =
+                conn.query_row(&format!("SELECT COUNT(*) FROM ({query});"), [], |row| {
+                    row.get(0)
+                })?;
+            if count > 0 {
+                counts.push((description.to_string(), count));
+            }
+        }
+        Ok(counts)
+    }
+
+    /// Removes the rows that violate a foreign key and the orphan rows. The entries whose author does not exist
+    /// lose their author, and the topics whose parent does not exist become top level topics, instead of being removed
+    pub(crate) fn repair(conn: &Connection) -> Result<()> {
+        conn.execute_batch(
+            "UPDATE rlist SET author_id = NULL WHERE author_id NOT IN (SELECT author_id FROM authors);
+            UPDATE topics SET parent_id = NULL WHERE parent_id NOT IN (SELECT topic_id FROM topics);",
+        )?;
+
+        let mut stmt = conn.prepare("PRAGMA foreign_key_check;")?;
+        let violations = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>("table")?, row.get::<_, i64>("rowid")?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        for (table, rowid) in violations {
+            conn.execute(&format!("DELETE FROM {table} WHERE rowid = ?;"), [rowid])?;
+        }
+
+        for (_description, query, table) in ORPHANS {
+            conn.execute(
+                &format!("DELETE FROM {table} WHERE rowid IN ({query});"),
+                [],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the size of the database, in bytes
+    pub(crate) fn size(conn: &Connection) -> Result<i64> {
+        Ok(conn.query_row(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size();",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Rebuilds the database file to reclaim the space left by the removed rows, and updates the statistics used by the query planner
+    pub(crate) fn optimize(conn: &Connection) -> Result<()> {
+        conn.execute_batch("VACUUM; ANALYZE;")?;
+        Ok(())
+    }
+}
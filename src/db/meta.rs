@@ -0,0 +1,91 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use rusqlite::{named_params, params_from_iter, types::Value, Connection};
+
+use crate::read_sql_response;
+
+pub(crate) struct DBMeta {}
+
+impl DBMeta {
+    /// Sets the metadata `meta` on the entry with id = `entry_id`, overwriting the values of the keys that are already set
+    pub(crate) fn set_many(
+        conn: &Connection,
+        entry_id: i64,
+        meta: &[(impl AsRef<str>, impl AsRef<str>)],
+    ) -> Result<()> {
+        let q = "INSERT INTO entry_meta (entry_id, key, value) VALUES (:entry_id, :key, :value)
+            ON CONFLICT (entry_id, key) DO UPDATE SET value = excluded.value;";
+        let mut stmt = conn.prepare_cached(q)?;
+        for (key, value) in meta.iter() {
+            stmt.execute(named_params! {
+                ":entry_id": entry_id,
+                ":key": key.as_ref(),
+                ":value": value.as_ref(),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Removes the metadata with the given `keys` from the entry with id = `entry_id`. Keys that are not set are ignored
+    pub(crate) fn remove_many(
+        conn: &Connection,
+        entry_id: i64,
+        keys: &[impl AsRef<str>],
+    ) -> Result<()> {
+        let q = "DELETE FROM entry_meta WHERE entry_id = :entry_id AND key = :key;";
+        let mut stmt = conn.prepare_cached(q)?;
+        for key in keys.iter() {
+            stmt.execute(named_params! {":entry_id": entry_id, ":key": key.as_ref()})?;
+        }
+        Ok(())
+    }
+
+    /// Removes all of the metadata of the entry with id = `entry_id`
+    pub(crate) fn remove_all(conn: &Connection, entry_id: i64) -> Result<()> {
+        let q = "DELETE FROM entry_meta WHERE entry_id = :entry_id;";
+        conn.prepare_cached(q)?
+            .execute(named_params! {":entry_id": entry_id})?;
+        Ok(())
+    }
+
+    /// Returns the metadata of the entry with id = `entry_id`
+    pub(crate) fn get_related_to(
+        conn: &Connection,
+        entry_id: i64,
+    ) -> Result<BTreeMap<String, String>> {
+        let q = "SELECT key, value FROM entry_meta WHERE entry_id = :entry_id;";
+        let mut stmt = conn.prepare_cached(q)?;
+        let mut rows = stmt.query(named_params! {":entry_id": entry_id})?;
+
+        let mut res = BTreeMap::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, key => String, value => String);
+            res.insert(key, value);
+        }
+        Ok(res)
+    }
+
+    /// Returns the metadata of the entries of the `rlist AS ls` table selected by `where_clause`, grouped by entry id.
+    /// `bindings` are bound to the positional parameters of `where_clause`
+    pub(crate) fn get_matching(
+        conn: &Connection,
+        where_clause: &str,
+        bindings: Vec<Value>,
+    ) -> Result<HashMap<i64, BTreeMap<String, String>>> {
+        let q = format!(
+            "SELECT entry_id, key, value
+            FROM entry_meta
+            WHERE entry_id IN (SELECT ls.entry_id FROM rlist AS ls {where_clause});"
+        );
+        let mut stmt = conn.prepare(&q)?;
+        let mut rows = stmt.query(params_from_iter(bindings))?;
+
+        let mut res: HashMap<i64, BTreeMap<String, String>> = HashMap::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, entry_id => i64, key => String, value => String);
+            res.entry(entry_id).or_default().insert(key, value);
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+
+use super::{
+    add_column_if_missing, author::DBAuthor, column_exists, entry::DBEntry, snapshot::DBSnapshot,
+    table_exists, topic::DBTopic,
+};
+
+/// A change to the schema, applied once to the databases created before it.
+/// The schema version of a database (stored in `PRAGMA user_version`) is the number of migrations applied to it
+struct Migration {
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// The migrations, in the order they are applied. New databases are created with the latest schema
+/// (see [`crate::RList::open`]), so a new column must be added both to the `CREATE TABLE` statement and here.
+/// Never edit or reorder the migrations that have been released: append a new one instead
+const MIGRATIONS: &[Migration] = &[Migration {
+    description: "bring the databases created before the schema was versioned up to date",
+    apply: unversioned,
+}];
+
+/// The schema version of the databases created by this version of rlist
+pub(crate) const LATEST_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// Returns the schema version of the database
+pub(crate) fn schema_version(conn: &Connection) -> Result<i64> {
+    Ok(conn.query_row("PRAGMA user_version;", [], |row| row.get(0))?)
+}
+
+/// Applies the migrations that the database is missing, each in its own transaction, or just marks it as up to date
+/// if it has just been `created`. Fails if the database was created by a newer version of rlist
+pub(crate) fn migrate(conn: &Connection, created: bool) -> Result<()> {
+    if created {
+        conn.execute_batch(&format!("PRAGMA user_version = {LATEST_VERSION};"))?;
+        return Ok(());
+    }
+    let version = schema_version(conn)?;
+    if version > LATEST_VERSION {
+        return Err(anyhow::anyhow!(
+            "The database was created by a newer version of rlist (schema version {version}, while this version supports up to {LATEST_VERSION}). Update rlist to open it"
+        ));
+    }
+    for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let version = i as i64 + 1;
+        apply(conn, migration, version).with_context(|| {
+            format!(
+                "Could not upgrade the database to schema version {version} ({})",
+                migration.description
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Applies `migration` and sets the schema version to `version`, rolling back both if it fails.
+/// The foreign keys are disabled meanwhile (which can only be done outside of a transaction), so that a migration can
+/// rebuild a table without deleting the rows that reference it
+fn apply(conn: &Connection, migration: &Migration, version: i64) -> Result<()> {
+    conn.execute_batch("PRAGMA foreign_keys = OFF; BEGIN TRANSACTION;")?;
+    let res = (migration.apply)(conn).and_then(|()| {
+        conn.execute_batch(&format!("PRAGMA user_version = {version}; COMMIT;"))?;
+        Ok(())
+    });
+    if res.is_err() {
+        let _ = conn.execute_batch("ROLLBACK;");
+    }
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    res
+}
+
+/// Version 1: the changes made by the versions of rlist that did not track the schema version,
+/// each of which is skipped if the database already has it
+fn unversioned(conn: &Connection) -> Result<()> {
+    add_column_if_missing(conn, "rlist", "snoozed_until", "DATETIME")?;
+    add_column_if_missing(conn, "rlist", "domain", "TEXT")?;
+    DBEntry::fill_missing_domains(conn)?;
+    add_column_if_missing(
+        conn,
+        "rlist",
+        "author_id",
+        "INTEGER REFERENCES authors (author_id) ON UPDATE CASCADE ON DELETE SET NULL",
+    )?;
+    add_column_if_missing(conn, "rlist", "length", "INTEGER")?;
+    add_column_if_missing(conn, "rlist", "site_name", "TEXT")?;
+    add_column_if_missing(conn, "rlist", "description", "TEXT")?;
+    add_column_if_missing(conn, "rlist", "archive_url", "TEXT")?;
+    add_column_if_missing(conn, "rlist", "snapshot", "TEXT")?;
+    add_column_if_missing(conn, "rlist", "archived", "BOOLEAN NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "rlist", "read_at", "DATETIME")?;
+    add_column_if_missing(conn, "rlist", "opens", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "rlist", "due", "DATETIME")?;
+    if add_column_if_missing(
+        conn,
+        "topics",
+        "parent_id",
+        "INTEGER REFERENCES topics (topic_id) ON UPDATE CASCADE ON DELETE SET NULL",
+    )? {
+        DBTopic::link_parents(conn)?;
+    }
+    if add_column_if_missing(conn, "rlist", "modified", "DATETIME")? {
+        conn.execute("UPDATE rlist SET modified = added;", [])?;
+    }
+    if !table_exists(conn, "snapshots_fts")? {
+        conn.execute_batch("CREATE VIRTUAL TABLE snapshots_fts USING fts5 (text);")?;
+        DBSnapshot::index_existing(conn)?;
+    }
+    if column_exists(conn, "rlist", "author")? {
+        DBAuthor::migrate_author_column(conn)?;
+    }
+    Ok(())
+}
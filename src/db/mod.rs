@@ -1,2 +1,56 @@
+use anyhow::Result;
+use rusqlite::{Connection, OptionalExtension};
+
+pub mod author;
 pub mod entry;
-pub mod topic;
\ No newline at end of file
+pub mod history;
+pub mod maintenance;
+pub mod meta;
+pub mod migrations;
+pub mod oplog;
+pub mod queue;
+pub mod snapshot;
+pub mod stats;
+pub mod topic;
+pub mod trash;
+
+/// Returns whether `table` has a column named `column`
+pub(crate) fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table});"))?;
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        if row.get::<_, String>("name")? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns whether the table (or view) `table` exists
+pub(crate) fn table_exists(conn: &Connection, table: &str) -> Result<bool> {
+    Ok(conn
+        .query_row(
+            "SELECT 1 FROM sqlite_master WHERE name = :name;",
+            &[(":name", table)],
+            |_row| Ok(()),
+        )
+        .optional()?
+        .is_some())
+}
+
+/// Adds the column `column` (declared as `definition`) to `table` if it does not exist yet.
+/// Used to bring databases created by older versions of rlist up to date. Returns whether the column was added.
+pub(crate) fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<bool> {
+    if column_exists(conn, table, column)? {
+        return Ok(false);
+    }
+    conn.execute_batch(&format!(
+        "ALTER TABLE {table} ADD COLUMN {column} {definition};"
+    ))?;
+    Ok(true)
+}
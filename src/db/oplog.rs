@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use rusqlite::{named_params, Connection};
+
+use crate::entry::Entry;
+use crate::read_sql_response;
+
+/// How many operations are kept in the log. Older operations cannot be undone anymore
+const MAX_OPERATIONS: i64 = 100;
+
+/// A change to a single entry: its state before and after the change. `None` means that the entry did not exist
+pub(crate) type Change = (Option<Entry>, Option<Entry>);
+
+pub(crate) struct DBOpLog {}
+
+impl DBOpLog {
+    /// Records an operation of type `kind` made of `changes`. The operations that were undone are forgotten,
+    /// as they cannot be redone anymore
+    pub(crate) fn record(
+        conn: &Connection,
+        kind: &str,
+        changes: &[(Option<&Entry>, Option<&Entry>)],
+    ) -> Result<()> {
+        conn.prepare_cached("DELETE FROM operations WHERE undone;")?
+            .execute([])?;
+
+        let q = "INSERT INTO operations (kind) VALUES (:kind) RETURNING op_id;";
+        let op_id = conn
+            .prepare_cached(q)?
+            .query_row(named_params! {":kind": kind}, |row| {
+                row.get::<_, i64>("op_id")
+            })?;
+
+        let q = "INSERT INTO operation_changes (op_id, seq, before, after) VALUES (:op_id, :seq, :before, :after);";
+        let mut stmt = conn.prepare_cached(q)?;
+        let serialize = |e: Option<&Entry>| e.map(serde_yaml::to_string).transpose();
+        for (seq, (before, after)) in changes.iter().enumerate() {
+            stmt.execute(named_params! {
+                ":op_id": op_id,
+                ":seq": seq as i64,
+                ":before": serialize(*before)?,
+                ":after": serialize(*after)?,
+            })?;
+        }
+
+        let q = "DELETE FROM operations WHERE op_id <= :op_id - :max;";
+        conn.prepare_cached(q)?
+            .execute(named_params! {":op_id": op_id, ":max": MAX_OPERATIONS})?;
+        Ok(())
+    }
+
+    /// Returns the id and the kind of the most recent operation that was not undone
+    pub(crate) fn last_done(conn: &Connection) -> Result<Option<(i64, String)>> {
+        Self::first_where(
+            conn,
+            "SELECT op_id, kind FROM operations WHERE NOT undone ORDER BY op_id DESC LIMIT 1;",
+        )
+    }
+
+    /// Returns the id and the kind of the oldest operation that was undone, which is the next one to be redone
+    pub(crate) fn first_undone(conn: &Connection) -> Result<Option<(i64, String)>> {
+        Self::first_where(
+            conn,
+            "SELECT op_id, kind FROM operations WHERE undone ORDER BY op_id ASC LIMIT 1;",
+        )
+    }
+
+    fn first_where(conn: &Connection, q: &str) -> Result<Option<(i64, String)>> {
+        let mut stmt = conn.prepare_cached(q)?;
+        let mut rows = stmt.query([])?;
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+        read_sql_response!(row, op_id => i64, kind => String);
+        Ok(Some((op_id, kind)))
+    }
+
+    /// Returns the changes of the operation with id = `op_id`, in the order they were made
+    pub(crate) fn changes(conn: &Connection, op_id: i64) -> Result<Vec<Change>> {
+        let q =
+            "SELECT before, after FROM operation_changes WHERE op_id = :op_id ORDER BY seq ASC;";
+        let mut stmt = conn.prepare_cached(q)?;
+        let mut rows = stmt.query(named_params! {":op_id": op_id})?;
+
+        let parse = |e: Option<String>| -> Result<Option<Entry>> {
+            e.map(|e| serde_yaml::from_str(&e).context("Could not read an operation of the log"))
+                .transpose()
+        };
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, before => Option<String>, after => Option<String>);
+            res.push((parse(before)?, parse(after)?));
+        }
+        Ok(res)
+    }
+
+    pub(crate) fn set_undone(conn: &Connection, op_id: i64, undone: bool) -> Result<()> {
+        let q = "UPDATE operations SET undone = :undone WHERE op_id = :op_id;";
+        conn.prepare_cached(q)?
+            .execute(named_params! {":undone": undone, ":op_id": op_id})?;
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use rusqlite::{params_from_iter, Connection};
+
+pub(crate) struct DBQueue {}
+
+impl DBQueue {
+    /// Returns the ids of the entries in the queue, in order
+    pub(crate) fn get_all(conn: &Connection) -> Result<Vec<i64>> {
+        let q = "SELECT entry_id FROM queue ORDER BY position;";
+        let mut stmt = conn.prepare_cached(q)?;
+        let ids = stmt.query_map([], |row| row.get("entry_id"))?;
+
+        Ok(ids.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Replaces the content of the queue with the entries identified by `entry_ids`, in the same order.
+    /// Should be run inside of a transaction.
+    pub(crate) fn set_all(conn: &Connection, entry_ids: &[i64]) -> Result<()> {
+        conn.execute("DELETE FROM queue;", [])?;
+        if entry_ids.is_empty() {
+            return Ok(());
+        }
+
+        let q = format!(
+            "INSERT INTO queue (entry_id, position) VALUES {};",
+            entry_ids
+                .iter()
+                .map(|_id| "(?, ?)")
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        let bindings = entry_ids
+            .iter()
+            .enumerate()
+            .flat_map(|(pos, id)| [*id, pos as i64 + 1]);
+        conn.execute(&q, params_from_iter(bindings))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use rusqlite::{named_params, Connection};
+
+use crate::read_sql_response;
+
+pub(crate) struct DBSnapshot {}
+
+impl DBSnapshot {
+    /// Replaces the indexed text of the snapshot of the entry with id = `entry_id`
+    pub(crate) fn index(conn: &Connection, entry_id: i64, text: &str) -> Result<()> {
+        let q = "DELETE FROM snapshots_fts WHERE rowid = :entry_id;";
+        conn.execute(q, named_params! {":entry_id": entry_id})?;
+
+        let q = "INSERT INTO snapshots_fts (rowid, text) VALUES (:entry_id, :text);";
+        conn.execute(q, named_params! {":entry_id": entry_id, ":text": text})?;
+        Ok(())
+    }
+
+    /// Indexes the snapshots that were saved before the full text index existed.
+    /// Snapshots that cannot be read are skipped
+    pub(crate) fn index_existing(conn: &Connection) -> Result<()> {
+        let q = "SELECT entry_id, snapshot FROM rlist WHERE snapshot IS NOT NULL;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query([])?;
+        let mut snapshots = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, entry_id => i64, snapshot => String);
+            snapshots.push((entry_id, snapshot));
+        }
+
+        for (entry_id, snapshot) in snapshots {
+            if let Ok(text) = std::fs::read_to_string(snapshot) {
+                Self::index(conn, entry_id, &text)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Updates the paths of the snapshots saved in the folder `old_dir`, which was moved to `new_dir`. Returns how many were updated
+    pub(crate) fn relocate(conn: &Connection, old_dir: &str, new_dir: &str) -> Result<usize> {
+        let q = "UPDATE rlist SET snapshot = :new_dir || substr(snapshot, length(:old_dir) + 1)
+            WHERE substr(snapshot, 1, length(:old_dir)) = :old_dir;";
+        Ok(conn.execute(q, named_params! {":old_dir": old_dir, ":new_dir": new_dir})?)
+    }
+
+    /// Turns the search terms typed by the user into an FTS5 query matching the snapshots that contain all of them
+    pub(crate) fn match_expression(terms: &str) -> String {
+        terms
+            .split_whitespace()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
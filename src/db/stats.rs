@@ -0,0 +1,177 @@
+use anyhow::Result;
+use rusqlite::{named_params, Connection};
+
+use crate::{read_sql_response, rlist::TopicStats};
+
+pub(crate) struct DBStats {}
+
+impl DBStats {
+    /// Returns the total number of entries in the reading list
+    pub(crate) fn count_entries(conn: &Connection) -> Result<i64> {
+        let q = "SELECT COUNT(*) AS count FROM rlist;";
+        Ok(conn.query_row(q, [], |row| row.get("count"))?)
+    }
+
+    /// Returns the number of entries that were marked as read
+    pub(crate) fn count_read(conn: &Connection) -> Result<i64> {
+        let q = "SELECT COUNT(*) AS count FROM rlist WHERE read_at IS NOT NULL;";
+        Ok(conn.query_row(q, [], |row| row.get("count"))?)
+    }
+
+    /// Returns the `read_at` dates of all of the entries that were read
+    pub(crate) fn read_dates(conn: &Connection) -> Result<Vec<String>> {
+        let q = "SELECT read_at FROM rlist WHERE read_at IS NOT NULL;";
+        let mut stmt = conn.prepare(q)?;
+        let dates = stmt.query_map([], |row| row.get("read_at"))?;
+        Ok(dates.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Returns the number of entries added (or read, if `read`) on each day of `year`, as `YYYY-MM-DD` dates
+    pub(crate) fn per_day(conn: &Connection, year: i32, read: bool) -> Result<Vec<(String, i64)>> {
+        let column = if read { "read_at" } else { "added" };
+        let q = format!(
+            "SELECT
+                date({column}) AS day,
+                COUNT(*) AS count
+            FROM rlist
+            WHERE strftime('%Y', {column}) = :year
+            GROUP BY day;"
+        );
+        let mut stmt = conn.prepare(&q)?;
+        let mut rows = stmt.query(named_params! {":year": format!("{year:04}")})?;
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, day => String, count => i64);
+            res.push((day, count));
+        }
+        Ok(res)
+    }
+
+    /// Returns the number of entries added in each month (formatted as `YYYY-MM`), in chronological order.
+    /// Months in which no entries were added are not returned
+    pub(crate) fn added_per_month(conn: &Connection) -> Result<Vec<(String, i64)>> {
+        let q = "SELECT
+            strftime('%Y-%m', added) AS month,
+            COUNT(*) AS count
+        FROM rlist
+        GROUP BY month
+        ORDER BY month;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query([])?;
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, month => String, count => i64);
+            res.push((month, count));
+        }
+
+        Ok(res)
+    }
+
+    /// Returns the `limit` topics with the most entries, along with their entry count
+    pub(crate) fn top_topics(conn: &Connection, limit: i64) -> Result<Vec<(String, i64)>> {
+        let q = "SELECT
+            t.name AS topic,
+            COUNT(*) AS count
+        FROM topics AS t
+        JOIN rlist_has_topic AS rht
+            ON rht.topic_id = t.topic_id
+        GROUP BY t.topic_id
+        ORDER BY count DESC, t.name
+        LIMIT :limit;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query(named_params! {":limit": limit})?;
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, topic => String, count => i64);
+            res.push((topic, count));
+        }
+
+        Ok(res)
+    }
+
+    /// Returns the `limit` authors with the most entries, along with their entry count
+    pub(crate) fn top_authors(conn: &Connection, limit: i64) -> Result<Vec<(String, i64)>> {
+        let q = "SELECT
+            a.name AS author,
+            COUNT(*) AS count
+        FROM authors AS a
+        JOIN rlist AS ls
+            ON ls.author_id = a.author_id
+        GROUP BY a.author_id
+        ORDER BY count DESC, a.name
+        LIMIT :limit;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query(named_params! {":limit": limit})?;
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, author => String, count => i64);
+            res.push((author, count));
+        }
+
+        Ok(res)
+    }
+
+    /// Returns the statistics of every topic that has at least one entry, sorted by entry count
+    pub(crate) fn per_topic(conn: &Connection) -> Result<Vec<TopicStats>> {
+        let q = "SELECT
+            t.name AS topic,
+            COUNT(*) AS count,
+            AVG(julianday('now', 'localtime') - julianday(ls.added)) AS average_age,
+            (
+                SELECT oldest.name
+                FROM rlist AS oldest
+                JOIN rlist_has_topic AS oldest_rht
+                    ON oldest_rht.entry_id = oldest.entry_id
+                WHERE oldest_rht.topic_id = t.topic_id AND oldest.read_at IS NULL
+                ORDER BY oldest.added, oldest.entry_id
+                LIMIT 1
+            ) AS oldest
+        FROM topics AS t
+        JOIN rlist_has_topic AS rht
+            ON rht.topic_id = t.topic_id
+        JOIN rlist AS ls
+            ON ls.entry_id = rht.entry_id
+        GROUP BY t.topic_id
+        ORDER BY count DESC, t.name;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query([])?;
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, topic => String, count => i64, oldest => Option<String>, average_age => f64);
+            res.push(TopicStats {
+                topic,
+                entries: count,
+                oldest,
+                average_age,
+            });
+        }
+
+        Ok(res)
+    }
+
+    /// Returns the number of entries of each domain, sorted by entry count
+    pub(crate) fn per_domain(conn: &Connection) -> Result<Vec<(String, i64)>> {
+        let q = "SELECT
+            domain,
+            COUNT(*) AS count
+        FROM rlist
+        WHERE domain IS NOT NULL AND domain != ''
+        GROUP BY domain
+        ORDER BY count DESC, domain;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query([])?;
+
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, domain => String, count => i64);
+            res.push((domain, count));
+        }
+
+        Ok(res)
+    }
+}
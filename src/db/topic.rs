@@ -1,14 +1,32 @@
 use anyhow::Result;
-
-use crate::{read_sql_response, topic::Topic};
+use rusqlite::{named_params, params_from_iter, Connection, OptionalExtension};
+
+use std::collections::HashMap;
+
+use crate::{
+    read_sql_response,
+    topic::{Topic, TOPIC_SEPARATOR},
+};
+
+/// Selects the ids of the topics whose name is bound to the positional parameters `{params}`, along with the ids of all of their subtopics,
+/// as `sub (topic_id, root)` where `root` is the name of the topic the subtopic descends from
+pub(crate) fn subtopics_cte(params: &str) -> String {
+    format!(
+        "WITH RECURSIVE sub (topic_id, root) AS (
+            SELECT topic_id, name FROM topics WHERE name IN ({params})
+            UNION
+            SELECT t.topic_id, sub.root FROM topics AS t JOIN sub ON t.parent_id = sub.topic_id
+        )"
+    )
+}
 
 pub(crate) struct DBTopic {}
 
 impl DBTopic {
-    // pub(crate) fn create(conn: &sqlite::Connection, topic: &impl AsRef<str>) -> Result<i64> {
-    //     let q = "INSERT INTO topics (name) 
-    //     VALUES (:topic) 
-    //     ON CONFLICT (name) DO UPDATE SET name=name 
+    // pub(crate) fn create(conn: &Connection, topic: &impl AsRef<str>) -> Result<i64> {
+    //     let q = "INSERT INTO topics (name)
+    //     VALUES (:topic)
+    //     ON CONFLICT (name) DO UPDATE SET name=name
     //     RETURNING topic_id;";
 
     //     let mut stmt = conn.prepare(q)?;
@@ -28,10 +46,8 @@ impl DBTopic {
 
     /// Creates all of the topics contained in `topics`. If a topic already exists, it is ignored.
     /// Returns the list of each ot the topics' ids (in the same order as in `topics`)
-    pub(crate) fn create_many(
-        conn: &sqlite::Connection,
-        topics: &Vec<impl AsRef<str>>,
-    ) -> Result<Vec<i64>> {
+    pub(crate) fn create_many(conn: &Connection, topics: &[impl AsRef<str>]) -> Result<Vec<i64>> {
+        let topics = topics.iter().map(Topic::normalize).collect::<Vec<_>>();
         let q = format!(
             "INSERT INTO topics (name) 
             VALUES {} 
@@ -39,27 +55,79 @@ impl DBTopic {
             RETURNING topic_id;",
             topics.iter().map(|_t| "(?)").collect::<Vec<_>>().join(", "),
         );
-        let mut stmt = conn.prepare(q)?;
-
-        stmt.bind_iter(topics.iter().enumerate().map(|(i, t)| (i + 1, t.as_ref())))?;
+        let mut stmt = conn.prepare(&q)?;
+        let mut rows = stmt.query(params_from_iter(topics.iter()))?;
 
         let mut res = Vec::with_capacity(topics.len());
 
-        while let sqlite::State::Row = stmt.next()? {
-            let topic_id = stmt.read::<i64, _>("topic_id")?;
+        while let Some(row) = rows.next()? {
+            let topic_id = row.get::<_, i64>("topic_id")?;
             res.push(topic_id);
         }
 
+        if topics.iter().any(|t| t.contains(TOPIC_SEPARATOR)) {
+            Self::link_parents(conn)?;
+        }
         Ok(res)
     }
 
+    /// Makes every nested topic (e.g. `a/b/c`) a child of its parent (`a/b`), creating the parents that do not exist yet
+    pub(crate) fn link_parents(conn: &Connection) -> Result<()> {
+        let mut stmt = conn.prepare("SELECT topic_id, name, parent_id FROM topics;")?;
+        let mut rows = stmt.query([])?;
+        let mut topics = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, topic_id => i64, name => String, parent_id => Option<i64>);
+            topics.push((topic_id, name, parent_id));
+        }
+
+        let mut ids = topics
+            .iter()
+            .map(|(id, name, _parent_id)| (name.clone(), *id))
+            .collect::<HashMap<_, _>>();
+        let mut insert =
+            conn.prepare("INSERT INTO topics (name) VALUES (:name) RETURNING topic_id;")?;
+        let mut update =
+            conn.prepare("UPDATE topics SET parent_id = :parent_id WHERE topic_id = :topic_id;")?;
+        let mut i = 0;
+        // Created parents are appended to `topics`, so that their own parents are created too
+        while let Some((topic_id, name, parent_id)) = topics.get(i).cloned() {
+            i += 1;
+            let parent = match name.rsplit_once(TOPIC_SEPARATOR) {
+                Some((parent, _child)) if !parent.is_empty() => parent,
+                _ => {
+                    if parent_id.is_some() {
+                        update.execute(
+                            named_params! {":parent_id": None::<i64>, ":topic_id": topic_id},
+                        )?;
+                    }
+                    continue;
+                }
+            };
+
+            let new_parent_id = match ids.get(parent) {
+                Some(id) => *id,
+                None => {
+                    let id = insert.query_row(named_params! {":name": parent}, |row| {
+                        row.get::<_, i64>("topic_id")
+                    })?;
+                    ids.insert(parent.to_string(), id);
+                    topics.push((id, parent.to_string(), None));
+                    id
+                }
+            };
+            if parent_id != Some(new_parent_id) {
+                update
+                    .execute(named_params! {":parent_id": new_parent_id, ":topic_id": topic_id})?;
+            }
+        }
+        Ok(())
+    }
+
     //TODO this should maybe return Ok(None) if an entry with that entry_id does not exist (?)
 
     /// Returns the list of tuples containing the ids and names of all of the topics related to the entry with id = `entry_id`
-    pub(crate) fn get_related_to(
-        conn: &sqlite::Connection,
-        entry_id: i64,
-    ) -> Result<Vec<(i64, String)>> {
+    pub(crate) fn get_related_to(conn: &Connection, entry_id: i64) -> Result<Vec<(i64, String)>> {
         let q = "SELECT 
             t.name AS topic, 
             t.topic_id AS id 
@@ -67,34 +135,82 @@ impl DBTopic {
         JOIN rlist_has_topic AS rht 
             ON rht.topic_id = t.topic_id 
         WHERE rht.entry_id = :entry_id;";
-        let mut stmt = conn.prepare(q)?;
-        stmt.bind((":entry_id", entry_id))?;
+        let mut stmt = conn.prepare_cached(q)?;
+        let mut rows = stmt.query(named_params! {":entry_id": entry_id})?;
 
         let mut res = Vec::new();
 
-        while let sqlite::State::Row = stmt.next()? {
-            read_sql_response!(stmt, id => i64, topic => String);
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, id => i64, topic => String);
             res.push((id, topic));
         }
 
         Ok(res)
     }
 
-    pub(crate) fn get_id_from_name(conn: &sqlite::Connection, topic: impl AsRef<str>) -> Result<i64> {
+    /// Returns the id of the topic named `topic`, or `None` if there is no such topic
+    pub(crate) fn get_id_from_name(
+        conn: &Connection,
+        topic: impl AsRef<str>,
+    ) -> Result<Option<i64>> {
         let q = "SELECT topic_id FROM topics WHERE name = :topic;";
+        Ok(conn
+            .query_row(
+                q,
+                named_params! {":topic": Topic::normalize(&topic)},
+                |row| row.get("topic_id"),
+            )
+            .optional()?)
+    }
+
+    /// Returns the list of tuples containing the id, the name and the number of related entries of every topic
+    pub(crate) fn get_all_with_counts(conn: &Connection) -> Result<Vec<(i64, String, i64)>> {
+        let q = "SELECT
+            t.topic_id AS id,
+            t.name AS topic,
+            COUNT(rht.entry_id) AS entries
+        FROM topics AS t
+        LEFT OUTER JOIN rlist_has_topic AS rht
+            ON rht.topic_id = t.topic_id
+        GROUP BY t.topic_id
+        ORDER BY t.name;";
         let mut stmt = conn.prepare(q)?;
-        stmt.bind((":topic", topic.as_ref()))?;
+        let mut rows = stmt.query([])?;
 
-        if let sqlite::State::Done = stmt.next()? {
-            return Err(anyhow::anyhow!("Could not find topic {} in your reading list", Topic::pretty_print(topic.as_ref())));
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, id => i64, topic => String, entries => i64);
+            res.push((id, topic, entries));
         }
-        let topic_id = stmt.read::<i64, _>("topic_id")?;
-        
-        Ok(topic_id)
+
+        Ok(res)
+    }
+
+    /// Sets the name of the topic with id = `topic_id` to `name`
+    pub(crate) fn rename(conn: &Connection, topic_id: i64, name: impl AsRef<str>) -> Result<()> {
+        let q = "UPDATE topics SET name = :name WHERE topic_id = :topic_id;";
+        conn.execute(
+            q,
+            named_params! {":name": Topic::normalize(name), ":topic_id": topic_id},
+        )?;
+
+        Ok(())
+    }
+
+    /// Links every entry of the topic with id = `from_id` to the topic with id = `into_id`, then deletes the former
+    pub(crate) fn merge_into(conn: &Connection, from_id: i64, into_id: i64) -> Result<()> {
+        let q = "INSERT OR IGNORE INTO rlist_has_topic (entry_id, topic_id)
+            SELECT entry_id, :into_id FROM rlist_has_topic WHERE topic_id = :from_id;";
+        conn.execute(q, named_params! {":into_id": into_id, ":from_id": from_id})?;
+
+        let q = "DELETE FROM topics WHERE topic_id = :from_id;";
+        conn.execute(q, named_params! {":from_id": from_id})?;
+
+        Ok(())
     }
 
     // Deletes a topic by its id. Returns None if no topic was found, else returns its name
-    // pub(crate) fn delete_by_id(conn: &sqlite::Connection, topic_id: i64) -> Result<Option<String>> {
+    // pub(crate) fn delete_by_id(conn: &Connection, topic_id: i64) -> Result<Option<String>> {
     //     let q = "DELETE FROM topics WHERE topic_id = :topic_id RETURNING *";
     //     let mut stmt = conn.prepare(q)?;
     //     stmt.bind((":topic_id", topic_id))?;
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use rusqlite::{named_params, Connection, OptionalExtension};
+
+use crate::entry::Entry;
+use crate::read_sql_response;
+
+pub(crate) struct DBTrash {}
+
+impl DBTrash {
+    /// Puts `entry` (which must have already been removed from the reading list) into the trash
+    pub(crate) fn add(conn: &Connection, entry: &Entry) -> Result<()> {
+        let q = "INSERT INTO trash (name, entry) VALUES (:name, :entry);";
+        conn.prepare_cached(q)?.execute(named_params! {
+            ":name": entry.name,
+            ":entry": serde_yaml::to_string(entry)?,
+        })?;
+        Ok(())
+    }
+
+    /// Returns all of the entries in the trash along with the date they were removed on, most recently removed first
+    pub(crate) fn get_all(conn: &Connection) -> Result<Vec<(String, Entry)>> {
+        let q = "SELECT entry, removed FROM trash ORDER BY removed DESC, trash_id DESC;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query([])?;
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            read_sql_response!(row, entry => String, removed => String);
+            res.push((removed, Self::parse(&entry)?));
+        }
+        Ok(res)
+    }
+
+    /// Takes the most recently removed entry with name = `name` out of the trash.
+    /// Returns `None` if there is no such entry in the trash
+    pub(crate) fn take_by_name(conn: &Connection, name: impl AsRef<str>) -> Result<Option<Entry>> {
+        let q = "DELETE FROM trash
+            WHERE trash_id = (
+                SELECT trash_id FROM trash WHERE name = :name ORDER BY removed DESC, trash_id DESC LIMIT 1
+            )
+            RETURNING entry;";
+        let entry = conn
+            .query_row(q, named_params! {":name": name.as_ref()}, |row| {
+                row.get::<_, String>("entry")
+            })
+            .optional()?;
+        entry.map(|e| Self::parse(&e)).transpose()
+    }
+
+    /// Permanently deletes the entries that were put into the trash before `before` (formatted like SQLITE_DATETIME_FORMAT),
+    /// or all of them if `before` is `None`. Returns the deleted entries
+    pub(crate) fn remove_older_than(conn: &Connection, before: Option<&str>) -> Result<Vec<Entry>> {
+        let q = "DELETE FROM trash WHERE :before IS NULL OR removed < :before RETURNING entry;";
+        let mut stmt = conn.prepare(q)?;
+        let mut rows = stmt.query(named_params! {":before": before})?;
+        let mut res = Vec::new();
+        while let Some(row) = rows.next()? {
+            let entry = row.get::<_, String>("entry")?;
+            res.push(Self::parse(&entry)?);
+        }
+        Ok(res)
+    }
+
+    fn parse(entry: &str) -> Result<Entry> {
+        serde_yaml::from_str(entry).context("Could not read an entry of the trash")
+    }
+}
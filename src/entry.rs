@@ -1,16 +1,59 @@
+use crate::theme::Themed;
+use anyhow::{Context, Result};
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
-use anyhow::{Result, Context};
+use std::{collections::BTreeMap, io::Write};
 
-use crate::{topic::Topic, utils::sql_string_to_dt};
+use crate::{
+    topic::Topic,
+    utils::{short_id, sql_string_to_dt},
+};
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Entry {
+    /// The id of the entry in the reading list, which never changes (restored entries get their id back, if it is still free).
+    /// `None` for the entries that were never in a reading list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
     pub name: String,
     pub url: String,
     pub author: Option<String>,
     pub topics: Vec<String>,
     pub added: String,
+    /// When the entry was last changed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snoozed_until: Option<String>,
+    /// The estimated reading time, in minutes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<i64>,
+    /// The name of the website the content belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub site_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The url of a snapshot of the content on the Wayback Machine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_url: Option<String>,
+    /// The path of the snapshot of the content saved with `rlist save`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<String>,
+    /// Archived entries are hidden from the default listings, but are kept in the reading list
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub archived: bool,
+    /// When the entry was marked as read. `None` if it was not read yet
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_at: Option<String>,
+    /// How many times the entry was opened with `rlist open`
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub opens: i64,
+    /// When the entry should be read by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    /// Arbitrary `key: value` pairs attached to the entry
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub meta: BTreeMap<String, String>,
 }
 
 impl Entry {
@@ -22,19 +65,59 @@ impl Entry {
         added: Option<String>,
     ) -> Self {
         Self {
+            id: None,
             name,
             url,
             author,
             topics,
             added: added.unwrap_or_default(),
+            modified: None,
+            snoozed_until: None,
+            length: None,
+            site_name: None,
+            description: None,
+            archive_url: None,
+            snapshot: None,
+            archived: false,
+            read_at: None,
+            opens: 0,
+            due: None,
+            meta: BTreeMap::new(),
         }
     }
 
-    /// Prints the entry to stdout.
-    /// If `!long`, then it will only print `name: url [by author]`
-    /// otherwise, it will also print the topics and `self.added`
-    pub fn pretty_print(&self, long: bool, fmt_str: impl AsRef<str>) -> Result<()> {
-        let topics_row = if long && self.topics.len() > 0 {
+    /// Returns the short id of the entry (see [`short_id`]), if it is in the reading list
+    pub fn short_id(&self) -> Option<String> {
+        self.id.map(short_id)
+    }
+
+    /// Prints the entry to `out`, preceded by its short id if it is in the reading list.
+    /// If `!long`, then it will only print `[short id] name: url [by author]`
+    /// otherwise, it will also print the id, the site name, the description, the topics, the metadata, the length, `self.added`,
+    /// the url of its snapshot on the Wayback Machine, whether it was saved offline, whether it is archived and, if the entry is snoozed,
+    /// the snooze expiration
+    pub fn pretty_print(
+        &self,
+        out: &mut impl Write,
+        long: bool,
+        fmt_str: impl AsRef<str>,
+    ) -> Result<()> {
+        let site_row = match self.site_name.as_deref() {
+            Some(site_name) if long => format!(" on {}", site_name.italic()),
+            _ => String::new(),
+        };
+
+        let description_row = match self.description.as_deref() {
+            Some(description) if long => format!("\n{}", description.dimmed()),
+            _ => String::new(),
+        };
+
+        let id_row = match self.id {
+            Some(id) if long => format!("\nID: {id}"),
+            _ => String::new(),
+        };
+
+        let topics_row = if long && !self.topics.is_empty() {
             format!(
                 "\nTopics: {}",
                 self.topics
@@ -47,25 +130,156 @@ impl Entry {
             String::new()
         };
 
+        let meta_row = if long && !self.meta.is_empty() {
+            format!(
+                "\nMeta: {}",
+                self.meta
+                    .iter()
+                    .map(|(k, v)| format!("{}={v}", k.bold()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        } else {
+            String::new()
+        };
+
+        let length_row = match self.length {
+            Some(length) if long => format!("\nLength: {length} min"),
+            _ => String::new(),
+        };
+
         let added_row = if long {
-            let dt = sql_string_to_dt(self.added.as_str()).context("Could not format datetime in the desired format")?;
+            let dt = sql_string_to_dt(self.added.as_str())
+                .context("Could not format datetime in the desired format")?;
 
             format!("\nAdded on {}", dt.format(fmt_str.as_ref()))
         } else {
             String::new()
         };
 
-        println!(
-            "{name}: {url}{maybe_author}{topics_row}{added_row}",
-            name = self.name.bold().truecolor(255, 165, 0), // orange
-            url = self.url.bright_blue().underline(),
+        let modified_row = match self.modified.as_deref() {
+            Some(modified) if long && modified != self.added => {
+                let dt = sql_string_to_dt(modified)
+                    .context("Could not format datetime in the desired format")?;
+                format!("\nModified on {}", dt.format(fmt_str.as_ref()))
+            }
+            _ => String::new(),
+        };
+
+        let due = self
+            .due
+            .as_deref()
+            .map(sql_string_to_dt)
+            .transpose()
+            .context("Could not format datetime in the desired format")?;
+        let overdue = due.is_some_and(|due| due < chrono::Local::now().naive_local());
+        let due_row = match due {
+            Some(due) if long => {
+                let row = format!("Due on {}", due.format(fmt_str.as_ref()));
+                if overdue {
+                    format!("\n{} {}", row.red(), "(overdue)".bold().red())
+                } else {
+                    format!("\n{row}")
+                }
+            }
+            _ if overdue => format!(" {}", "(overdue)".bold().red()),
+            _ => String::new(),
+        };
+
+        let archive_row = match self.archive_url.as_deref() {
+            Some(archive_url) if long => {
+                format!("\nArchived at {}", archive_url.link())
+            }
+            _ => String::new(),
+        };
+
+        let snapshot_row = if long && self.snapshot.is_some() {
+            "\nSaved offline".to_string()
+        } else {
+            String::new()
+        };
+
+        let read_row = match self.read_at.as_deref() {
+            Some(read_at) if long => {
+                let dt = sql_string_to_dt(read_at)
+                    .context("Could not format datetime in the desired format")?;
+                format!("\nRead on {}", dt.format(fmt_str.as_ref()))
+            }
+            _ => String::new(),
+        };
+
+        let opens_row = match self.opens {
+            0 => String::new(),
+            1 if long => "\nOpened once".to_string(),
+            n if long => format!("\nOpened {n} times"),
+            _ => String::new(),
+        };
+
+        let archived_row = if long && self.archived {
+            "\nArchived".to_string()
+        } else {
+            String::new()
+        };
+
+        let snoozed_row = match self.snoozed_until.as_deref() {
+            Some(until) if long => {
+                let dt = sql_string_to_dt(until)
+                    .context("Could not format datetime in the desired format")?;
+                if dt > chrono::Local::now().naive_local() {
+                    format!("\nSnoozed until {}", dt.format(fmt_str.as_ref()))
+                } else {
+                    String::new()
+                }
+            }
+            _ => String::new(),
+        };
+
+        let short_id = self
+            .short_id()
+            .map(|id| format!("{} ", id.dimmed()))
+            .unwrap_or_default();
+
+        writeln!(
+            out,
+            "{short_id}{name}: {url}{maybe_author}{site_row}{description_row}{id_row}{topics_row}{meta_row}{length_row}{added_row}{modified_row}{due_row}{archive_row}{snapshot_row}{read_row}{opens_row}{archived_row}{snoozed_row}",
+            name = self.name.entry_name(),
+            url = self.url.link(),
             maybe_author = self
                 .author
                 .as_ref()
-                .map(|v| format!(" by {}", v.green()))
+                .map(|v| format!(" by {}", v.author_name()))
                 .unwrap_or("".into()),
-        );
+        )?;
 
         Ok(())
     }
+
+    /// Returns the entry as a single line of tab separated fields, meant to be parsed by scripts (see `rlist --porcelain`):
+    /// id, short id, name, url, author, topics (separated by commas), added, modified, read on, due, archived (`1` or `0`), length.
+    /// Missing values are empty, and the dates are written as `%Y-%m-%d %H:%M:%S`.
+    /// The format never changes: new fields can only be appended at the end of the line
+    pub fn porcelain(&self) -> String {
+        // Tabs and newlines would break the line into fields or lines
+        let field = |s: &str| s.replace(['\t', '\n', '\r'], " ");
+        let optional = |s: Option<&str>| s.map(field).unwrap_or_default();
+        [
+            self.id.map(|id| id.to_string()).unwrap_or_default(),
+            self.short_id().unwrap_or_default(),
+            field(&self.name),
+            field(&self.url),
+            optional(self.author.as_deref()),
+            field(&self.topics.join(",")),
+            field(&self.added),
+            optional(self.modified.as_deref()),
+            optional(self.read_at.as_deref()),
+            optional(self.due.as_deref()),
+            if self.archived { "1" } else { "0" }.to_string(),
+            self.length.map(|l| l.to_string()).unwrap_or_default(),
+        ]
+        .join("\t")
+    }
+}
+
+fn is_zero(n: &i64) -> bool {
+    *n == 0
 }
@@ -0,0 +1,148 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::theme::Themed;
+
+/// What kind of failure an error is, for the programs that handle the errors of rlist (see `--format json`)
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// An entry, author, topic or file that does not exist
+    NotFound,
+    /// An entry that would have the same name or url as another one, or that is already where it would be put
+    AlreadyExists,
+    /// A failure of the database
+    Database,
+    /// A failure while reading or writing a file
+    Io,
+    /// A failure while fetching a page or a file from the internet
+    Network,
+    /// A file (such as the config or an import) that could not be parsed
+    Parse,
+    /// An invalid command line: an unknown option, or an argument (such as a date, a duration or a url) that could not be understood
+    InvalidInput,
+    /// Any other failure
+    Other,
+}
+
+impl ErrorCode {
+    /// Returns the code of `err`: the one of the first [`RListError`] in its chain of causes,
+    /// or the one matching the first error whose kind is known
+    pub fn of(err: &anyhow::Error) -> Self {
+        err.chain()
+            .find_map(|e| {
+                if let Some(e) = e.downcast_ref::<RListError>() {
+                    Some(e.code)
+                } else if e.is::<rusqlite::Error>() {
+                    Some(Self::Database)
+                } else if e.is::<std::io::Error>() {
+                    Some(Self::Io)
+                } else if e.is::<ureq::Error>() {
+                    Some(Self::Network)
+                } else if e.is::<serde_yaml::Error>() || e.is::<serde_json::Error>() {
+                    Some(Self::Parse)
+                } else if e.is::<clap::Error>() {
+                    Some(Self::InvalidInput)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(Self::Other)
+    }
+}
+
+/// The thing that caused an error, identified by one of its fields (e.g. the entry with name `x`)
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Entity {
+    /// `entry`, `author`, `topic`, `backup` or `profile`, or the kind of an invalid argument: `url`, `date`, `duration` or `metadata`
+    pub kind: &'static str,
+    /// The field the entity was looked for by, such as `name`, `url`, `id` or `path`
+    pub field: &'static str,
+    pub value: String,
+}
+
+impl Entity {
+    pub fn new(kind: &'static str, field: &'static str, value: impl Display) -> Self {
+        Self {
+            kind,
+            field,
+            value: value.to_string(),
+        }
+    }
+}
+
+/// An error caused by a specific entity of the reading list, which is reported along with its message.
+/// Functions return it wrapped in an [`anyhow::Error`]: use [`ErrorCode::of`] or downcast it to tell it apart
+#[derive(Debug, Clone)]
+pub struct RListError {
+    pub code: ErrorCode,
+    pub entity: Entity,
+    message: String,
+}
+
+impl RListError {
+    pub fn new(code: ErrorCode, entity: Entity, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            entity,
+            message: message.into(),
+        }
+    }
+
+    /// There is no entry with name = `name`
+    pub fn entry_not_found(name: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        Self::new(
+            ErrorCode::NotFound,
+            Entity::new("entry", "name", name),
+            format!(
+                "Could not find any entry in your reading list with name {}",
+                name.entry_name()
+            ),
+        )
+    }
+
+    /// There is no author with name = `name`
+    pub fn author_not_found(name: impl AsRef<str>) -> Self {
+        let name = name.as_ref();
+        Self::new(
+            ErrorCode::NotFound,
+            Entity::new("author", "name", name),
+            format!(
+                "Could not find author {} in your reading list",
+                name.author_name()
+            ),
+        )
+    }
+
+    /// `value` is not a valid `kind` (e.g. a `date`), as explained by `message`
+    pub fn invalid_input(
+        kind: &'static str,
+        value: impl Display,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::new(
+            ErrorCode::InvalidInput,
+            Entity::new(kind, "value", value),
+            message,
+        )
+    }
+
+    /// There is no entry with id = `entry_id`
+    pub fn entry_id_not_found(entry_id: i64) -> Self {
+        Self::new(
+            ErrorCode::NotFound,
+            Entity::new("entry", "id", entry_id),
+            format!("Could not find any entry in your reading list with id {entry_id}"),
+        )
+    }
+}
+
+impl Display for RListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for RListError {}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+
+/// The period a reading goal refers to
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl GoalPeriod {
+    /// Returns the index of the period containing `date`. Consecutive periods have consecutive indices
+    fn index_of(&self, date: NaiveDate) -> i64 {
+        match self {
+            GoalPeriod::Day => date.num_days_from_ce() as i64,
+            // Weeks start on Monday, and Mondays are always 7 days apart
+            GoalPeriod::Week => (date.num_days_from_ce() as i64
+                - date.weekday().num_days_from_monday() as i64)
+                .div_euclid(7),
+            GoalPeriod::Month => date.year() as i64 * 12 + date.month0() as i64,
+        }
+    }
+
+    /// The name of the period, e.g. `week`
+    pub fn name(&self) -> &'static str {
+        match self {
+            GoalPeriod::Day => "day",
+            GoalPeriod::Week => "week",
+            GoalPeriod::Month => "month",
+        }
+    }
+
+    /// How the current period is called, e.g. `this week`
+    pub fn current(&self) -> &'static str {
+        match self {
+            GoalPeriod::Day => "today",
+            GoalPeriod::Week => "this week",
+            GoalPeriod::Month => "this month",
+        }
+    }
+}
+
+/// A reading goal, such as reading 3 entries per week
+#[derive(Deserialize, Debug, Clone)]
+pub struct Goal {
+    /// How many entries should be read in each period
+    pub entries: i64,
+    pub per: GoalPeriod,
+}
+
+/// How far along a goal is, as computed by [`Goal::progress`]
+#[derive(Debug)]
+pub struct GoalProgress {
+    /// The number of entries read in the current period
+    pub read: i64,
+    /// The number of consecutive periods in which the goal was met, up to the current one.
+    /// The current period only counts once the goal is met, but does not break the streak before then
+    pub streak: i64,
+    /// The longest streak ever
+    pub best_streak: i64,
+}
+
+impl Goal {
+    /// Computes the progress of the goal on `today`, given the dates on which entries were read
+    pub fn progress(&self, read_dates: &[NaiveDate], today: NaiveDate) -> GoalProgress {
+        let mut per_period: HashMap<i64, i64> = HashMap::new();
+        for date in read_dates.iter() {
+            *per_period.entry(self.per.index_of(*date)).or_default() += 1;
+        }
+        let met =
+            |period: i64| per_period.get(&period).copied().unwrap_or_default() >= self.entries;
+
+        let current = self.per.index_of(today);
+        let mut period = if met(current) { current } else { current - 1 };
+        let mut streak = 0;
+        while met(period) {
+            streak += 1;
+            period -= 1;
+        }
+
+        let mut met_periods = per_period
+            .keys()
+            .copied()
+            .filter(|p| met(*p))
+            .collect::<Vec<_>>();
+        met_periods.sort();
+        let (mut best_streak, mut run) = (0, 0);
+        for (i, period) in met_periods.iter().enumerate() {
+            run = if i > 0 && met_periods[i - 1] == period - 1 {
+                run + 1
+            } else {
+                1
+            };
+            best_streak = best_streak.max(run);
+        }
+
+        GoalProgress {
+            read: per_period.get(&current).copied().unwrap_or_default(),
+            streak,
+            best_streak,
+        }
+    }
+}
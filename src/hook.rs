@@ -0,0 +1,79 @@
+use std::{
+    io::Write,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::entry::Entry;
+
+/// The points of the commands at which a shell hook can be run
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Hook {
+    PreAdd,
+    PostAdd,
+    PreEdit,
+    PostEdit,
+    PreRemove,
+    PostRemove,
+}
+
+impl Hook {
+    /// The name of the hook in the config, e.g. `post_add`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hook::PreAdd => "pre_add",
+            Hook::PostAdd => "post_add",
+            Hook::PreEdit => "pre_edit",
+            Hook::PostEdit => "post_edit",
+            Hook::PreRemove => "pre_remove",
+            Hook::PostRemove => "post_remove",
+        }
+    }
+
+    /// Whether the hook runs before the change is applied, in which case its failure aborts the command
+    pub fn is_pre(&self) -> bool {
+        matches!(self, Hook::PreAdd | Hook::PreEdit | Hook::PreRemove)
+    }
+
+    /// Runs `command` in the shell, writing `entry` as JSON to its stdin, and waits for it to exit.
+    /// The name of the hook and the path of the reading list are passed in `$RLIST_HOOK` and `$RLIST_DB_FILE`.
+    /// Returns an error if the command could not be run or if it exited with a failure
+    pub fn run(&self, command: &str, entry: &Entry, db_file: &Path) -> Result<()> {
+        let mut shell = if cfg!(target_os = "windows") {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+
+        let mut child = shell
+            .arg(command)
+            .env("RLIST_HOOK", self.name())
+            .env("RLIST_DB_FILE", db_file)
+            .stdin(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Could not run the {} hook", self.name()))?;
+
+        let json = serde_json::to_string(entry)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The hook may exit without reading its input, which is not an error
+            let _ = stdin.write_all(json.as_bytes());
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!(
+                "The {} hook failed ({status})",
+                self.name()
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+//! The reading list behind the `rlist` command line tool, which can also be embedded by other programs.
+//!
+//! [`RList`] opens (or creates) a reading list stored in a SQLite database (or in any other [`storage::Storage`]), and exposes all of the operations on it:
+//! ```no_run
+//! use rlist::{AddOptions, RList};
+//!
+//! let rlist = RList::init("/home/me/rlist/rlist.sqlite")?;
+//! rlist.add(
+//!     "The Rust book".to_string(),
+//!     "https://doc.rust-lang.org/book".to_string(),
+//!     AddOptions::default(),
+//! )?;
+//! for entry in rlist.query().topics_all(["rust"]).run()? {
+//!     println!("{}: {}", entry.name, entry.url);
+//! }
+//! # anyhow::Ok(())
+//! ```
+
+pub mod author;
+pub mod backup;
+pub mod config;
+mod db;
+pub mod entry;
+pub mod error;
+pub mod goal;
+pub mod hook;
+pub mod markdown;
+pub mod rlist;
+pub mod storage;
+pub mod theme;
+pub mod topic;
+pub mod utils;
+pub mod web;
+
+pub use crate::{
+    config::Config,
+    entry::Entry,
+    rlist::{AddOptions, EditOptions, OrderBy, QueryBuilder, QueryIter, QueryOptions, RList},
+};
@@ -1,22 +1,15 @@
-use std::{
-    fs,
-    path::{Path, PathBuf},
+use std::{ffi::OsString, io::IsTerminal, path::PathBuf};
+
+use clap::{Parser, ValueEnum};
+use colored::Colorize;
+use commands::{completions, is_broken_pipe, man, Action, AppContext};
+use rlist::{
+    error::{ErrorCode, RListError},
+    storage::StorageKind,
+    Config, RList,
 };
 
-use anyhow::Context;
-use clap::{Parser, Subcommand};
-use config::Config;
-use dateparser::DateTimeUtc;
-use rlist::OrderBy;
-
-use crate::{entry::Entry, rlist::RList};
-
-mod config;
-mod db;
-mod entry;
-mod rlist;
-mod topic;
-mod utils;
+mod commands;
 
 /// Reading list manager for the command line
 #[derive(Parser, Debug)]
@@ -25,299 +18,220 @@ struct Args {
     #[command(subcommand)]
     action: Action,
 
-    /// The path to the database used by rlist to save your reading list. Default path is: `~/rlist/rlist.sqlite` on unix based systems
+    /// The path to the database used by rlist to save your reading list. Default path is: `$XDG_DATA_HOME/rlist/rlist.sqlite` (`~/.local/share/rlist/rlist.sqlite`) on unix based systems
     /// Note that this argument will take precedence over the option set in the config file, if any.
     #[arg(long)]
     db_file: Option<PathBuf>,
 
-    /// The path to the (optional) config file. Default config path (automatically picked up by the program) is `~/.config/rlist.yml` on unix based systems
+    /// The path to the (optional) config file. Default config path (automatically picked up by the program) is `$XDG_CONFIG_HOME/rlist/config.yml` (`~/.config/rlist/config.yml`) on unix based systems
     #[arg(long)]
     config: Option<PathBuf>,
-}
-
-#[derive(Subcommand, Debug)]
-enum Action {
-    /// Add an entry to the reading list
-    #[command(aliases=&["a", "create"])]
-    Add {
-        /// The name of the entry
-        name: String,
-
-        /// The content of the entry
-        url: String,
-
-        /// The author of the content
-        #[arg(short, long)]
-        author: Option<String>,
-
-        /// Topics related to the content of the entry
-        #[arg(short, long, num_args = 1..)]
-        topics: Vec<String>,
-    },
-
-    /// Remove an entry from the reading list
-    #[command(aliases=&["rm", "r", "d", "delete"])]
-    Remove {
-        /// The name of the entry you want to remove
-        /// Takes precedence over --topics/-t
-        name: Option<String>,
-
-        /// Remove ALL of the entries that are linked to ALL of the topics specified after this option
-        #[arg(short, long, num_args = 1..)]
-        topics: Option<Vec<String>>,
-    },
-
-    /// Edit an entry
-    #[command(aliases=&["e", "mv"])]
-    Edit {
-        /// The name of the entry you want to edit
-        old_name: String,
-
-        /// The new name of the entry
-        new_name: Option<String>,
-
-        /// The new author of the entry
-        #[arg(short, long)]
-        author: Option<String>,
-
-        /// The new url of the entry
-        #[arg(long)]
-        url: Option<String>,
-
-        /// Sets the topics of the entry to this list.
-        /// Takes precedence over `--add-topics`. `--topics a b c` is the same as `--clear-topics --add-topics a b c`
-        #[arg(short, long, num_args = 1..)]
-        topics: Option<Vec<String>>,
 
-        /// The topics you want to add to the entry
-        #[arg(long, num_args = 1..)]
-        add_topics: Option<Vec<String>>,
-
-        /// If set, remove the entry from all of the topics
-        #[arg(long)]
-        clear_topics: bool,
-
-        /// The list of topics you want the entry to be removed from
-        #[arg(long, num_args = 1..)]
-        remove_topics: Option<Vec<String>>,
-    },
-
-    /// Show the content of your reading list
-    #[command(aliases=&["ls", "l", "q", "query", "s", "search", "find", "f"])]
-    List {
-        /// A substring that the name of the entries must contain
-        query: Option<String>,
-
-        /// If set, the result will also show the `added` date and the topics for each entry
-        #[arg(short, long)]
-        long: bool,
-
-        /// Only show topics that are in all of the topics specified in this option
-        #[arg(short, long, num_args = 1..)]
-        topics: Option<Vec<String>>,
-
-        /// If set, the list will contain all of the entries that are in at least one of the topics specified with `--topics`
-        #[arg(long)]
-        or: bool,
-
-        /// Only show the entries that have an author name that contains this substring
-        #[arg(short, long)]
-        author: Option<String>,
-
-        /// Only show the entries that have urls that contain this substring
-        #[arg(long)]
-        url: Option<String>,
-
-        /// The attribute used to sort the entries. Options are: name, author, url, added
-        #[arg(short, long)]
-        sort_by: Option<OrderBy>,
-
-        /// Whether to sort in ascending or descending order. Aliases: `--descending`
-        #[arg(short, long, aliases=&["descending"])]
-        desc: bool,
-
-        /// Only show entries added after the datetime passed to this option
-        #[arg(long)]
-        from: Option<String>,
-
-        /// Only show entries added before the datetime passed to this option
-        #[arg(long)]
-        to: Option<String>,
-    },
+    /// Open the reading list as a database encrypted with SQLCipher, prompting for its passphrase if it is not saved in the keyring.
+    /// Requires rlist to be built with the `sqlcipher` feature. Same as setting `encrypted: true` in the config file
+    #[arg(long)]
+    encrypted: bool,
+
+    /// The format of the errors. With `json`, an error is printed to stderr as a JSON object with its `code` (such as `not_found`),
+    /// its `message`, the `entity` that caused it (if any, such as `{"kind": "entry", "field": "name", "value": "..."}`) and its `causes`
+    #[arg(long, value_name = "FORMAT", default_value = "text")]
+    format: OutputFormat,
+
+    /// Never prompt for input: commands that would ask for confirmation fail instead (unless told to proceed, e.g. with `--yes`).
+    /// Automatically enabled when stdin is not a terminal
+    #[arg(long, global = true)]
+    no_input: bool,
+
+    /// Only print the essential output, without headers, totals and confirmations (e.g. "Entry added to rlist")
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Print the entries as lines of tab separated fields, in a format that never changes between versions, for scripts.
+    /// Implies `--quiet`. The fields are: id, short id, name, url, author, topics (separated by commas), added, modified,
+    /// read on, due, archived (1 or 0) and length
+    #[arg(long, global = true)]
+    porcelain: bool,
+
+    /// When to color the output. With `auto`, colors are disabled when the output is not a terminal (e.g. when it is piped
+    /// to another command) or when the `NO_COLOR` environment variable is set, unless `CLICOLOR_FORCE` is set
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto")]
+    color: ColorChoice,
+
+    /// Print what rlist is doing to stderr: which config file was loaded and which reading list was opened.
+    /// Pass it twice (`-vv`) to also print each SQL statement that is run, with the values of its parameters and how long it took
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+}
 
-    /// Imports a set of entries from a yml file
-    /// Note that entries with the same name or url as an entry in your reading list will not be imported (and the topics in the import file will not be appended to existing entry)
-    Import { path: PathBuf },
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
 
-    /// Exports the contennt of the whole reading list into a yml file
-    Export { path: PathBuf },
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
 }
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
-
-    let mut config = Config::new_from_arg(args.config)?;
-    if let Some(p) = args.db_file {
-        config.db_file = p;
+    let args = parse_args(std::env::args_os().collect());
+    let format = args.format;
+    match run(args) {
+        // The output was closed by the command reading it (e.g. `rlist ls | head -1`), which is not a failure of rlist
+        Err(err) if is_broken_pipe(&err) => Ok(()),
+        Err(err) if format == OutputFormat::Json => {
+            eprintln!("{}", error_json(&err));
+            std::process::exit(1);
+        }
+        res => res,
     }
-    let rlist = RList::init(config)?;
+}
 
-    match args.action {
-        Action::Add {
-            name,
-            author,
-            url,
-            topics,
-        } => {
-            let entry = rlist.add(name, url, author, topics)?;
-            println!("Entry added to rlist:");
-            entry.pretty_print(true, rlist.config.datetime_format)?;
+/// Parses the command line `raw_args`, exiting if they are not valid as clap does, except that the error is printed as JSON
+/// if `--format json` is among them
+fn parse_args(raw_args: Vec<OsString>) -> Args {
+    Args::try_parse_from(&raw_args).unwrap_or_else(|err| {
+        let json = raw_args
+            .windows(2)
+            .any(|w| w[0] == "--format" && w[1] == "json")
+            || raw_args.iter().any(|a| a == "--format=json");
+        // Help and version are printed to stdout, and are not errors
+        if !json || !err.use_stderr() {
+            err.exit();
         }
-        Action::Remove { name, topics } => {
-            if name.is_some() {
-                let old_entry = rlist.remove_by_name(name.unwrap())?;
-                print!("Removed entry: \n");
-                old_entry.pretty_print(true, rlist.config.datetime_format)?;
-                println!();
-            } else if topics.is_some() {
-                let old_entries = rlist.remove_by_topics(topics.unwrap())?;
-                if old_entries.len() == 0 {
-                    println!("No entries were removed");
-                    return Ok(());
-                }
-                
-                println!("Removed these entries:");
-                old_entries.iter().for_each(|e| {
-                    if let Err(e) = e.pretty_print(true, &rlist.config.datetime_format) {
-                        eprintln!("{}", e);
-                    }
-                    println!();
-                });
+        let code = err.exit_code();
+        // The error of clap also contains the usage, which is kept among the causes
+        let rendered = err.to_string();
+        let message = rendered
+            .lines()
+            .take_while(|line| !line.trim().is_empty())
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let message = message
+            .strip_prefix("error: ")
+            .unwrap_or(&message)
+            .to_string();
+        eprintln!("{}", error_json(&anyhow::Error::new(err).context(message)));
+        std::process::exit(code);
+    })
+}
 
-                if old_entries.len() > 1 {
-                    println!("Removed a total of {} entries", old_entries.len());
-                }
-            } else {
-                // If neither name or topics is passed to the cli
-                return Err(anyhow::anyhow!("No criteria for deletion was selected"));
-            }
-        }
-        Action::Edit {
-            old_name,
-            new_name,
-            author,
-            url,
-            topics,
-            add_topics,
-            clear_topics,
-            remove_topics,
-        } => {
-            let new_entry = rlist.edit(
-                old_name,
-                new_name,
-                author,
-                url,
-                topics,
-                add_topics,
-                clear_topics,
-                remove_topics,
-            )?;
-            println!("Here's the edited entry:");
-            new_entry.pretty_print(true, rlist.config.datetime_format)?;
-            println!();
-        }
-        Action::List {
-            long,
-            query,
-            topics,
-            author,
-            url,
-            sort_by,
-            desc,
-            from,
-            to,
-            or,
-        } => {
-            let opt_from = if let Some(inner) = from {
-                Some(inner.parse::<DateTimeUtc>()?)
-            } else {
-                None
-            };
-            let opt_to = if let Some(inner) = to {
-                Some(inner.parse::<DateTimeUtc>()?)
-            } else {
-                None
-            };
+/// Returns `err` as the JSON object printed with `--format json`, without the colors of its message
+fn error_json(err: &anyhow::Error) -> serde_json::Value {
+    let ansi = regex::Regex::new("\x1b\\[[0-9;]*m").expect("the regex is valid");
+    let plain = |e: &dyn std::fmt::Display| ansi.replace_all(&e.to_string(), "").into_owned();
+    serde_json::json!({
+        "code": ErrorCode::of(err),
+        "message": plain(err),
+        "entity": err.chain().find_map(|e| e.downcast_ref::<RListError>()).map(|e| &e.entity),
+        "causes": err.chain().skip(1).map(|e| plain(e)).collect::<Vec<_>>(),
+    })
+}
 
-            let entries = rlist.query(
-                query, topics, author, url, sort_by, desc, opt_from, opt_to, or,
-            )?;
+fn run(args: Args) -> anyhow::Result<()> {
+    match args.color {
+        ColorChoice::Auto => {}
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+    }
+    // These commands only describe rlist itself, so they must work even when the config or the reading list cannot be opened
+    match args.action {
+        Action::Completions(completions) => {
+            return completions::run(&mut std::io::stdout(), completions)
+        }
+        Action::Man => return man::run(&mut std::io::stdout()),
+        _ => {}
+    }
 
-            entries.iter().for_each(|e| {
-                if let Err(e) = e.pretty_print(long, &rlist.config.datetime_format) {
-                    eprintln!("{}", e);
-                }
-                println!();
-            });
+    let mut config = Config::new_from_arg(args.config.clone())?;
+    let args = expand_alias(args, &config);
+    if let Some(p) = args.db_file {
+        config.db_file = p;
+    }
+    config.migrate_legacy_data_dir()?;
+    config.encrypted |= args.encrypted;
+    if args.verbose > 0 {
+        print_setup(&config);
+    }
+    rlist::theme::set_theme(config.theme.clone());
+    let mut rlist = RList::open(config.storage()?)?;
+    rlist.trace_sql(args.verbose > 1);
+    match config.backup_policy() {
+        // The backups would not be encrypted
+        Some(_policy) if config.encrypted => eprintln!(
+            "{}: automatic backups are not supported for encrypted databases, and are disabled",
+            "Warning".bold().yellow()
+        ),
+        Some(policy) => rlist.set_backups(policy),
+        None => {}
+    }
 
-            if entries.len() > 0 {
-                println!(
-                    "A total of {} {} matched your query",
-                    entries.len(),
-                    if entries.len() == 1 {
-                        "entry"
-                    } else {
-                        "entries"
-                    }
-                );
-            }
-        }
-        Action::Import { path } => {
-            let content =
-                fs::read_to_string(&path).context("Could not import reading list from file")?;
-            let entries: Vec<Entry> = serde_yaml::from_str(&content)
-                .context("Could not import reading list from file")?;
-            let imported_count = rlist.import(entries)?;
+    let mut ctx = AppContext {
+        config,
+        rlist,
+        out: Box::new(std::io::stdout()),
+        no_input: args.no_input || !std::io::stdin().is_terminal(),
+        quiet: args.quiet || args.porcelain,
+        porcelain: args.porcelain,
+    };
+    args.action.run(&mut ctx)
+}
 
-            println!(
-                "Imported {imported_count} {word}{source}",
-                word = if imported_count == 1 {
-                    "entry"
-                } else {
-                    "entries"
-                },
-                source = path
-                    .to_str()
-                    .map(|p| format!(" from {p}"))
-                    .unwrap_or_default()
-            );
+/// Prints the config file that was loaded and the reading list that is opened, for `--verbose`
+fn print_setup(config: &Config) {
+    let label = "Verbose".bold().dimmed();
+    match &config.path {
+        Some(path) => eprintln!("{label}: loaded the config file {}", path.display()),
+        None => eprintln!("{label}: no config file was found, using the default config"),
+    }
+    let file = || config.db_file.display().to_string();
+    // The url of a database can contain its password
+    let url = || match config.db_url.as_deref().map(url::Url::parse) {
+        Some(Ok(mut url)) => {
+            let _ = url.set_password(None);
+            url.to_string()
         }
-        Action::Export { path } => {
-            let entries = rlist.dump_all()?;
-            fs::create_dir_all(
-                Path::new(&path)
-                    .parent()
-                    .ok_or(anyhow::anyhow!("Could not create the export file"))?,
-            )?;
-            let content = serde_yaml::to_string(&entries)
-                .context("Could not export the content of your reading list")?;
-            fs::write(&path, content)
-                .context("Could not export the content of your reading list")?;
+        _ => "the url set with `db_url`".to_string(),
+    };
+    let (kind, location) = match config.storage {
+        StorageKind::Sqlite if config.encrypted => ("encrypted SQLite database", file()),
+        StorageKind::Sqlite => ("SQLite database", file()),
+        StorageKind::File => ("file", file()),
+        StorageKind::Postgres => ("PostgreSQL database", url()),
+        StorageKind::Libsql => ("libsql database", url()),
+    };
+    eprintln!("{label}: opening the reading list in the {kind} at {location}");
+}
 
-            println!(
-                "Exported {count} {word}{destination}",
-                count = entries.len(),
-                word = if entries.len() == 1 {
-                    "entry"
-                } else {
-                    "entries"
-                },
-                destination = path
-                    .to_str()
-                    .map(|p| format!(" to {p}"))
-                    .unwrap_or_default()
-            );
-        }
+/// If the subcommand is one of the aliases in the config, parses the arguments again with the alias replaced by the arguments
+/// it stands for. Unknown subcommands are parsed as plugins, so the commands of rlist take precedence over the aliases,
+/// which take precedence over the plugins
+fn expand_alias(args: Args, config: &Config) -> Args {
+    let Action::Plugin(plugin_args) = &args.action else {
+        return args;
+    };
+    let Some(expansion) = plugin_args.first().and_then(|name| config.alias(name)) else {
+        return args;
+    };
+    if args.verbose > 0 {
+        eprintln!(
+            "{}: expanding the alias {} to `{}`",
+            "Verbose".bold().dimmed(),
+            plugin_args[0],
+            shlex::try_join(expansion.iter().map(String::as_str)).unwrap_or_default()
+        );
     }
-    Ok(())
+    // The arguments of a plugin are the last ones, starting from its name
+    let raw_args = std::env::args_os().collect::<Vec<_>>();
+    let at = raw_args.len() - plugin_args.len();
+    let expanded = raw_args[..at]
+        .iter()
+        .cloned()
+        .chain(expansion.iter().map(OsString::from))
+        .chain(raw_args[at + 1..].iter().cloned());
+    parse_args(expanded.collect())
 }
@@ -0,0 +1,162 @@
+/// A `[title](url)` link found in a markdown document
+#[derive(Debug, Clone)]
+pub struct MarkdownLink {
+    pub title: String,
+    pub url: String,
+    /// The topic the link is filed under, derived from the `##` headings it comes after.
+    /// Nested headings (`###`, ...) give a topic nested under the one of their parent heading, such as `rust/async`
+    pub topic: Option<String>,
+}
+
+/// Returns all of the `[title](url)` links in the markdown document `content`, in order.
+/// Images, links with an empty title or url and the content of code blocks are skipped.
+/// The level 1 heading (`#`) is taken as the title of the document, so it does not give a topic
+pub fn parse_links(content: &str) -> Vec<MarkdownLink> {
+    let mut links = Vec::new();
+    // The text of the current heading of each level, starting from `##`
+    let mut headings: Vec<String> = Vec::new();
+    let mut in_code_block = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        if let Some((level, text)) = parse_heading(trimmed) {
+            if level >= 2 {
+                // Skipped levels (a `####` right after a `##`) are left out of the topic
+                headings.resize(level - 2, String::new());
+                headings.push(strip_links(text));
+            } else {
+                headings.clear();
+            }
+            continue;
+        }
+
+        let topic = headings
+            .iter()
+            .filter(|h| !h.is_empty())
+            .map(|h| h.replace('/', "-"))
+            .collect::<Vec<_>>();
+        let topic = (!topic.is_empty()).then(|| topic.join("/"));
+        for (title, url) in find_links(line) {
+            links.push(MarkdownLink {
+                title,
+                url,
+                topic: topic.clone(),
+            });
+        }
+    }
+    links
+}
+
+/// Returns the level and the text of `line` if it is an ATX heading (`## text`)
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim();
+    Some((level, text))
+}
+
+/// Replaces the links in `text` with their titles
+fn strip_links(text: &str) -> String {
+    let mut res = String::new();
+    let mut rest = text;
+    while let Some((start, title, _url, end)) = next_link(rest) {
+        res.push_str(&rest[..start]);
+        res.push_str(&title);
+        rest = &rest[end..];
+    }
+    res.push_str(rest);
+    res.trim().to_string()
+}
+
+/// Returns the titles and the urls of the links in `line`
+fn find_links(line: &str) -> Vec<(String, String)> {
+    let mut links = Vec::new();
+    let mut rest = line;
+    while let Some((start, title, url, end)) = next_link(rest) {
+        let is_image = rest[..start].ends_with('!');
+        if !is_image && !title.is_empty() && !url.is_empty() {
+            links.push((title, url));
+        }
+        rest = &rest[end..];
+    }
+    links
+}
+
+/// Finds the first link in `s`, returning the index of its `[`, its title, its url and the index right after its `)`
+fn next_link(s: &str) -> Option<(usize, String, String, usize)> {
+    let mut search_from = 0;
+    loop {
+        let start = search_from + s[search_from..].find('[')?;
+        // The title can contain balanced brackets, such as `[The [Rust] book](...)`
+        let mut depth = 0;
+        let mut title_end = None;
+        for (i, c) in s[start..].char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        title_end = Some(start + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(title_end) = title_end else {
+            search_from = start + 1;
+            continue;
+        };
+        if !s[title_end + 1..].starts_with('(') {
+            search_from = start + 1;
+            continue;
+        }
+
+        let url_start = title_end + 2;
+        // Urls can contain balanced parentheses too, such as Wikipedia links
+        let mut depth = 1;
+        let mut url_end = None;
+        for (i, c) in s[url_start..].char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        url_end = Some(url_start + i);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let Some(url_end) = url_end else {
+            search_from = start + 1;
+            continue;
+        };
+
+        let title = s[start + 1..title_end].trim().to_string();
+        // The url can be followed by a title, as in `[title](url "tooltip")`, and can be wrapped in `<>`
+        let url = s[url_start..url_end]
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .to_string();
+        return Some((start, title, url, url_end + 1));
+    }
+}
@@ -1,20 +1,61 @@
-use crate::config::Config;
-use crate::entry::Entry;
-use anyhow::Result;
+use crate::theme::Themed;
+use crate::{
+    author::Author,
+    backup::BackupPolicy,
+    entry::Entry,
+    error::{Entity, ErrorCode, RListError},
+    storage::{SqliteStorage, Storage},
+    topic::Topic,
+};
+use anyhow::{Context, Result};
 use colored::Colorize;
 use dateparser::DateTimeUtc;
-use std::{collections::HashSet, path::Path, str::FromStr};
+use regex::Regex;
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    ffi::{c_int, c_uint, c_void, CStr},
+    fmt::Display,
+    io::Write,
+    path::Path,
+    str::FromStr,
+    time::Duration,
+};
 
-use crate::db::{entry::DBEntry, topic::DBTopic};
-use crate::read_sql_response;
-use crate::utils::{dt_to_string, opt_from_sql};
+use crate::db::table_exists;
+use crate::db::{
+    author::DBAuthor,
+    entry::{DBEntry, RLIST_COLUMNS},
+    history::DBHistory,
+    maintenance::DBMaintenance,
+    meta::DBMeta,
+    migrations,
+    oplog::DBOpLog,
+    queue::DBQueue,
+    snapshot::DBSnapshot,
+    stats::DBStats,
+    topic::{subtopics_cte, DBTopic},
+    trash::DBTrash,
+};
+use crate::utils::{domain_of, dt_to_string, escape_like, normalize_name, sql_string_to_dt};
+use rusqlite::{
+    backup::Progress, ffi, params_from_iter, types::Value, Connection, DatabaseName, OpenFlags,
+    OptionalExtension,
+};
+use serde::Deserialize;
 
-#[derive(Debug, Clone)]
+/// The attributes the entries can be sorted by. Can also be read from the config, in lowercase
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
 pub enum OrderBy {
     Name,
     Url,
     Author,
     Added,
+    Modified,
+    Length,
+    Opens,
+    Due,
 }
 
 impl FromStr for OrderBy {
@@ -26,366 +67,2505 @@ impl FromStr for OrderBy {
             "url" => Ok(Self::Url),
             "author" => Ok(Self::Author),
             "added" => Ok(Self::Added),
+            "modified" => Ok(Self::Modified),
+            "length" => Ok(Self::Length),
+            "opens" => Ok(Self::Opens),
+            "due" => Ok(Self::Due),
             other => Err(anyhow::anyhow!("Option \"{other}\" not recognized")),
         }
     }
 }
 
-impl ToString for OrderBy {
-    fn to_string(&self) -> String {
-        (match self {
+impl OrderBy {
+    /// Returns the SQL expression used to sort the rows of `rlist AS ls` joined with `authors AS a`
+    fn column(&self) -> &'static str {
+        match self {
+            OrderBy::Name => "ls.name",
+            OrderBy::Url => "ls.url",
+            OrderBy::Author => "a.name",
+            OrderBy::Added => "ls.added",
+            OrderBy::Modified => "ls.modified",
+            OrderBy::Length => "ls.length",
+            OrderBy::Opens => "ls.opens",
+            OrderBy::Due => "ls.due",
+        }
+    }
+}
+
+impl Display for OrderBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             OrderBy::Name => "name",
             OrderBy::Url => "url",
             OrderBy::Author => "author",
             OrderBy::Added => "added",
+            OrderBy::Modified => "modified",
+            OrderBy::Length => "length",
+            OrderBy::Opens => "opens",
+            OrderBy::Due => "due",
         })
-        .to_string()
     }
 }
 
-pub struct RList {
-    conn: sqlite::Connection,
-    pub config: Config,
+/// The filters used to select entries from the reading list, and the order in which they are returned
+#[derive(Default)]
+pub struct QueryOptions {
+    /// A substring that the name of the entries must contain
+    pub query: Option<String>,
+    /// The entries must be related to __all__ of these topics (or to __at least one__ of them if `or` is set)
+    pub topics: Option<Vec<String>>,
+    pub or: bool,
+    /// A substring that the author of the entries must contain
+    pub author: Option<String>,
+    /// A substring that the url of the entries must contain
+    pub url: Option<String>,
+    /// If set, `query`, `author` and `url` are SQL `LIKE` patterns that must match the whole value
+    /// (`%` matches any sequence of characters and `_` any single character), instead of plain substrings
+    pub like: bool,
+    /// The domain of the url of the entries (subdomains included)
+    pub domain: Option<String>,
+    /// `from` and `to` control the range of the dates in which the returned entries were created
+    pub from: Option<DateTimeUtc>,
+    pub to: Option<DateTimeUtc>,
+    /// `modified_from` and `modified_to` control the range of the dates in which the returned entries were last changed
+    pub modified_from: Option<DateTimeUtc>,
+    pub modified_to: Option<DateTimeUtc>,
+    /// Only the entries that were added or changed after this datetime are returned
+    pub changed_since: Option<DateTimeUtc>,
+    /// Terms that the saved snapshots of the entries must __all__ contain. Entries without a snapshot are excluded
+    pub content: Option<String>,
+    /// The maximum length (in minutes) of the entries. Entries whose length is not known are excluded
+    pub max_length: Option<i64>,
+    /// If `Some(false)`, entries that are currently snoozed are excluded. If `Some(true)`, __only__ the snoozed entries are returned.
+    pub snoozed: Option<bool>,
+    /// If `Some(false)`, archived entries are excluded. If `Some(true)`, __only__ the archived entries are returned.
+    pub archived: Option<bool>,
+    /// If `Some(false)`, only the unread entries are returned. If `Some(true)`, only the read ones are.
+    pub read: Option<bool>,
+    /// `read_after` and `read_before` control the range of the dates in which the returned entries were read.
+    /// Unread entries are excluded when any of them is set
+    pub read_after: Option<DateTimeUtc>,
+    pub read_before: Option<DateTimeUtc>,
+    /// Only the entries due before this datetime (formatted like SQLITE_DATETIME_FORMAT) are returned
+    pub due_before: Option<String>,
+    /// Only the entries whose due date has passed are returned
+    pub overdue: bool,
+    /// The entries must have __all__ of these metadata keys. If a value is given, the metadata must also have that exact value
+    pub meta: Vec<(String, Option<String>)>,
+    pub sort_by: Option<OrderBy>,
+    pub desc: bool,
 }
 
-impl RList {
-    /// Creates the db file, initializes the tables and establishes a connection to the sqlite db
-    /// Forwards the errors raised by the called functions, such as std::fs and sqlite ones.
-    pub fn init(config: Config) -> Result<Self> {
-        std::fs::create_dir_all(&Path::new(&config.db_file).parent().ok_or(anyhow::anyhow!(
-            "Could not create directories needed to create the reading list"
-        ))?)?;
-
-        let conn = sqlite::open(&config.db_file)?;
-
-        let q = "
-        PRAGMA foreign_keys = ON;
-        CREATE TABLE IF NOT EXISTS rlist (
-            entry_id INTEGER PRIMARY KEY,
-            name TEXT NON NULL UNIQUE,
-            url TEXT NOT NULL UNIQUE,
-            author TEXT,
-            added DATETIME NOT NULL DEFAULT (datetime('now', 'localtime'))
-        );
-        CREATE TABLE IF NOT EXISTS topics (
-            topic_id INTEGER PRIMARY KEY,
-            name TEXT NON NULL UNIQUE
-        );
-        CREATE TABLE IF NOT EXISTS rlist_has_topic (
-            entry_id INTEGER,
-            topic_id INTEGER,
-            PRIMARY KEY (entry_id, topic_id),
-            FOREIGN KEY (entry_id) REFERENCES rlist (entry_id) ON UPDATE CASCADE ON DELETE CASCADE,
-            FOREIGN KEY (topic_id) REFERENCES topics (topic_id) ON UPDATE CASCADE ON DELETE CASCADE
-        );";
-        conn.execute(q)?;
-
-        Ok(Self { conn, config })
-    }
-
-    /// Adds the entry to the database. Returns Ok(()) if the entry was added
-    pub fn add(
-        &self,
-        name: String,
-        url: String,
-        author: Option<String>,
-        topics: Vec<String>,
-    ) -> Result<Entry> {
-        let (entry_id, mut entry) =
-            DBEntry::create(&self.conn, name.as_str(), url.as_str(), author.as_deref(), None)?;
-
-        if topics.len() > 0 {
-            let topic_ids = DBTopic::create_many(&self.conn, &topics)?;
-            DBEntry::associate_with_topics(&self.conn, entry_id, topic_ids)?;
+impl QueryOptions {
+    /// Returns the `ORDER BY` clause sorting the rows of `rlist AS ls` joined with `authors AS a` as specified by `sort_by` and `desc`.
+    /// Ties are broken by the id of the entries, so that the order is stable
+    fn order_clause(&self) -> String {
+        match &self.sort_by {
+            Some(sort_col) => {
+                let order = if self.desc { "DESC" } else { "ASC" };
+                format!("ORDER BY {} {order}, ls.entry_id", sort_col.column())
+            }
+            None => "ORDER BY ls.entry_id".to_string(),
         }
-        entry.topics = topics;
-
-        Ok(entry)
     }
 
-    /// Removes the entry by name. Returns Ok(the old entry if it existed)
-    pub fn remove_by_name(&self, name: String) -> Result<Entry> {
-        DBEntry::remove_by_name(&self.conn, name.clone())
+    /// Returns the condition on `column` for the `query`, `author` and `url` filters, along with the value to be bound to its parameter.
+    /// `value` is a substring of the column, unless `like` is set
+    fn like_filter(&self, column: &str, value: &str) -> (String, Value) {
+        if self.like {
+            (format!("{column} LIKE ?"), value.to_string().into())
+        } else {
+            (
+                format!("{column} LIKE '%' || ? || '%' ESCAPE '\\'"),
+                escape_like(value).into(),
+            )
+        }
     }
 
-    /// Returns the list of entries that match the query.
-    /// If query is set, then it will be contained in each of the entries' names
-    /// If author is set, then only entries with an author that contains this value will be returned
-    /// Same with url
-    /// If topics is set, then the returned enties will be contained in __all__ of those topics. If `or` is set to true,
-    /// then the function will return the entries that are in __at least one__ of the topics.
-    /// `from` and `to` control the range of the dates in which the returned entries were created.
-    pub fn query(
-        &self,
-        query: Option<String>,
-        topics: Option<Vec<String>>,
-        author: Option<String>,
-        url: Option<String>,
-        sort_by: Option<OrderBy>,
-        desc: bool,
-        from: Option<DateTimeUtc>,
-        to: Option<DateTimeUtc>,
-        or: bool,
-    ) -> Result<Vec<Entry>> {
+    /// Returns the `WHERE` clause (or an empty string if there are no filters) selecting the entries of the `rlist AS ls` table
+    /// that match the filters, along with the values to be bound to its positional parameters
+    fn where_clause(&self) -> (String, Vec<Value>) {
         let mut bindings = Vec::new();
         let mut clauses = Vec::new();
-        if query.is_some() {
-            clauses.push("ls.name LIKE '%' || :q || '%'");
-            bindings.push((":q", query.as_deref().unwrap()));
+        if let Some(query) = &self.query {
+            let (matches, pattern) = self.like_filter("ls.name", query);
+            clauses.push(matches);
+            bindings.push(pattern);
         };
-        if author.is_some() {
-            clauses.push("ls.author LIKE '%' || :author || '%'");
-            bindings.push((":author", author.as_deref().unwrap()));
+        if let Some(author) = &self.author {
+            let (matches, pattern) = self.like_filter("name", author);
+            clauses.push(format!(
+                "ls.author_id IN (SELECT author_id FROM authors WHERE {matches})"
+            ));
+            bindings.push(pattern);
+        }
+        if let Some(url) = &self.url {
+            let (matches, pattern) = self.like_filter("ls.url", url);
+            clauses.push(matches);
+            bindings.push(pattern);
         }
-        if url.is_some() {
-            clauses.push("ls.url LIKE '%' || :url || '%'");
-            bindings.push((":url", url.as_deref().unwrap()));
+        if let Some(domain) = &self.domain {
+            let domain = domain.to_lowercase();
+            let domain = domain.strip_prefix("www.").unwrap_or(&domain);
+            clauses.push("(ls.domain = ? OR ls.domain LIKE '%.' || ? ESCAPE '\\')".to_string());
+            bindings.push(domain.to_string().into());
+            bindings.push(escape_like(domain).into());
         }
 
         // SQLite format:  YYYY-MM-DD HH:MM:SS
-        let opt_from = from.map(|dt| dt_to_string(dt));
-        if let Some(from) = opt_from.as_deref() {
-            clauses.push("ls.added >= :from");
-            bindings.push((":from", from.as_ref()));
+        if let Some(from) = &self.from {
+            clauses.push("ls.added >= ?".to_string());
+            bindings.push(dt_to_string(from).into());
         }
-        let opt_to = to.map(|dt| dt_to_string(dt));
-        if let Some(to) = opt_to.as_deref() {
-            clauses.push("ls.added <= :to");
-            bindings.push((":to", to.as_ref()));
+        if let Some(to) = &self.to {
+            clauses.push("ls.added <= ?".to_string());
+            bindings.push(dt_to_string(to).into());
+        }
+        if let Some(from) = &self.modified_from {
+            clauses.push("ls.modified >= ?".to_string());
+            bindings.push(dt_to_string(from).into());
+        }
+        if let Some(to) = &self.modified_to {
+            clauses.push("ls.modified <= ?".to_string());
+            bindings.push(dt_to_string(to).into());
+        }
+        if let Some(since) = &self.changed_since {
+            clauses.push("(ls.added >= ? OR ls.modified >= ?)".to_string());
+            bindings.push(dt_to_string(since).into());
+            bindings.push(dt_to_string(since).into());
         }
 
-        let sort = if let Some(sort_col) = sort_by {
-            let order = if desc { "DESC" } else { "ASC" };
-            format!("ORDER BY {} {};", sort_col.to_string(), order)
-        } else {
-            ";".to_string()
-        };
+        if let Some(content) = &self.content {
+            clauses.push(
+                "ls.entry_id IN (SELECT rowid FROM snapshots_fts WHERE snapshots_fts MATCH ?)"
+                    .to_string(),
+            );
+            bindings.push(DBSnapshot::match_expression(content).into());
+        }
 
-        let q = format!(
-            "
-            SELECT 
-                ls.name AS name, 
-                ls.url AS url, 
-                ls.author AS author, 
-                ls.added AS added, 
-                t.name AS topic 
-            FROM rlist AS ls 
-            LEFT OUTER JOIN rlist_has_topic AS rht 
-                ON ls.entry_id = rht.entry_id 
-            LEFT OUTER JOIN topics AS t 
-                ON t.topic_id = rht.topic_id
-            {}
-            {sort}",
-            if clauses.len() > 0 {
-                format!("WHERE {}", clauses.join(" AND "))
-            } else {
-                "".to_string()
-            }
-        );
+        if let Some(max_length) = self.max_length {
+            clauses.push("ls.length <= ?".to_string());
+            bindings.push(max_length.into());
+        }
 
-        let mut stmt = self.conn.prepare(q)?;
-        stmt.bind_iter(bindings)?;
+        match self.snoozed {
+            Some(true) => {
+                clauses.push("ls.snoozed_until > datetime('now', 'localtime')".to_string())
+            }
+            Some(false) => clauses.push(
+                "(ls.snoozed_until IS NULL OR ls.snoozed_until <= datetime('now', 'localtime'))"
+                    .to_string(),
+            ),
+            None => {}
+        }
 
-        let mut res: Vec<Entry> = Vec::new();
+        match self.archived {
+            Some(true) => clauses.push("ls.archived".to_string()),
+            Some(false) => clauses.push("NOT ls.archived".to_string()),
+            None => {}
+        }
 
-        while let sqlite::State::Row = stmt.next()? {
-            let name = stmt.read::<String, _>("name")?;
-            let topic = stmt.read::<String, _>("topic").ok();
+        match self.read {
+            Some(true) => clauses.push("ls.read_at IS NOT NULL".to_string()),
+            Some(false) => clauses.push("ls.read_at IS NULL".to_string()),
+            None => {}
+        }
+        if let Some(after) = &self.read_after {
+            clauses.push("ls.read_at >= ?".to_string());
+            bindings.push(dt_to_string(after).into());
+        }
+        if let Some(before) = &self.read_before {
+            clauses.push("ls.read_at <= ?".to_string());
+            bindings.push(dt_to_string(before).into());
+        }
+        if let Some(due_before) = &self.due_before {
+            clauses.push("ls.due <= ?".to_string());
+            bindings.push(due_before.to_string().into());
+        }
+        if self.overdue {
+            clauses.push("ls.due < datetime('now', 'localtime')".to_string());
+        }
 
-            // If the entry is already in the vector, then just add the current topic to the entry's topics
-            if let Some(pos) = res.iter().position(|e| e.name == name) {
-                if topic.is_some() {
-                    res[pos].topics.push(topic.unwrap());
+        for (key, value) in self.meta.iter() {
+            match value {
+                Some(value) => {
+                    clauses.push(
+                        "ls.entry_id IN (SELECT entry_id FROM entry_meta WHERE key = ? AND value = ?)"
+                            .to_string(),
+                    );
+                    bindings.push(key.to_string().into());
+                    bindings.push(value.to_string().into());
                 }
+                None => {
+                    clauses.push(
+                        "ls.entry_id IN (SELECT entry_id FROM entry_meta WHERE key = ?)"
+                            .to_string(),
+                    );
+                    bindings.push(key.to_string().into());
+                }
+            }
+        }
+
+        if let Some(topics) = &self.topics {
+            let topics = topics.iter().map(Topic::normalize).collect::<HashSet<_>>();
+            // Entries related to a subtopic are related to its parent too.
+            // With AND semantics, an entry must be related to as many of the topics as there are (distinct) topics
+            let having = if self.or {
+                String::new()
             } else {
-                // else create a new entry
-                read_sql_response!(stmt, url => String, added => String, author => String);
-                let author = opt_from_sql(author);
+                format!("HAVING COUNT(DISTINCT sub.root) = {}", topics.len())
+            };
+            clauses.push(format!(
+                "ls.entry_id IN (
+                    {cte}
+                    SELECT rht.entry_id
+                    FROM rlist_has_topic AS rht
+                    JOIN sub
+                        ON sub.topic_id = rht.topic_id
+                    GROUP BY rht.entry_id
+                    {having}
+                )",
+                cte = subtopics_cte(&topics.iter().map(|_t| "?").collect::<Vec<_>>().join(", "))
+            ));
+            bindings.extend(topics.into_iter().map(|t| t.to_string().into()));
+        }
 
-                let topics = topic.map(|t| vec![t]).unwrap_or_default();
+        let where_clause = if !clauses.is_empty() {
+            format!("WHERE {}", clauses.join(" AND "))
+        } else {
+            "".to_string()
+        };
+        (where_clause, bindings)
+    }
+}
 
-                let entry = Entry::new(name.clone(), url, author, topics, Some(added));
-                res.push(entry);
-            }
-        }
+/// Builds and runs a query on the reading list, created by [`RList::query`]:
+/// ```no_run
+/// # use rlist::{OrderBy, RList};
+/// # let rlist = RList::init("rlist.sqlite")?;
+/// let entries = rlist
+///     .query()
+///     .name_contains("async")
+///     .topics_all(["rust"])
+///     .sort(OrderBy::Added)
+///     .desc()
+///     .run()?;
+/// # anyhow::Ok(())
+/// ```
+pub struct QueryBuilder<'a> {
+    rlist: &'a RList,
+    opts: QueryOptions,
+}
 
-        // Filter out the topics based on topics
-        if let Some(topics) = topics {
-            let required_topics_set = topics.iter().collect::<HashSet<_>>();
+impl<'a> QueryBuilder<'a> {
+    /// Replaces all of the filters and the sort order set so far with `opts`
+    pub fn options(mut self, opts: QueryOptions) -> Self {
+        self.opts = opts;
+        self
+    }
 
-            res = res
-                .into_iter()
-                .filter(|entry| {
-                    let entry_topics_set = entry.topics.iter().collect::<HashSet<_>>();
+    /// The name of the entries must contain `query`
+    pub fn name_contains(mut self, query: impl Into<String>) -> Self {
+        self.opts.query = Some(query.into());
+        self
+    }
 
-                    let intersection_len = entry_topics_set
-                        .intersection(&required_topics_set)
-                        .collect::<Vec<_>>()
-                        .len();
+    /// The entries must be related to all of `topics` (or to their subtopics)
+    pub fn topics_all<T: Into<String>>(mut self, topics: impl IntoIterator<Item = T>) -> Self {
+        self.opts.topics = Some(topics.into_iter().map(Into::into).collect());
+        self.opts.or = false;
+        self
+    }
 
-                    if or {
-                        intersection_len > 0
-                    } else {
-                        intersection_len == required_topics_set.len()
-                    }
-                })
-                .collect();
-        }
+    /// The entries must be related to at least one of `topics` (or to their subtopics)
+    pub fn topics_any<T: Into<String>>(mut self, topics: impl IntoIterator<Item = T>) -> Self {
+        self.opts.topics = Some(topics.into_iter().map(Into::into).collect());
+        self.opts.or = true;
+        self
+    }
 
-        Ok(res)
+    /// The author of the entries must contain `author`
+    pub fn author_contains(mut self, author: impl Into<String>) -> Self {
+        self.opts.author = Some(author.into());
+        self
     }
 
-    pub fn edit(
-        &self,
-        old_name: String,
-        new_name: Option<String>,
-        author: Option<String>,
-        url: Option<String>,
-        topics: Option<Vec<String>>,
-        add_topics: Option<Vec<String>>,
-        clear_topics: bool,
-        remove_topics: Option<Vec<String>>,
-    ) -> Result<Entry> {
-        // If no edit is set, then return an error
-        if new_name.is_none()
-            && author.is_none()
-            && url.is_none()
-            && topics.is_none()
-            && add_topics.is_none()
-            && !clear_topics
-            && remove_topics.is_none()
-        {
-            return Err(anyhow::anyhow!("No edit options were given"));
-        }
+    /// The url of the entries must contain `url`
+    pub fn url_contains(mut self, url: impl Into<String>) -> Self {
+        self.opts.url = Some(url.into());
+        self
+    }
 
-        let mut updates = Vec::new();
-        let mut bindings = vec![(":old_name", old_name.as_ref())];
-        if new_name.is_some() {
-            updates.push("name = :new_name");
-            bindings.push((":new_name", new_name.as_deref().unwrap()));
+    /// Match the name, the author and the url of the entries against SQL `LIKE` patterns instead of substrings (see [`QueryOptions::like`])
+    pub fn like(mut self) -> Self {
+        self.opts.like = true;
+        self
+    }
+
+    /// The url of the entries must belong to `domain` (or to one of its subdomains)
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.opts.domain = Some(domain.into());
+        self
+    }
+
+    pub fn added_after(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.from = Some(dt);
+        self
+    }
+
+    pub fn added_before(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.to = Some(dt);
+        self
+    }
+
+    pub fn modified_after(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.modified_from = Some(dt);
+        self
+    }
+
+    pub fn modified_before(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.modified_to = Some(dt);
+        self
+    }
+
+    pub fn changed_since(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.changed_since = Some(dt);
+        self
+    }
+
+    /// The saved snapshots of the entries must contain all of the terms in `terms`
+    pub fn content(mut self, terms: impl Into<String>) -> Self {
+        self.opts.content = Some(terms.into());
+        self
+    }
+
+    /// The entries must take at most `minutes` to read
+    pub fn max_length(mut self, minutes: i64) -> Self {
+        self.opts.max_length = Some(minutes);
+        self
+    }
+
+    /// Only return the snoozed entries if `snoozed`, otherwise exclude them
+    pub fn snoozed(mut self, snoozed: bool) -> Self {
+        self.opts.snoozed = Some(snoozed);
+        self
+    }
+
+    /// Only return the archived entries if `archived`, otherwise exclude them
+    pub fn archived(mut self, archived: bool) -> Self {
+        self.opts.archived = Some(archived);
+        self
+    }
+
+    /// Only return the read entries if `read`, otherwise only the unread ones
+    pub fn read(mut self, read: bool) -> Self {
+        self.opts.read = Some(read);
+        self
+    }
+
+    pub fn read_after(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.read_after = Some(dt);
+        self
+    }
+
+    pub fn read_before(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.read_before = Some(dt);
+        self
+    }
+
+    /// The entries must be due before `dt`
+    pub fn due_before(mut self, dt: DateTimeUtc) -> Self {
+        self.opts.due_before = Some(dt_to_string(&dt));
+        self
+    }
+
+    /// Only return the entries whose due date has passed
+    pub fn overdue(mut self) -> Self {
+        self.opts.overdue = true;
+        self
+    }
+
+    /// The entries must have the metadata `key` and, if `value` is given, it must have that exact value
+    pub fn meta(mut self, key: impl Into<String>, value: Option<String>) -> Self {
+        self.opts.meta.push((key.into(), value));
+        self
+    }
+
+    pub fn sort(mut self, order_by: OrderBy) -> Self {
+        self.opts.sort_by = Some(order_by);
+        self
+    }
+
+    /// Sort the entries in descending order
+    pub fn desc(mut self) -> Self {
+        self.opts.desc = true;
+        self
+    }
+
+    /// Runs the query, returning the matching entries
+    pub fn run(self) -> Result<Vec<Entry>> {
+        self.rlist.query_with(&self.opts)
+    }
+
+    /// Runs the query, returning an iterator that reads the matching entries one at a time
+    pub fn iter(self) -> Result<QueryIter<'a>> {
+        self.rlist.query_iter(&self.opts)
+    }
+}
+
+/// How many entries [`QueryIter`] reads from the database at a time
+const QUERY_ITER_BATCH: usize = 200;
+
+/// An iterator over the entries matching a query, returned by [`RList::query_iter`] and [`QueryBuilder::iter`]
+pub struct QueryIter<'a> {
+    conn: &'a Connection,
+    /// The ids of the matching entries that were not fetched yet, in order
+    entry_ids: std::vec::IntoIter<i64>,
+    /// The fetched entries that were not returned yet, in order
+    batch: std::vec::IntoIter<Entry>,
+}
+
+impl Iterator for QueryIter<'_> {
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(entry) = self.batch.next() {
+            return Some(Ok(entry));
         }
-        if author.is_some() {
-            updates.push("author = :author");
-            bindings.push((":author", author.as_deref().unwrap()));
+        let entry_ids: Vec<i64> = self.entry_ids.by_ref().take(QUERY_ITER_BATCH).collect();
+        if entry_ids.is_empty() {
+            return None;
         }
-        if url.is_some() {
-            updates.push("url = :url");
-            bindings.push((":url", url.as_deref().unwrap()));
+        match DBEntry::get_complete_by_ids(self.conn, &entry_ids) {
+            Ok(entries) => {
+                self.batch = entries.into_iter();
+                self.batch.next().map(Ok)
+            }
+            Err(e) => Some(Err(e)),
         }
+    }
 
-        // If there are no updates on the entry to be made, then just get the entry and its id.
-        let (entry_id, mut entry) = if updates.len() == 0 {
-            DBEntry::get_by_name_without_topics(&self.conn, old_name)?
-        } else {
-            // else perform the updates and construct a new Entry with the resulting data
-            let q = format!(
-                "UPDATE rlist
-                SET {u}
-                WHERE name = :old_name
-                RETURNING *;",
-                u = updates.join(", ")
-            );
-            let mut stmt = self.conn.prepare(q)?;
-            stmt.bind_iter(bindings)?;
-            if let sqlite::State::Done = stmt.next()? {
-                return Err(anyhow::anyhow!(
-                    "Could not find any entry in your reading list with name {}",
-                    old_name.as_str().bold().truecolor(255, 165, 0)
-                ));
-            }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.entry_ids.len() + self.batch.len()))
+    }
+}
 
-            read_sql_response!(stmt, entry_id => i64, name => String, url => String, added => String, author => String);
-            let author = opt_from_sql(author);
+/// The optional data of an entry created by [`RList::add`]
+#[derive(Default, Clone)]
+pub struct AddOptions {
+    pub author: Option<String>,
+    pub topics: Vec<String>,
+    pub meta: Vec<(String, String)>,
+    /// The estimated reading time, in minutes
+    pub length: Option<i64>,
+    /// When the entry should be read by, formatted like SQLITE_DATETIME_FORMAT
+    pub due: Option<String>,
+}
 
-            (
-                entry_id,
-                Entry::new(name, url, author, Vec::new(), Some(added)),
-            )
-        };
+/// The changes applied to an entry by [`RList::edit`]. Fields left to their default value are not changed
+#[derive(Default, Clone)]
+pub struct EditOptions {
+    pub new_name: Option<String>,
+    pub author: Option<String>,
+    /// Removes the author of the entry. Ignored if `author` is set
+    pub clear_author: bool,
+    pub url: Option<String>,
+    /// The new length of the entry, in minutes
+    pub length: Option<i64>,
+    /// Removes the length of the entry. Ignored if `length` is set
+    pub clear_length: bool,
+    /// Replaces all of the topics of the entry. Takes precedence over `add_topics`
+    pub topics: Option<Vec<String>>,
+    pub add_topics: Option<Vec<String>>,
+    pub clear_topics: bool,
+    pub remove_topics: Option<Vec<String>>,
+    /// The metadata to set on the entry. Keys that are already set are overwritten
+    pub meta: Vec<(String, String)>,
+    /// The keys of the metadata to remove from the entry
+    pub remove_meta: Vec<String>,
+    /// The new due date, formatted like SQLITE_DATETIME_FORMAT
+    pub due: Option<String>,
+    pub clear_due: bool,
+    /// Marks the entry as read (or as unread, if `false`). Read entries are also removed from the queue
+    pub read: Option<bool>,
+    /// Archives (or unarchives, if `false`) the entry. Archived entries are also removed from the queue
+    pub archived: Option<bool>,
+}
 
-        if clear_topics || topics.is_some() {
-            DBEntry::unlink_all_topics(&self.conn, entry_id)?;
-        }
+impl EditOptions {
+    /// Returns whether no change at all is requested
+    pub fn is_empty(&self) -> bool {
+        self.new_name.is_none()
+            && self.author.is_none()
+            && !self.clear_author
+            && self.url.is_none()
+            && self.length.is_none()
+            && !self.clear_length
+            && self.due.is_none()
+            && !self.clear_due
+            && self.topics.is_none()
+            && self.add_topics.is_none()
+            && !self.clear_topics
+            && self.remove_topics.is_none()
+            && self.meta.is_empty()
+            && self.remove_meta.is_empty()
+            && self.read.is_none()
+            && self.archived.is_none()
+    }
+}
 
-        // --topics has precedence over --add-topics, and if the first is set, then the second won't do anything
-        // --topics removes all topics associated with the entry and creates the new ones, whilst --add-topics just appends some topics
-        let topics_to_add = if topics.is_some() { topics } else { add_topics };
+/// A topic rename planned by [`RList::plan_topics_rename`]
+#[derive(Debug, Clone)]
+pub struct TopicRename {
+    pub topic_id: i64,
+    pub from: String,
+    pub to: String,
+    /// The number of entries related to the topic being renamed
+    pub entries: i64,
+    /// Whether the topic will be merged into another one (either an existing topic or another renamed one)
+    pub merge: bool,
+}
 
-        if topics_to_add.is_some() {
-            let t = topics_to_add.unwrap();
-            let topic_ids = DBTopic::create_many(&self.conn, &t)?;
-            DBEntry::associate_with_topics(&self.conn, entry_id, topic_ids)?;
-        }
+/// An entry rename planned by [`RList::plan_normalize`]
+#[derive(Debug, Clone)]
+pub struct EntryRename {
+    pub entry_id: i64,
+    pub from: String,
+    pub to: String,
+}
+
+/// The changes planned by [`RList::plan_normalize`] to normalize the names of the entries and of the topics
+#[derive(Debug, Clone, Default)]
+pub struct Normalization {
+    /// The entries to rename. An entry whose normalized name is already taken by another entry gets a numbered name, e.g. `name (2)`
+    pub entries: Vec<EntryRename>,
+    pub topics: Vec<TopicRename>,
+}
+
+impl Normalization {
+    /// Returns whether all of the names are already normalized
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.topics.is_empty()
+    }
+}
 
-        if remove_topics.is_some() {
-            DBEntry::unlink_topics_by_name(&self.conn, entry_id, remove_topics.unwrap())?;
+/// What [`RList::import`] does with an imported entry whose name or url already belongs to an entry of the reading list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnConflict {
+    /// Keep the existing entry as it is and skip the imported one
+    #[default]
+    Skip,
+    /// Replace the existing entry with the imported one
+    Overwrite,
+    /// Add the topics and the metadata of the imported entry to the existing one, and fill in its author and length if it has none
+    Merge,
+    /// Import the entry with a numbered name, such as `name (2)`. Since urls are unique, entries with the same url are still skipped
+    Rename,
+}
+
+impl FromStr for OnConflict {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(Self::Skip),
+            "overwrite" => Ok(Self::Overwrite),
+            "merge" => Ok(Self::Merge),
+            "rename" => Ok(Self::Rename),
+            other => Err(anyhow::anyhow!("Option \"{other}\" not recognized")),
         }
+    }
+}
 
-        entry.topics = DBTopic::get_related_to(&self.conn, entry_id)?
-            .into_iter()
-            .map(|(_i, e)| e)
-            .collect();
+impl Display for OnConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            OnConflict::Skip => "skip",
+            OnConflict::Overwrite => "overwrite",
+            OnConflict::Merge => "merge",
+            OnConflict::Rename => "rename",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    pub on_conflict: OnConflict,
+    /// Adds the topics of the imported entries to the existing entries they conflict with, even if they are skipped,
+    /// and keeps the topics of the entries that are overwritten
+    pub merge_topics: bool,
+    /// Only computes what would be done with each entry, without changing the reading list
+    pub dry_run: bool,
+}
+
+/// What [`RList::import`] did with an imported entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImportOutcome {
+    Created,
+    /// The entry with this name was replaced by the imported one
+    Overwritten(String),
+    /// The imported entry was merged into the entry with this name
+    Merged(String),
+    /// The entry was created with this name, since its own name was taken
+    Renamed(String),
+    /// The entry was not imported, for this reason
+    Skipped(String),
+    /// The entry was not imported, but its topics were added to the entry with this name (see [`ImportOptions::merge_topics`])
+    TopicsMerged(String),
+}
+
+/// An entry passed to [`RList::import`], along with what was done with it
+#[derive(Debug, Clone)]
+pub struct ImportResult {
+    pub entry: Entry,
+    pub outcome: ImportOutcome,
+}
+/// The problems found in the database by [`RList::check`]
+#[derive(Debug, Default)]
+pub struct DbCheck {
+    /// The problems reported by the integrity check of SQLite, which mean that the database file is corrupted
+    pub integrity_errors: Vec<String>,
+    /// For each table, how many of its rows reference a row that does not exist
+    pub foreign_key_violations: Vec<(String, i64)>,
+    /// How many rows of each kind belong to a row that does not exist, without referencing it with a foreign key
+    pub orphans: Vec<(String, i64)>,
+}
+
+impl DbCheck {
+    /// Returns whether no problem at all was found
+    pub fn is_ok(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.foreign_key_violations.is_empty()
+            && self.orphans.is_empty()
+    }
 
-        Ok(entry)
+    /// Returns whether the problems found can be fixed by [`RList::repair`]: the corruption of the file cannot
+    pub fn is_repairable(&self) -> bool {
+        self.integrity_errors.is_empty()
     }
+}
+
+/// Summary metrics about the reading list, as returned by [`RList::stats`]
+#[derive(Debug)]
+pub struct Stats {
+    pub total: i64,
+    /// The number of entries that were marked as read. The other `total - read` entries are unread
+    pub read: i64,
+    /// The number of entries added in each month (`YYYY-MM`), in chronological order
+    pub added_per_month: Vec<(String, i64)>,
+    /// The topics with the most entries, along with their entry count
+    pub top_topics: Vec<(String, i64)>,
+    /// The authors with the most entries, along with their entry count
+    pub top_authors: Vec<(String, i64)>,
+}
+
+/// Statistics about a single topic, as returned by [`RList::topic_stats`]
+#[derive(Debug)]
+pub struct TopicStats {
+    pub topic: String,
+    pub entries: i64,
+    /// The name of the oldest entry related to the topic that was not read yet, if any
+    pub oldest: Option<String>,
+    /// The average age of the entries related to the topic, in days
+    pub average_age: f64,
+}
+
+#[derive(Debug, Clone)]
+pub enum AuthorOrder {
+    Name,
+    Count,
+}
+
+impl FromStr for AuthorOrder {
+    type Err = anyhow::Error;
 
-    pub fn remove_by_topics(&self, topics: Vec<String>) -> Result<Vec<Entry>> {
-        let mut res = Vec::new();
-        for topic in topics {
-            let old_entries = self.remove_by_topic(topic)?;
-            res.extend(old_entries);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "count" => Ok(Self::Count),
+            other => Err(anyhow::anyhow!("Option \"{other}\" not recognized")),
         }
-        Ok(res)
     }
+}
 
-    /// Removes all of the entries that are in `topic` and returns them
-    pub fn remove_by_topic(&self, topic: String) -> Result<Vec<Entry>> {
-        let topic_id = DBTopic::get_id_from_name(&self.conn, topic.as_str())?;
-
-        let entries = self.query(
-            None,
-            Some(vec![topic]),
-            None,
-            None,
-            None,
-            false,
-            None,
-            None,
-            false,
-        )?;
+/// A change of a single field of an entry, as returned by [`RList::history`]
+#[derive(Debug)]
+pub struct HistoryRecord {
+    /// When the change was made, formatted like SQLITE_DATETIME_FORMAT
+    pub at: String,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
 
-        DBEntry::remove_related_to(&self.conn, topic_id)?;
+/// The kinds of operations recorded in the operations log, which can be undone and redone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Add,
+    Edit,
+    /// A removal that moved the entries to the trash
+    Trash,
+    /// A permanent removal
+    Remove,
+    /// A merge of some entries into another one, which moved them to the trash
+    Merge,
+}
 
-        Ok(entries)
+impl OperationKind {
+    /// The name under which the kind is stored in the db
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Edit => "edit",
+            Self::Trash => "trash",
+            Self::Remove => "remove",
+            Self::Merge => "merge",
+        }
     }
 
-    pub(crate) fn dump_all(&self) -> Result<Vec<Entry>> {
-        DBEntry::get_all_complete(&self.conn)
+    /// Whether the entries removed by the operation go through the trash
+    fn uses_trash(&self) -> bool {
+        matches!(self, Self::Trash | Self::Merge)
     }
+}
 
-    /// Creates all of the entries provided.
-    pub(crate) fn import(&self, entries: Vec<Entry>) -> Result<u64> {
-        let mut c = 0;
-        for e in entries {
-            match DBEntry::create(
-                &self.conn,
-                e.name.as_str(),
-                e.url.as_str(),
-                e.author.as_deref(),
-                Some(e.added).as_deref()
-            ) {
-                Ok((entry_id, _entry)) => {
-                    if let Ok(topic_ids) = DBTopic::create_many(&self.conn, &e.topics) {
-                        if DBEntry::associate_with_topics(&self.conn, entry_id, topic_ids).is_ok() {
-                            c += 1;
-                        }
-                    }
-                }
-                Err(err) => {
-                    eprintln!("{}: {err}", "Warning".bold().yellow())
-                }
-            }
+impl FromStr for OperationKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "add" => Ok(Self::Add),
+            "edit" => Ok(Self::Edit),
+            "trash" => Ok(Self::Trash),
+            "remove" => Ok(Self::Remove),
+            "merge" => Ok(Self::Merge),
+            other => Err(anyhow::anyhow!("Unknown operation \"{other}\" in the log")),
         }
-        Ok(c)
     }
 }
+
+impl Display for OperationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Add => write!(f, "addition"),
+            Self::Edit => write!(f, "edit"),
+            Self::Trash | Self::Remove => write!(f, "removal"),
+            Self::Merge => write!(f, "merge"),
+        }
+    }
+}
+
+pub struct RList {
+    conn: Connection,
+    /// How many transactions are currently open. Nested transactions are implemented with savepoints
+    depth: Cell<u32>,
+    storage: Box<dyn Storage>,
+    /// Whether the reading list was loaded from `storage`, and must be saved to it after every change
+    sync: bool,
+    /// Where to save a backup of the reading list before it is changed, if automatic backups are enabled
+    backups: Option<BackupPolicy>,
+    /// Whether the automatic backup was already saved, since it is only saved before the first change
+    backed_up: Cell<bool>,
+}
+
+impl RList {
+    /// Creates the db file, initializes the tables and establishes a connection to the sqlite db
+    /// Forwards the errors raised by the called functions, such as std::fs and sqlite ones.
+    pub fn init(db_file: impl AsRef<Path>) -> Result<Self> {
+        Self::open(Box::new(SqliteStorage::new(db_file)))
+    }
+
+    /// Opens the reading list kept in `storage`, creating it if it does not exist yet
+    pub fn open(storage: Box<dyn Storage>) -> Result<Self> {
+        let conn = storage.connect()?;
+        let created = !table_exists(&conn, "rlist")?;
+
+        let q = format!(
+            "
+        PRAGMA foreign_keys = ON;
+        CREATE TABLE IF NOT EXISTS authors (
+            author_id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE,
+            url TEXT,
+            notes TEXT
+        );
+        CREATE TABLE IF NOT EXISTS rlist ({RLIST_COLUMNS});
+        CREATE TABLE IF NOT EXISTS topics (
+            topic_id INTEGER PRIMARY KEY,
+            name TEXT NON NULL UNIQUE,
+            parent_id INTEGER REFERENCES topics (topic_id) ON UPDATE CASCADE ON DELETE SET NULL
+        );
+        CREATE TABLE IF NOT EXISTS rlist_has_topic (
+            entry_id INTEGER,
+            topic_id INTEGER,
+            PRIMARY KEY (entry_id, topic_id),
+            FOREIGN KEY (entry_id) REFERENCES rlist (entry_id) ON UPDATE CASCADE ON DELETE CASCADE,
+            FOREIGN KEY (topic_id) REFERENCES topics (topic_id) ON UPDATE CASCADE ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS trash (
+            trash_id INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            entry TEXT NOT NULL,
+            removed DATETIME NOT NULL DEFAULT (datetime('now', 'localtime'))
+        );
+        CREATE TABLE IF NOT EXISTS queue (
+            entry_id INTEGER PRIMARY KEY,
+            position INTEGER NOT NULL,
+            FOREIGN KEY (entry_id) REFERENCES rlist (entry_id) ON UPDATE CASCADE ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS entry_meta (
+            entry_id INTEGER,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (entry_id, key),
+            FOREIGN KEY (entry_id) REFERENCES rlist (entry_id) ON UPDATE CASCADE ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS operations (
+            op_id INTEGER PRIMARY KEY,
+            kind TEXT NOT NULL,
+            at DATETIME NOT NULL DEFAULT (datetime('now', 'localtime')),
+            undone BOOLEAN NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS operation_changes (
+            op_id INTEGER,
+            seq INTEGER,
+            before TEXT,
+            after TEXT,
+            PRIMARY KEY (op_id, seq),
+            FOREIGN KEY (op_id) REFERENCES operations (op_id) ON UPDATE CASCADE ON DELETE CASCADE
+        );
+        CREATE TABLE IF NOT EXISTS history (
+            history_id INTEGER PRIMARY KEY,
+            entry_id INTEGER NOT NULL,
+            field TEXT NOT NULL,
+            old_value TEXT,
+            new_value TEXT,
+            at DATETIME NOT NULL DEFAULT (datetime('now', 'localtime')),
+            FOREIGN KEY (entry_id) REFERENCES rlist (entry_id) ON UPDATE CASCADE ON DELETE CASCADE
+        );"
+        );
+        conn.execute_batch(&q)?;
+
+        if created {
+            // Not created along with the other tables, since the databases that do not have it yet
+            // must also index the snapshots they already have (see the first migration)
+            conn.execute_batch("CREATE VIRTUAL TABLE snapshots_fts USING fts5 (text);")?;
+        }
+        migrations::migrate(&conn, created)?;
+        // Not a migration, since it cannot be applied until the user resolves the clashes it warns about
+        DBEntry::make_name_and_url_case_insensitive(&conn)?;
+
+        // The trigger and the indices on `rlist` are dropped along with the table when it is rebuilt by a migration,
+        // so they are created once the migrations are done.
+        // `rlist.name` and `rlist.url` are already indexed, by their UNIQUE constraints
+        conn.execute_batch(
+            "CREATE TRIGGER IF NOT EXISTS remove_snapshot_text AFTER DELETE ON rlist BEGIN
+                DELETE FROM snapshots_fts WHERE rowid = old.entry_id;
+            END;
+            CREATE INDEX IF NOT EXISTS rlist_added ON rlist (added);
+            CREATE INDEX IF NOT EXISTS rlist_has_topic_topic_id ON rlist_has_topic (topic_id);",
+        )?;
+
+        let mut rlist = Self {
+            conn,
+            depth: Cell::new(0),
+            storage,
+            sync: false,
+            backups: None,
+            backed_up: Cell::new(false),
+        };
+        if let Some(entries) = rlist.storage.load()? {
+            rlist.in_transaction(|| {
+                for e in entries.iter() {
+                    rlist.create_complete(e)?;
+                }
+                Ok(())
+            })?;
+            rlist.sync = true;
+        }
+        Ok(rlist)
+    }
+
+    /// Returns whether the trash, the queue, the history of the entries and the operations that can be undone are kept
+    /// after the reading list is closed. They are not if its storage only saves the entries, such as a file or a remote database
+    pub fn keeps_trash(&self) -> bool {
+        !self.sync
+    }
+
+    /// Adds the entry to the database. Returns the new entry
+    pub fn add(&self, name: String, url: String, opts: AddOptions) -> Result<Entry> {
+        let AddOptions {
+            author,
+            topics,
+            meta,
+            length,
+            due,
+        } = opts;
+        self.in_transaction(|| {
+            let (entry_id, _entry) = DBEntry::create(
+                &self.conn,
+                name.as_str(),
+                url.as_str(),
+                author.as_deref(),
+                length,
+                None,
+                None,
+            )?;
+
+            if !topics.is_empty() {
+                let topic_ids = DBTopic::create_many(&self.conn, &topics)?;
+                DBEntry::associate_with_topics(&self.conn, entry_id, topic_ids)?;
+            }
+            DBMeta::set_many(&self.conn, entry_id, &meta)?;
+            if let Some(due) = due.as_deref() {
+                DBEntry::set_due(&self.conn, entry_id, Some(due))?;
+            }
+
+            let entry = self.get_by_id(entry_id)?;
+            self.record(OperationKind::Add, &[(None, Some(&entry))])?;
+            Ok(entry)
+        })
+    }
+
+    /// Adds the entry or, if an entry with the same name or url already exists, updates it:
+    /// the fields set in `opts` (and the name or the url, if they differ) are overwritten, and the topics are appended to its topics.
+    /// Returns the resulting entry, along with whether it was created
+    pub fn add_or_update(
+        &self,
+        name: String,
+        url: String,
+        opts: AddOptions,
+    ) -> Result<(Entry, bool)> {
+        self.in_transaction(|| {
+            let by_name = DBEntry::get_id_from_name(&self.conn, &name)?;
+            let by_url = DBEntry::get_id_from_url(&self.conn, &url)?;
+            let entry_id = match (by_name, by_url) {
+                (None, None) => return Ok((self.add(name, url, opts)?, true)),
+                (Some(a), Some(b)) if a != b => {
+                    return Err(anyhow::anyhow!(
+                        "Could not update the entry: the name {} and the url {} belong to different entries",
+                        name.entry_name(),
+                        url.link()
+                    ))
+                }
+                (Some(entry_id), _) | (None, Some(entry_id)) => entry_id,
+            };
+
+            let existing = self.get_by_id(entry_id)?;
+            let AddOptions {
+                author,
+                topics,
+                meta,
+                length,
+                due,
+            } = opts;
+            let edit = EditOptions {
+                new_name: by_name.is_none().then_some(name),
+                url: by_url.is_none().then_some(url),
+                author: author.filter(|a| existing.author.as_ref() != Some(a)),
+                length: length.filter(|l| existing.length != Some(*l)),
+                add_topics: Some(topics)
+                    .filter(|t| t.iter().any(|t| !existing.topics.contains(&Topic::normalize(t)))),
+                meta: meta
+                    .into_iter()
+                    .filter(|(k, v)| existing.meta.get(k) != Some(v))
+                    .collect(),
+                due: due.filter(|d| existing.due.as_ref() != Some(d)),
+                ..Default::default()
+            };
+            if edit.is_empty() {
+                return Ok((existing, false));
+            }
+            Ok((self.edit(existing.name, edit)?, false))
+        })
+    }
+
+    /// Removes the entry by name, moving it to the trash unless `permanent` is set. Returns Ok(the old entry if it existed)
+    pub fn remove_by_name(&self, name: String, permanent: bool) -> Result<Entry> {
+        let mut entries = self.remove_many(&[name], permanent)?;
+        Ok(entries.remove(0))
+    }
+
+    /// Removes all of the entries with the given names as a single operation (so that undoing it restores all of them),
+    /// moving them to the trash unless `permanent` is set. Fails without removing anything if any of the entries does not exist.
+    /// Returns the removed entries
+    pub fn remove_many(&self, names: &[String], permanent: bool) -> Result<Vec<Entry>> {
+        let entries = self.in_transaction(|| {
+            let mut entries = Vec::with_capacity(names.len());
+            for name in names {
+                let entry = DBEntry::remove_by_name(&self.conn, name.as_str())?;
+                if !permanent {
+                    DBTrash::add(&self.conn, &entry)?;
+                }
+                entries.push(entry);
+            }
+            let changes = entries.iter().map(|e| (Some(e), None)).collect::<Vec<_>>();
+            self.record(removal_kind(permanent), &changes)?;
+            Ok(entries)
+        })?;
+        if permanent {
+            remove_snapshots(&entries);
+        }
+        Ok(entries)
+    }
+
+    /// Returns a [`QueryBuilder`] to select the entries of the reading list. Without any filter, it returns all of the entries
+    pub fn query(&self) -> QueryBuilder<'_> {
+        QueryBuilder {
+            rlist: self,
+            opts: QueryOptions::default(),
+        }
+    }
+
+    /// Returns the list of entries that match the filters in `opts`, sorted as specified by `opts.sort_by` and `opts.desc`
+    pub fn query_with(&self, opts: &QueryOptions) -> Result<Vec<Entry>> {
+        let (where_clause, bindings) = opts.where_clause();
+        DBEntry::get_matching_complete(&self.conn, &where_clause, &opts.order_clause(), bindings)
+    }
+
+    /// Returns an iterator over the entries that match the filters in `opts`, sorted as specified by `opts.sort_by` and `opts.desc`.
+    /// Only the ids of the matching entries are selected upfront: the entries themselves are read from the database
+    /// in batches, as the iterator is advanced
+    pub fn query_iter(&self, opts: &QueryOptions) -> Result<QueryIter<'_>> {
+        let (where_clause, bindings) = opts.where_clause();
+
+        let q = format!(
+            "
+            SELECT ls.entry_id AS entry_id
+            FROM rlist AS ls 
+            LEFT OUTER JOIN authors AS a
+                ON a.author_id = ls.author_id
+            {where_clause}
+            {};",
+            opts.order_clause()
+        );
+
+        let mut stmt = self.conn.prepare(&q)?;
+        let entry_ids = stmt
+            .query_map(params_from_iter(bindings), |row| row.get("entry_id"))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+
+        Ok(QueryIter {
+            conn: &self.conn,
+            entry_ids: entry_ids.into_iter(),
+            batch: Vec::new().into_iter(),
+        })
+    }
+
+    /// Returns the number of entries that match the filters in `opts`, without fetching them
+    pub fn count(&self, opts: &QueryOptions) -> Result<i64> {
+        let (where_clause, bindings) = opts.where_clause();
+
+        let q = format!("SELECT COUNT(*) AS count FROM rlist AS ls {where_clause};");
+        Ok(self
+            .conn
+            .query_row(&q, params_from_iter(bindings), |row| row.get("count"))?)
+    }
+
+    /// Applies the changes in `opts` to the entry with name = `old_name`. Returns the edited entry
+    pub fn edit(&self, old_name: String, opts: EditOptions) -> Result<Entry> {
+        // If no edit is set, then return an error
+        if opts.is_empty() {
+            return Err(anyhow::anyhow!("No edit options were given"));
+        }
+        self.in_transaction(|| {
+            let entry_id = self.get_existing_id(old_name.as_str())?;
+            let before = self.get_by_id(entry_id)?;
+            self.apply_edit(old_name, opts)?;
+            self.record_history(entry_id, &before)?;
+            let after = self.get_by_id(entry_id)?;
+            self.record(OperationKind::Edit, &[(Some(&before), Some(&after))])?;
+            Ok(after)
+        })
+    }
+
+    /// Applies the changes in `opts` to all of the entries with the given names, as a single operation
+    /// (so that undoing it reverts all of them). Returns the edited entries.
+    /// `opts.new_name` is not allowed, since the entries cannot all get the same name
+    pub fn edit_many(&self, names: &[String], opts: EditOptions) -> Result<Vec<Entry>> {
+        if opts.is_empty() {
+            return Err(anyhow::anyhow!("No edit options were given"));
+        }
+        if opts.new_name.is_some() && names.len() > 1 {
+            return Err(anyhow::anyhow!(
+                "Several entries cannot be given the same name"
+            ));
+        }
+        self.in_transaction(|| {
+            let mut changes = Vec::with_capacity(names.len());
+            for name in names {
+                let entry_id = self.get_existing_id(name.as_str())?;
+                let before = self.get_by_id(entry_id)?;
+                self.apply_edit(name.clone(), opts.clone())?;
+                self.record_history(entry_id, &before)?;
+                changes.push((before, self.get_by_id(entry_id)?));
+            }
+            self.record(
+                OperationKind::Edit,
+                &changes
+                    .iter()
+                    .map(|(before, after)| (Some(before), Some(after)))
+                    .collect::<Vec<_>>(),
+            )?;
+            Ok(changes.into_iter().map(|(_before, after)| after).collect())
+        })
+    }
+
+    /// Applies the changes in `opts` to the entry with name = `old_name`, without recording them. Returns the id of the edited entry
+    fn apply_edit(&self, old_name: String, opts: EditOptions) -> Result<i64> {
+        let EditOptions {
+            new_name,
+            author,
+            clear_author,
+            url,
+            length,
+            clear_length,
+            topics,
+            add_topics,
+            clear_topics,
+            remove_topics,
+            meta,
+            remove_meta,
+            due,
+            clear_due,
+            read,
+            archived,
+        } = opts;
+
+        let mut updates = Vec::new();
+        let mut bindings: Vec<(_, Value)> = vec![(":old_name", normalize_name(&old_name).into())];
+        if let Some(new_name) = new_name.as_deref() {
+            updates.push("name = :new_name");
+            bindings.push((":new_name", normalize_name(new_name).into()));
+        }
+        if let Some(author) = author.as_deref() {
+            updates.push("author_id = :author_id");
+            bindings.push((
+                ":author_id",
+                DBAuthor::get_or_create(&self.conn, author)?.into(),
+            ));
+        } else if clear_author {
+            updates.push("author_id = NULL");
+        }
+        if let Some(url) = url.as_deref() {
+            updates.push("url = :url");
+            bindings.push((":url", url.to_string().into()));
+            updates.push("domain = :domain");
+            bindings.push((":domain", domain_of(url).unwrap_or_default().into()));
+        }
+        if let Some(length) = length {
+            updates.push("length = :length");
+            bindings.push((":length", length.into()));
+        } else if clear_length {
+            updates.push("length = NULL");
+        }
+        if let Some(due) = due.as_deref() {
+            updates.push("due = :due");
+            bindings.push((":due", due.to_string().into()));
+        } else if clear_due {
+            updates.push("due = NULL");
+        }
+        if let Some(read) = read {
+            // Entries that were already read keep their original date
+            updates.push(
+                "read_at = CASE WHEN :read THEN COALESCE(read_at, datetime('now', 'localtime')) ELSE NULL END",
+            );
+            bindings.push((":read", read.into()));
+        }
+        if let Some(archived) = archived {
+            updates.push("archived = :archived");
+            bindings.push((":archived", archived.into()));
+        }
+
+        // If there are no updates on the entry to be made, then just get its id.
+        let entry_id = if updates.is_empty() {
+            self.get_existing_id(old_name.as_str())?
+        } else {
+            // else perform the updates and construct a new Entry with the resulting data
+            let q = format!(
+                "UPDATE rlist
+                SET {u}
+                WHERE name = :old_name
+                RETURNING entry_id;",
+                u = updates.join(", ")
+            );
+            let bindings = bindings
+                .iter()
+                .map(|(name, value)| (*name, value as &dyn rusqlite::ToSql))
+                .collect::<Vec<_>>();
+            let entry_id = self
+                .conn
+                .query_row(&q, bindings.as_slice(), |row| row.get("entry_id"))
+                .optional()?;
+
+            entry_id.ok_or_else(|| RListError::entry_not_found(&old_name))?
+        };
+
+        if clear_topics || topics.is_some() {
+            DBEntry::unlink_all_topics(&self.conn, entry_id)?;
+        }
+
+        // --topics has precedence over --add-topics, and if the first is set, then the second won't do anything
+        // --topics removes all topics associated with the entry and creates the new ones, whilst --add-topics just appends some topics
+        let topics_to_add = if topics.is_some() { topics } else { add_topics };
+
+        if let Some(t) = topics_to_add {
+            let topic_ids = DBTopic::create_many(&self.conn, &t)?;
+            DBEntry::associate_with_topics(&self.conn, entry_id, topic_ids)?;
+        }
+
+        if let Some(remove_topics) = remove_topics {
+            DBEntry::unlink_topics_by_name(&self.conn, entry_id, remove_topics)?;
+        }
+
+        DBMeta::remove_many(&self.conn, entry_id, &remove_meta)?;
+        DBMeta::set_many(&self.conn, entry_id, &meta)?;
+
+        if read == Some(true) || archived == Some(true) {
+            self.dequeue(entry_id)?;
+        }
+
+        Ok(entry_id)
+    }
+
+    /// Returns the entry that should be read next, that is the first entry of the queue or, if there is none,
+    /// the oldest entry. Only the unread entries that are neither snoozed nor archived are considered.
+    /// Returns `None` if there is no such entry
+    pub fn next(&self) -> Result<Option<Entry>> {
+        let (where_clause, bindings) = QueryOptions {
+            snoozed: Some(false),
+            archived: Some(false),
+            read: Some(false),
+            ..Default::default()
+        }
+        .where_clause();
+
+        let queued = format!(
+            "SELECT ls.entry_id AS entry_id FROM queue AS q JOIN rlist AS ls ON ls.entry_id = q.entry_id {where_clause} ORDER BY q.position ASC LIMIT 1;"
+        );
+        let oldest = format!(
+            "SELECT ls.entry_id AS entry_id FROM rlist AS ls {where_clause} ORDER BY ls.added ASC, ls.entry_id ASC LIMIT 1;"
+        );
+        for q in [queued, oldest] {
+            let entry_id = self
+                .conn
+                .query_row(&q, params_from_iter(bindings.iter()), |row| {
+                    row.get("entry_id")
+                })
+                .optional()?;
+            if let Some(entry_id) = entry_id {
+                return self.get_by_id(entry_id).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Appends the entry with name = `name` to the queue. Returns its position in the queue (starting from 1)
+    pub fn queue_add(&self, name: impl AsRef<str>) -> Result<usize> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        self.in_transaction(|| {
+            let mut queue = DBQueue::get_all(&self.conn)?;
+            if queue.contains(&entry_id) {
+                return Err(RListError::new(
+                    ErrorCode::AlreadyExists,
+                    Entity::new("entry", "name", name.as_ref()),
+                    format!(
+                        "Entry {} is already in the queue",
+                        name.as_ref().entry_name()
+                    ),
+                )
+                .into());
+            }
+            queue.push(entry_id);
+            DBQueue::set_all(&self.conn, &queue)?;
+            Ok(queue.len())
+        })
+    }
+
+    /// Returns the entries in the queue, in order
+    pub fn queue_list(&self) -> Result<Vec<Entry>> {
+        DBQueue::get_all(&self.conn)?
+            .into_iter()
+            .map(|entry_id| self.get_by_id(entry_id))
+            .collect()
+    }
+
+    /// Moves the entry with name = `name` to position `position` (starting from 1) in the queue.
+    /// Positions past the end of the queue move the entry to the end of the queue
+    pub fn queue_move(&self, name: impl AsRef<str>, position: usize) -> Result<()> {
+        if position == 0 {
+            return Err(anyhow::anyhow!("Queue positions start from 1"));
+        }
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        self.in_transaction(|| {
+            let mut queue = DBQueue::get_all(&self.conn)?;
+            let current = queue.iter().position(|id| *id == entry_id).ok_or_else(|| {
+                anyhow::anyhow!("Entry {} is not in the queue", name.as_ref().entry_name())
+            })?;
+            queue.remove(current);
+            queue.insert((position - 1).min(queue.len()), entry_id);
+            DBQueue::set_all(&self.conn, &queue)
+        })
+    }
+
+    /// Removes the first entry of the queue and returns it. Returns `None` if the queue is empty
+    pub fn queue_pop(&self) -> Result<Option<Entry>> {
+        self.in_transaction(|| {
+            let mut queue = DBQueue::get_all(&self.conn)?;
+            if queue.is_empty() {
+                return Ok(None);
+            }
+            let entry_id = queue.remove(0);
+            DBQueue::set_all(&self.conn, &queue)?;
+            self.get_by_id(entry_id).map(Some)
+        })
+    }
+
+    /// Removes the entry with name = `name` from the queue. The entry is kept in the reading list
+    pub fn queue_remove(&self, name: impl AsRef<str>) -> Result<()> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        self.in_transaction(|| {
+            let mut queue = DBQueue::get_all(&self.conn)?;
+            let len = queue.len();
+            queue.retain(|id| *id != entry_id);
+            if queue.len() == len {
+                return Err(anyhow::anyhow!(
+                    "Entry {} is not in the queue",
+                    name.as_ref().entry_name()
+                ));
+            }
+            DBQueue::set_all(&self.conn, &queue)
+        })
+    }
+
+    /// Returns the id of the entry with name = `name`, or an error if there is no such entry
+    fn get_existing_id(&self, name: &str) -> Result<i64> {
+        Ok(DBEntry::get_id_from_name(&self.conn, name)?
+            .ok_or_else(|| RListError::entry_not_found(name))?)
+    }
+
+    /// Computes the summary metrics of the reading list. `top` is the maximum number of topics and authors returned
+    pub fn stats(&self, top: i64) -> Result<Stats> {
+        Ok(Stats {
+            total: DBStats::count_entries(&self.conn)?,
+            read: DBStats::count_read(&self.conn)?,
+            added_per_month: DBStats::added_per_month(&self.conn)?,
+            top_topics: DBStats::top_topics(&self.conn, top)?,
+            top_authors: DBStats::top_authors(&self.conn, top)?,
+        })
+    }
+
+    /// Returns the number of entries added (or read, if `read`) on each day of `year`. Days without entries are left out
+    pub fn activity_per_day(
+        &self,
+        year: i32,
+        read: bool,
+    ) -> Result<HashMap<chrono::NaiveDate, i64>> {
+        DBStats::per_day(&self.conn, year, read)?
+            .into_iter()
+            .map(|(day, count)| Ok((chrono::NaiveDate::parse_from_str(&day, "%Y-%m-%d")?, count)))
+            .collect()
+    }
+
+    /// Returns the dates on which the entries that were marked as read were read
+    pub fn read_dates(&self) -> Result<Vec<chrono::NaiveDate>> {
+        DBStats::read_dates(&self.conn)?
+            .iter()
+            .map(|d| Ok(sql_string_to_dt(d)?.date()))
+            .collect()
+    }
+
+    /// Returns the distinct authors of the entries along with the number of entries of each of them.
+    /// If `filter` is set, only the authors whose name contains it are returned
+    pub fn authors(&self, filter: Option<&str>, sort_by: AuthorOrder) -> Result<Vec<Author>> {
+        DBAuthor::get_all_with_counts(&self.conn, filter, matches!(sort_by, AuthorOrder::Count))
+    }
+
+    /// Renames the author `old` to `new`. If `new` already exists, the two authors are merged.
+    /// Returns the number of entries by the renamed author
+    pub fn rename_author(&self, old: impl AsRef<str>, new: impl AsRef<str>) -> Result<usize> {
+        self.in_transaction(|| DBAuthor::rename(&self.conn, old.as_ref(), new.as_ref()))
+    }
+
+    /// Sets the url and/or the notes of the author with name = `name`. `None` values are left untouched
+    pub fn set_author_details(
+        &self,
+        name: impl AsRef<str>,
+        url: Option<&str>,
+        notes: Option<&str>,
+    ) -> Result<()> {
+        self.in_transaction(|| DBAuthor::set_details(&self.conn, name.as_ref(), url, notes))
+    }
+
+    /// Fills in the author, the site name and the description of the entry with name = `name`, but only if they are not set yet.
+    /// Returns the updated entry and whether anything changed
+    pub fn fill_details(
+        &self,
+        name: impl AsRef<str>,
+        author: Option<&str>,
+        site_name: Option<&str>,
+        description: Option<&str>,
+    ) -> Result<(Entry, bool)> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        let old = self.get_by_id(entry_id)?;
+
+        self.in_transaction(|| {
+            self.track(&[entry_id], || {
+                // The author is only created if it is actually going to be used
+                let author_id = match author {
+                    Some(author) if old.author.is_none() => {
+                        Some(DBAuthor::get_or_create(&self.conn, author)?)
+                    }
+                    _ => None,
+                };
+                DBEntry::fill_details(&self.conn, entry_id, author_id, site_name, description)
+            })
+        })?;
+
+        let new = self.get_by_id(entry_id)?;
+        let changed = old.author != new.author
+            || old.site_name != new.site_name
+            || old.description != new.description;
+        Ok((new, changed))
+    }
+
+    /// Returns the names of the entries that are missing an author, a site name or a description, oldest first
+    pub fn names_missing_details(&self) -> Result<Vec<String>> {
+        DBEntry::names_missing_details(&self.conn)
+    }
+
+    /// Stores the url of the snapshot of the entry with name = `name`. Returns the updated entry
+    pub fn set_archive_url(&self, name: impl AsRef<str>, archive_url: &str) -> Result<Entry> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        self.in_transaction(|| {
+            self.track(&[entry_id], || {
+                DBEntry::set_archive_url(&self.conn, entry_id, archive_url)
+            })
+        })?;
+        self.get_by_id(entry_id)
+    }
+
+    /// Returns the names of all of the topics, sorted alphabetically
+    pub fn topic_names(&self) -> Result<Vec<String>> {
+        Ok(DBTopic::get_all_with_counts(&self.conn)?
+            .into_iter()
+            .map(|(_id, name, _entries)| name)
+            .collect())
+    }
+
+    /// Returns the names of the entries that have no snapshot yet, oldest first
+    pub fn names_not_archived(&self) -> Result<Vec<String>> {
+        DBEntry::names_not_archived(&self.conn)
+    }
+
+    /// Merges the entries named in `remove` into the entry with name = `keep`: `keep` gets all of their topics and metadata
+    /// (its own metadata wins on conflicts) and the oldest `added` date, and then they are moved to the trash.
+    /// Everything happens in a single transaction, recorded as a single operation. Returns the merged entry
+    pub fn merge(&self, keep: impl AsRef<str>, remove: &[impl AsRef<str>]) -> Result<Entry> {
+        let keep_id = self.get_existing_id(keep.as_ref())?;
+        self.in_transaction(|| {
+            let before = self.get_by_id(keep_id)?;
+            let removed = self.track(&[keep_id], || {
+                let mut removed = Vec::with_capacity(remove.len());
+                for name in remove.iter() {
+                    if self.get_existing_id(name.as_ref())? == keep_id {
+                        return Err(anyhow::anyhow!(
+                            "Cannot merge entry {} into itself",
+                            name.as_ref().entry_name()
+                        ));
+                    }
+                    let old = DBEntry::remove_by_name(&self.conn, name.as_ref())?;
+                    DBTrash::add(&self.conn, &old)?;
+                    if !old.topics.is_empty() {
+                        let topic_ids = DBTopic::create_many(&self.conn, &old.topics)?;
+                        DBEntry::associate_with_topics(&self.conn, keep_id, topic_ids)?;
+                    }
+                    let kept_meta = DBMeta::get_related_to(&self.conn, keep_id)?;
+                    let new_meta = old
+                        .meta
+                        .iter()
+                        .filter(|(key, _value)| !kept_meta.contains_key(*key))
+                        .collect::<Vec<_>>();
+                    DBMeta::set_many(&self.conn, keep_id, &new_meta)?;
+                    DBEntry::keep_oldest_added(&self.conn, keep_id, &old.added)?;
+                    removed.push(old);
+                }
+                Ok(removed)
+            })?;
+            let after = self.get_by_id(keep_id)?;
+            let mut changes = removed.iter().map(|e| (Some(e), None)).collect::<Vec<_>>();
+            changes.push((Some(&before), Some(&after)));
+            self.record(OperationKind::Merge, &changes)?;
+            Ok(after)
+        })
+    }
+
+    /// Archives (or unarchives, if `!archived`) the entry with name = `name`.
+    /// Archived entries are also removed from the queue. Returns the updated entry
+    pub fn set_archived(&self, name: impl AsRef<str>, archived: bool) -> Result<Entry> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        self.in_transaction(|| {
+            self.track(&[entry_id], || {
+                DBEntry::set_archived(&self.conn, entry_id, archived)
+            })?;
+            if archived {
+                self.dequeue(entry_id)?;
+            }
+            Ok(())
+        })?;
+        self.get_by_id(entry_id)
+    }
+
+    /// Marks the entry with name = `name` as read (or as unread, if `!read`).
+    /// Read entries are also removed from the queue. Returns the updated entry
+    pub fn set_read(&self, name: impl AsRef<str>, read: bool) -> Result<Entry> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        self.in_transaction(|| {
+            self.track(&[entry_id], || {
+                DBEntry::set_read(&self.conn, entry_id, read)
+            })?;
+            if read {
+                self.dequeue(entry_id)?;
+            }
+            Ok(())
+        })?;
+        self.get_by_id(entry_id)
+    }
+
+    /// Counts one more opening of the entry with name = `name`. Returns the updated entry
+    pub fn record_open(&self, name: impl AsRef<str>) -> Result<Entry> {
+        let entry_id = self.in_transaction(|| DBEntry::increment_opens(&self.conn, name))?;
+        self.get_by_id(entry_id)
+    }
+
+    /// Removes the entry with id = `entry_id` from the queue, if it is in it. Expects to be run inside of a transaction
+    fn dequeue(&self, entry_id: i64) -> Result<()> {
+        let mut queue = DBQueue::get_all(&self.conn)?;
+        queue.retain(|id| *id != entry_id);
+        DBQueue::set_all(&self.conn, &queue)
+    }
+
+    /// Writes `content` as the snapshot of the entry with name = `name`, in a file inside of `dir`,
+    /// replacing its previous snapshot. Returns the updated entry
+    pub fn save_snapshot(
+        &self,
+        name: impl AsRef<str>,
+        content: &str,
+        dir: impl AsRef<Path>,
+    ) -> Result<Entry> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        std::fs::create_dir_all(dir.as_ref())?;
+        // Snapshots are named after the id of the entry, so that they are not affected by renames
+        let path = dir.as_ref().join(format!("{entry_id}.txt"));
+        std::fs::write(&path, content)
+            .with_context(|| format!("Could not write the snapshot to {}", path.display()))?;
+
+        self.in_transaction(|| {
+            self.track(&[entry_id], || {
+                DBEntry::set_snapshot(&self.conn, entry_id, &path.to_string_lossy())
+            })?;
+            DBSnapshot::index(&self.conn, entry_id, content)
+        })?;
+        self.get_by_id(entry_id)
+    }
+
+    /// Returns the number of entries of each domain, sorted by number of entries. Entries whose url has no domain are ignored
+    pub fn domain_stats(&self) -> Result<Vec<(String, i64)>> {
+        DBStats::per_domain(&self.conn)
+    }
+
+    /// Computes the statistics of every topic that has at least one entry, sorted by number of entries
+    pub fn topic_stats(&self) -> Result<Vec<TopicStats>> {
+        DBStats::per_topic(&self.conn)
+    }
+
+    /// Returns whether the reading list contains an entry with name = `name`
+    pub fn contains(&self, name: impl AsRef<str>) -> Result<bool> {
+        Ok(DBEntry::get_id_from_name(&self.conn, name.as_ref())?.is_some())
+    }
+
+    /// Returns the entry with url = `url` along with its topics and metadata, if there is one
+    pub fn get_by_url(&self, url: impl AsRef<str>) -> Result<Option<Entry>> {
+        DBEntry::get_id_from_url(&self.conn, url.as_ref())?
+            .map(|entry_id| self.get_by_id(entry_id))
+            .transpose()
+    }
+
+    /// Returns the entry with name = `name` along with its topics and metadata
+    pub fn get_by_name(&self, name: impl AsRef<str>) -> Result<Entry> {
+        self.get_by_id(self.get_existing_id(name.as_ref())?)
+    }
+
+    /// Returns the entry with id = `entry_id` along with its topics and metadata
+    pub fn get_by_id(&self, entry_id: i64) -> Result<Entry> {
+        DBEntry::get_complete_by_id(&self.conn, entry_id)
+    }
+
+    /// Hides the entry with name = `name` from the default listings until `until` (formatted like SQLITE_DATETIME_FORMAT).
+    /// If `until` is `None`, the entry is woken up. Returns the updated entry
+    pub fn snooze(&self, name: impl AsRef<str>, until: Option<&str>) -> Result<Entry> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        self.in_transaction(|| {
+            self.track(&[entry_id], || {
+                DBEntry::set_snoozed_until(&self.conn, entry_id, until)
+            })
+        })?;
+        self.get_by_id(entry_id)
+    }
+
+    /// Removes all of the entries that are in all of `topics` (or in any of them, if `or` is set) or in their subtopics,
+    /// moving them to the trash unless `permanent` is set, and returns them.
+    /// The entries are selected like [`QueryBuilder::topics_all`] and [`QueryBuilder::topics_any`] do
+    pub fn remove_by_topics(
+        &self,
+        topics: Vec<String>,
+        or: bool,
+        permanent: bool,
+    ) -> Result<Vec<Entry>> {
+        self.in_transaction(|| {
+            let query = self.query();
+            let query = if or {
+                query.topics_any(topics)
+            } else {
+                query.topics_all(topics)
+            };
+            let names = query.run()?.into_iter().map(|e| e.name).collect::<Vec<_>>();
+            self.remove_many(&names, permanent)
+        })
+    }
+
+    /// Reverts the most recent operation (an add, an edit, a removal or a merge) that was not undone yet.
+    /// Returns the kind of the reverted operation and the names of the entries it touched, or `None` if there is nothing to undo
+    pub fn undo(&self) -> Result<Option<(OperationKind, Vec<String>)>> {
+        self.in_transaction(|| {
+            let Some((op_id, kind)) = DBOpLog::last_done(&self.conn)? else {
+                return Ok(None);
+            };
+            let kind = kind.parse::<OperationKind>()?;
+            let changes = DBOpLog::changes(&self.conn, op_id)?;
+
+            let mut names = Vec::new();
+            for (before, after) in changes.into_iter().rev() {
+                self.apply_change(kind, after.as_ref(), before.as_ref())?;
+                names.extend(before.or(after).map(|e| e.name));
+            }
+            names.reverse();
+            DBOpLog::set_undone(&self.conn, op_id, true)?;
+            Ok(Some((kind, names)))
+        })
+    }
+
+    /// Applies again the oldest operation that was undone and not redone yet.
+    /// Returns the kind of the operation and the names of the entries it touched, or `None` if there is nothing to redo
+    pub fn redo(&self) -> Result<Option<(OperationKind, Vec<String>)>> {
+        self.in_transaction(|| {
+            let Some((op_id, kind)) = DBOpLog::first_undone(&self.conn)? else {
+                return Ok(None);
+            };
+            let kind = kind.parse::<OperationKind>()?;
+            let changes = DBOpLog::changes(&self.conn, op_id)?;
+
+            let mut names = Vec::new();
+            for (before, after) in changes.into_iter() {
+                self.apply_change(kind, before.as_ref(), after.as_ref())?;
+                names.extend(after.or(before).map(|e| e.name));
+            }
+            DBOpLog::set_undone(&self.conn, op_id, false)?;
+            Ok(Some((kind, names)))
+        })
+    }
+
+    /// Brings an entry from the state `from` to the state `to`, where `None` means that the entry does not exist.
+    /// Only the fields that differ between `from` and `to` are changed, so that the changes made to the other fields since
+    /// (e.g. by `mark-read`) are kept. Fails if the entry was changed since in a way that the change would revert.
+    /// Entries removed by an operation of a kind that [uses the trash](OperationKind::uses_trash) go through the trash.
+    /// Expects to be run inside of a transaction
+    fn apply_change(
+        &self,
+        kind: OperationKind,
+        from: Option<&Entry>,
+        to: Option<&Entry>,
+    ) -> Result<()> {
+        match (from, to) {
+            (Some(from), None) => {
+                let current = self.get_by_name(from.name.as_str())?;
+                if let Some(field) = changed_fields(from, &current).first() {
+                    return Err(changed_since(&from.name, field));
+                }
+                let entry = DBEntry::remove_by_name(&self.conn, from.name.as_str())?;
+                if kind.uses_trash() {
+                    DBTrash::add(&self.conn, &entry)?;
+                }
+            }
+            (None, Some(to)) => {
+                if kind.uses_trash() {
+                    DBTrash::take_by_name(&self.conn, to.name.as_str())?;
+                }
+                let mut to = to.clone();
+                // The snapshots of permanently removed entries are deleted along with them
+                if to.snapshot.as_ref().is_some_and(|s| !Path::new(s).exists()) {
+                    to.snapshot = None;
+                }
+                self.create_complete(&to)?;
+            }
+            (Some(from), Some(to)) => {
+                let entry_id = self.get_existing_id(from.name.as_str())?;
+                let current = self.get_by_id(entry_id)?;
+                let changed_since_op = changed_fields(from, &current);
+                let mut fields = entry_fields(&current);
+                let values = entry_fields(to);
+                for field in changed_fields(from, to) {
+                    if changed_since_op.contains(&field) {
+                        return Err(changed_since(&from.name, &field));
+                    }
+                    match values.get(field.as_str()) {
+                        Some(value) => fields.insert(field.into(), value.clone()),
+                        None => fields.remove(field.as_str()),
+                    };
+                }
+                let entry: Entry = serde_yaml::from_value(serde_yaml::Value::Mapping(fields))?;
+                self.track(&[entry_id], || {
+                    DBEntry::overwrite(&self.conn, entry_id, &entry)
+                })?;
+            }
+            (None, None) => {}
+        }
+        Ok(())
+    }
+
+    /// Records an operation in the operations log, so that it can be undone. Expects to be run inside of a transaction
+    fn record(
+        &self,
+        kind: OperationKind,
+        changes: &[(Option<&Entry>, Option<&Entry>)],
+    ) -> Result<()> {
+        DBOpLog::record(&self.conn, kind.as_str(), changes)
+    }
+
+    /// Returns the entries in the trash along with the date they were removed on, most recently removed first
+    pub fn trash_list(&self) -> Result<Vec<(String, Entry)>> {
+        DBTrash::get_all(&self.conn)
+    }
+
+    /// Moves the most recently removed entry with name = `name` out of the trash and back into the reading list.
+    /// Returns the restored entry
+    pub fn trash_restore(&self, name: impl AsRef<str>) -> Result<Entry> {
+        let entry_id = self.in_transaction(|| {
+            let entry = DBTrash::take_by_name(&self.conn, name.as_ref())?.ok_or_else(|| {
+                RListError::new(
+                    ErrorCode::NotFound,
+                    Entity::new("entry", "name", name.as_ref()),
+                    format!(
+                        "Could not find any entry with name {} in the trash",
+                        name.as_ref().entry_name()
+                    ),
+                )
+            })?;
+            self.create_complete(&entry)
+        })?;
+        self.get_by_id(entry_id)
+    }
+
+    /// Permanently deletes the entries that were put into the trash before `before` (formatted like SQLITE_DATETIME_FORMAT),
+    /// or all of them if `before` is `None`, along with their snapshots. Returns the number of deleted entries
+    pub fn trash_empty(&self, before: Option<&str>) -> Result<usize> {
+        let removed = self.in_transaction(|| DBTrash::remove_older_than(&self.conn, before))?;
+        remove_snapshots(&removed);
+        Ok(removed.len())
+    }
+
+    pub fn dump_all(&self) -> Result<Vec<Entry>> {
+        DBEntry::get_all_complete(&self.conn)
+    }
+
+    /// Imports all of the entries provided, in a single transaction. The entries whose name or url already belongs to
+    /// an entry of the reading list are handled according to `opts.on_conflict`, and the entries that cannot be created
+    /// are skipped. Returns what was done with each entry, in order
+    pub fn import(&self, entries: Vec<Entry>, opts: &ImportOptions) -> Result<Vec<ImportResult>> {
+        let mut results = Vec::with_capacity(entries.len());
+        let import = || {
+            for entry in entries {
+                // Each entry is imported in a nested transaction, so that a failure only rolls back its own changes
+                let outcome = self
+                    .in_transaction(|| self.import_entry(&entry, opts))
+                    .unwrap_or_else(|err| ImportOutcome::Skipped(err.to_string()));
+                results.push(ImportResult { entry, outcome });
+            }
+            // A dry run imports the entries like any other import, so that the outcomes are exact, and then rolls back all of the changes
+            if opts.dry_run {
+                Err(anyhow::Error::new(DryRun))
+            } else {
+                Ok(())
+            }
+        };
+        // Nothing is changed by a dry run, so there is nothing to back up
+        let res = if opts.dry_run {
+            self.in_transaction_without_backup(import)
+        } else {
+            self.in_transaction(import)
+        };
+        match res {
+            Err(err) if !err.is::<DryRun>() => Err(err),
+            _ => Ok(results),
+        }
+    }
+
+    /// Imports the entry `e`, handling the conflicts with the existing entries according to `opts.on_conflict`
+    fn import_entry(&self, e: &Entry, opts: &ImportOptions) -> Result<ImportOutcome> {
+        let by_name = DBEntry::get_id_from_name(&self.conn, &e.name)?;
+        let by_url = DBEntry::get_id_from_url(&self.conn, &e.url)?;
+        let existing_id = match (by_name, by_url) {
+            (None, None) => {
+                self.create_complete(e)?;
+                return Ok(ImportOutcome::Created);
+            }
+            (Some(a), Some(b)) if a != b => {
+                let other = self.get_by_id(b)?;
+                return Ok(ImportOutcome::Skipped(format!(
+                    "the name belongs to an entry and the url to another one ({})",
+                    other.name
+                )));
+            }
+            (Some(entry_id), _) | (None, Some(entry_id)) => entry_id,
+        };
+        let existing = self.get_by_id(existing_id)?;
+
+        let skip_reason = match opts.on_conflict {
+            OnConflict::Skip if by_name.is_some() && by_url.is_some() => {
+                Some("an entry with the same name and url already exists".to_string())
+            }
+            OnConflict::Skip if by_name.is_some() => {
+                Some("an entry with the same name already exists".to_string())
+            }
+            OnConflict::Skip | OnConflict::Rename if by_url.is_some() => Some(format!(
+                "an entry with the same url already exists ({})",
+                existing.name
+            )),
+            _ => None,
+        };
+        if let Some(reason) = skip_reason {
+            let new_topics = e
+                .topics
+                .iter()
+                .filter(|t| !existing.topics.contains(&Topic::normalize(t)))
+                .cloned()
+                .collect::<Vec<_>>();
+            if !opts.merge_topics || new_topics.is_empty() {
+                return Ok(ImportOutcome::Skipped(reason));
+            }
+            let edit = EditOptions {
+                add_topics: Some(new_topics),
+                ..Default::default()
+            };
+            self.track(&[existing_id], || {
+                self.apply_edit(existing.name.clone(), edit)
+            })?;
+            return Ok(ImportOutcome::TopicsMerged(existing.name));
+        }
+
+        match opts.on_conflict {
+            OnConflict::Skip => unreachable!("the entries that conflict are skipped above"),
+            OnConflict::Rename => {
+                let mut n = 2;
+                let mut name = format!("{} ({n})", e.name);
+                while DBEntry::get_id_from_name(&self.conn, &name)?.is_some() {
+                    n += 1;
+                    name = format!("{} ({n})", e.name);
+                }
+                self.create_complete(&Entry {
+                    name: name.clone(),
+                    ..e.clone()
+                })?;
+                Ok(ImportOutcome::Renamed(name))
+            }
+            OnConflict::Overwrite => {
+                let mut e = e.clone();
+                if opts.merge_topics {
+                    e.topics.extend(existing.topics.iter().cloned());
+                }
+                self.track(&[existing_id], || {
+                    DBEntry::overwrite(&self.conn, existing_id, &e)
+                })?;
+                Ok(ImportOutcome::Overwritten(existing.name))
+            }
+            OnConflict::Merge => {
+                let edit = EditOptions {
+                    author: e.author.clone().filter(|_| existing.author.is_none()),
+                    length: e.length.filter(|_| existing.length.is_none()),
+                    add_topics: Some(e.topics.clone()).filter(|t| {
+                        t.iter()
+                            .any(|t| !existing.topics.contains(&Topic::normalize(t)))
+                    }),
+                    meta: e
+                        .meta
+                        .iter()
+                        .filter(|(k, _v)| !existing.meta.contains_key(*k))
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect(),
+                    ..Default::default()
+                };
+                if !edit.is_empty() {
+                    self.track(&[existing_id], || {
+                        self.apply_edit(existing.name.clone(), edit)
+                    })?;
+                }
+                Ok(ImportOutcome::Merged(existing.name))
+            }
+        }
+    }
+
+    /// Copies the entries with the given names, with all of their data, to the reading list `target`, and then removes them
+    /// from this one if `remove` is set. Fails without changing either reading list if any of the entries does not exist
+    /// or if `target` already has an entry with the same name or url. Returns the transferred entries.
+    /// The entries are only removed from this reading list once they are saved in `target`, so they cannot be lost if
+    /// something fails in between, and their snapshot files are left in place, since the copies still refer to them
+    pub fn transfer(&self, names: &[String], target: &RList, remove: bool) -> Result<Vec<Entry>> {
+        self.in_transaction(|| {
+            let mut entries = Vec::with_capacity(names.len());
+            for name in names {
+                let entry = if remove {
+                    DBEntry::remove_by_name(&self.conn, name.as_str())?
+                } else {
+                    self.get_by_name(name)?
+                };
+                entries.push(entry);
+            }
+            if remove {
+                let changes = entries.iter().map(|e| (Some(e), None)).collect::<Vec<_>>();
+                self.record(OperationKind::Remove, &changes)?;
+            }
+
+            target.in_transaction(|| {
+                let mut created = Vec::with_capacity(entries.len());
+                for e in entries.iter() {
+                    let by_name = DBEntry::get_id_from_name(&target.conn, &e.name)?;
+                    let by_url = DBEntry::get_id_from_url(&target.conn, &e.url)?;
+                    if let Some(existing_id) = by_name.or(by_url) {
+                        let (field, value) = if by_name.is_some() {
+                            ("name", &e.name)
+                        } else {
+                            ("url", &e.url)
+                        };
+                        return Err(RListError::new(
+                            ErrorCode::AlreadyExists,
+                            Entity::new("entry", field, value),
+                            format!(
+                                "{} could not be transferred: the other reading list already has an entry with the same {field} ({})",
+                                e.name,
+                                target.get_by_id(existing_id)?.name
+                            ),
+                        )
+                        .into());
+                    }
+                    // The id of the entry may belong to another entry of `target`
+                    let entry_id = target.create_complete(&Entry {
+                        id: None,
+                        ..e.clone()
+                    })?;
+                    created.push(target.get_by_id(entry_id)?);
+                }
+                let changes = created.iter().map(|e| (None, Some(e))).collect::<Vec<_>>();
+                target.record(OperationKind::Add, &changes)?;
+                Ok(created)
+            })
+        })
+    }
+
+    /// Creates the entry `e` with all of its data: topics, metadata and every other column. Returns the id of the new entry
+    fn create_complete(&self, e: &Entry) -> Result<i64> {
+        let (entry_id, _entry) = DBEntry::create(
+            &self.conn,
+            e.name.as_str(),
+            e.url.as_str(),
+            e.author.as_deref(),
+            e.length,
+            Some(e.added.as_str()),
+            e.id,
+        )?;
+        if !e.topics.is_empty() {
+            let topic_ids = DBTopic::create_many(&self.conn, &e.topics)?;
+            DBEntry::associate_with_topics(&self.conn, entry_id, topic_ids)?;
+        }
+        DBMeta::set_many(&self.conn, entry_id, &e.meta.iter().collect::<Vec<_>>())?;
+        DBEntry::restore_details(&self.conn, entry_id, e)?;
+        if let Some(text) = e
+            .snapshot
+            .as_ref()
+            .and_then(|s| std::fs::read_to_string(s).ok())
+        {
+            DBSnapshot::index(&self.conn, entry_id, &text)?;
+        }
+        Ok(entry_id)
+    }
+
+    /// Computes the renames of all of the topics whose name fully matches the regex `pattern`.
+    /// `replacement` can reference the capture groups of `pattern` (`$1`, `${name}`, ...).
+    /// Topics that would end up with the same name (or with the name of an already existing topic) are marked to be merged.
+    pub fn plan_topics_rename(
+        &self,
+        pattern: impl AsRef<str>,
+        replacement: impl AsRef<str>,
+    ) -> Result<Vec<TopicRename>> {
+        let re = Regex::new(&format!("^(?:{})$", pattern.as_ref()))?;
+        let topics = DBTopic::get_all_with_counts(&self.conn)?;
+
+        let mut renames = Vec::new();
+        for (topic_id, name, entries) in topics.iter() {
+            if !re.is_match(name) {
+                continue;
+            }
+            let to = re.replace(name, replacement.as_ref()).to_string();
+            if to.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Topic {} would be renamed to an empty string",
+                    name.bold()
+                ));
+            }
+            if &to == name {
+                continue;
+            }
+            renames.push(TopicRename {
+                topic_id: *topic_id,
+                from: name.clone(),
+                to,
+                entries: *entries,
+                merge: false,
+            });
+        }
+
+        mark_topic_merges(&topics, &mut renames);
+        Ok(renames)
+    }
+
+    /// Computes the renames needed to normalize the names of the entries and of the topics (see [`normalize_name`]),
+    /// which may have been saved by older versions of rlist with extra whitespace or in a different Unicode form
+    pub fn plan_normalize(&self) -> Result<Normalization> {
+        let mut plan = Normalization::default();
+
+        let (normalized, renamed): (Vec<_>, Vec<_>) = DBEntry::get_all_names(&self.conn)?
+            .into_iter()
+            .map(|(entry_id, name)| EntryRename {
+                entry_id,
+                to: normalize_name(&name),
+                from: name,
+            })
+            .partition(|r| r.from == r.to);
+        // Names are unique regardless of their (ASCII) case
+        let mut taken = normalized
+            .iter()
+            .map(|r| r.to.to_ascii_lowercase())
+            .collect::<HashSet<_>>();
+        for mut r in renamed {
+            let mut n = 2;
+            let normalized = r.to.clone();
+            while !taken.insert(r.to.to_ascii_lowercase()) {
+                r.to = format!("{normalized} ({n})");
+                n += 1;
+            }
+            plan.entries.push(r);
+        }
+
+        let topics = DBTopic::get_all_with_counts(&self.conn)?;
+        plan.topics = topics
+            .iter()
+            .filter_map(|(topic_id, name, entries)| {
+                let to = Topic::normalize(name);
+                (&to != name).then(|| TopicRename {
+                    topic_id: *topic_id,
+                    from: name.clone(),
+                    to,
+                    entries: *entries,
+                    merge: false,
+                })
+            })
+            .collect();
+        mark_topic_merges(&topics, &mut plan.topics);
+
+        Ok(plan)
+    }
+
+    /// Applies the renames computed by [`RList::plan_normalize`] in a single transaction
+    pub fn normalize(&self, plan: &Normalization) -> Result<()> {
+        self.in_transaction(|| {
+            let entry_ids = plan.entries.iter().map(|r| r.entry_id).collect::<Vec<_>>();
+            self.track(&entry_ids, || {
+                for r in plan.entries.iter() {
+                    DBEntry::set_name(&self.conn, r.entry_id, &r.to)?;
+                }
+                Ok(())
+            })?;
+            self.rename_topics(&plan.topics)
+        })
+    }
+
+    /// Applies the renames computed by [`RList::plan_topics_rename`] in a single transaction
+    pub fn rename_topics(&self, renames: &[TopicRename]) -> Result<()> {
+        self.in_transaction(|| {
+            let mut entry_ids = Vec::new();
+            for r in renames.iter() {
+                entry_ids.extend(DBEntry::get_ids_related_to(&self.conn, r.topic_id)?);
+            }
+            entry_ids.sort();
+            entry_ids.dedup();
+
+            self.track(&entry_ids, || {
+                // Topics are first renamed to temporary names so that renames such as `a -> b`, `b -> a` do not clash
+                for r in renames.iter() {
+                    DBTopic::rename(&self.conn, r.topic_id, format!("\0rename-{}", r.topic_id))?;
+                }
+
+                for r in renames.iter() {
+                    match DBTopic::get_id_from_name(&self.conn, r.to.as_str())? {
+                        Some(target_id) => DBTopic::merge_into(&self.conn, r.topic_id, target_id)?,
+                        None => DBTopic::rename(&self.conn, r.topic_id, r.to.as_str())?,
+                    }
+                }
+                DBTopic::link_parents(&self.conn)
+            })
+        })
+    }
+
+    /// Returns the changes made to the entry with name = `name`, oldest first
+    pub fn history(&self, name: impl AsRef<str>) -> Result<Vec<HistoryRecord>> {
+        let entry_id = self.get_existing_id(name.as_ref())?;
+        DBHistory::get_related_to(&self.conn, entry_id)
+    }
+
+    /// Runs `f` and records in the history of the entries with ids in `entry_ids` how it changed them.
+    /// Expects to be run inside of a transaction
+    fn track<T>(&self, entry_ids: &[i64], f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let before = entry_ids
+            .iter()
+            .map(|id| self.get_by_id(*id))
+            .collect::<Result<Vec<_>>>()?;
+        let res = f()?;
+        for (entry_id, before) in entry_ids.iter().zip(before) {
+            self.record_history(*entry_id, &before)?;
+        }
+        Ok(res)
+    }
+
+    /// Records in the history of the entry with id = `entry_id` how it changed since `before`,
+    /// updating its `modified` date if it did. Expects to be run inside of a transaction
+    fn record_history(&self, entry_id: i64, before: &Entry) -> Result<()> {
+        if DBHistory::record(&self.conn, entry_id, before, &self.get_by_id(entry_id)?)? {
+            DBEntry::touch(&self.conn, entry_id)?;
+        }
+        Ok(())
+    }
+
+    /// Runs `f` inside of a transaction, so that the changes it makes to the reading list are applied all together or not at all:
+    /// they are committed if `f` returns `Ok` and rolled back otherwise.
+    /// ```no_run
+    /// # use rlist::{EditOptions, RList};
+    /// # let rlist = RList::init("rlist.sqlite")?;
+    /// rlist.transaction(|rlist| {
+    ///     rlist.edit("old".to_string(), EditOptions { new_name: Some("new".to_string()), ..Default::default() })?;
+    ///     rlist.set_read("new", true)
+    /// })?;
+    /// # anyhow::Ok(())
+    /// ```
+    /// Transactions can be nested, in which case only the changes of the inner one are rolled back if it fails
+    pub fn transaction<T>(&self, f: impl FnOnce(&Self) -> Result<T>) -> Result<T> {
+        self.in_transaction(|| f(self))
+    }
+
+    /// Enables the automatic backups: from now on, a backup of the reading list is saved according to `policy`
+    /// before the first change made to it, and the oldest backups are removed
+    pub fn set_backups(&mut self, policy: BackupPolicy) {
+        self.backups = Some(policy);
+    }
+
+    /// Enables or disables printing each SQL statement run on the reading list to stderr once it is done, with the values
+    /// bound to its parameters and how long it took (to the millisecond). The statements run while opening the reading list are not included
+    pub fn trace_sql(&mut self, enabled: bool) {
+        // The trace and profile hooks of rusqlite cannot be used together, and only the first gives the bound values
+        // while only the second gives the timing: the profile event of `sqlite3_trace_v2` gives both
+        let (mask, callback) = match enabled {
+            true => (
+                ffi::SQLITE_TRACE_PROFILE as c_uint,
+                Some(print_statement as _),
+            ),
+            false => (0, None),
+        };
+        // SAFETY: the handle is valid as long as the connection, which unregisters the callback when it is closed
+        unsafe {
+            ffi::sqlite3_trace_v2(self.conn.handle(), mask, callback, std::ptr::null_mut());
+        }
+    }
+
+    /// Saves a consistent copy of the whole reading list to the SQLite database at `path`, which is overwritten if it exists.
+    /// Uses the backup API of SQLite, so that the changes still in the write-ahead log are included too
+    pub fn backup(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        self.conn
+            .backup(DatabaseName::Main, path, None)
+            .with_context(|| format!("Could not back up the reading list to {}", path.display()))
+    }
+
+    /// Replaces the whole content of the reading list with the one of the backup at `path`, saved by [`RList::backup`]
+    /// (or of any other rlist database). The automatic backup is saved first, if backups are enabled.
+    /// Returns the number of entries in the restored reading list
+    pub fn restore(&mut self, path: impl AsRef<Path>) -> Result<i64> {
+        let path = path.as_ref();
+        let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Could not open the backup {}", path.display()))?;
+        // Files that are not SQLite databases fail to be read
+        if !table_exists(&source, "rlist").unwrap_or(false) {
+            return Err(anyhow::anyhow!(
+                "{} is not a backup of a reading list",
+                path.display()
+            ));
+        }
+        drop(source);
+
+        self.backup_before_first_change()?;
+        self.conn
+            .restore(DatabaseName::Main, path, None::<fn(Progress)>)
+            .with_context(|| format!("Could not restore the backup {}", path.display()))?;
+        if self.sync {
+            self.storage.save(&self.dump_all()?)?;
+        }
+        self.count(&QueryOptions::default())
+    }
+
+    /// Checks that the database is intact: that the file is not corrupted, that every row only references rows that exist,
+    /// and that there are no orphan rows
+    pub fn check(&self) -> Result<DbCheck> {
+        Ok(DbCheck {
+            integrity_errors: DBMaintenance::integrity_errors(&self.conn)?,
+            foreign_key_violations: DBMaintenance::foreign_key_violations(&self.conn)?,
+            orphans: DBMaintenance::orphans(&self.conn)?,
+        })
+    }
+
+    /// Fixes the problems found by [`RList::check`], except for the corruption of the file: removes the rows that reference
+    /// rows that do not exist (or unsets the reference, for the author of the entries and the parent of the topics) and the orphan rows
+    pub fn repair(&self) -> Result<()> {
+        self.in_transaction(|| DBMaintenance::repair(&self.conn))
+    }
+
+    /// Rebuilds the database file, reclaiming the unused space, and updates the statistics SQLite uses to plan the queries.
+    /// Returns the size of the database before and after, in bytes
+    pub fn optimize(&self) -> Result<(i64, i64)> {
+        let before = DBMaintenance::size(&self.conn)?;
+        DBMaintenance::optimize(&self.conn)?;
+        Ok((before, DBMaintenance::size(&self.conn)?))
+    }
+
+    /// Saves the automatic backup, if backups are enabled and it was not saved yet
+    fn backup_before_first_change(&self) -> Result<()> {
+        let Some(policy) = self.backups.as_ref() else {
+            return Ok(());
+        };
+        if self.backed_up.replace(true) {
+            return Ok(());
+        }
+        self.backup(policy.new_backup_path())?;
+        policy.rotate()
+    }
+
+    /// Runs `f` inside of a transaction (or of a savepoint, if a transaction is already open),
+    /// which is committed if `f` returns `Ok` and rolled back otherwise
+    fn in_transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if self.depth.get() == 0 {
+            self.backup_before_first_change()?;
+        }
+        self.in_transaction_without_backup(f)
+    }
+
+    /// Like [`RList::in_transaction`], but without saving the automatic backup first.
+    /// Only meant for the changes that are always rolled back, such as the ones of a dry run
+    fn in_transaction_without_backup<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let depth = self.depth.get();
+        let (begin, commit, rollback) = if depth == 0 {
+            (
+                // The write lock is taken right away, since a deferred transaction that tries to write
+                // while another command is writing fails without waiting for the lock to be released
+                "BEGIN IMMEDIATE TRANSACTION;".to_string(),
+                "COMMIT;".to_string(),
+                "ROLLBACK;".to_string(),
+            )
+        } else {
+            (
+                format!("SAVEPOINT sp{depth};"),
+                format!("RELEASE sp{depth};"),
+                format!("ROLLBACK TO sp{depth}; RELEASE sp{depth};"),
+            )
+        };
+
+        execute_retrying_if_busy(&self.conn, &begin)?;
+        self.depth.set(depth + 1);
+        let res = f();
+        self.depth.set(depth);
+        match res {
+            Ok(res) => {
+                self.conn.execute_batch(&commit)?;
+                if depth == 0 && self.sync {
+                    self.storage.save(&self.dump_all()?)?;
+                }
+                Ok(res)
+            }
+            Err(err) => {
+                self.conn.execute_batch(&rollback)?;
+                Err(err)
+            }
+        }
+    }
+}
+
+/// The error that rolls back the changes of a dry run of [`RList::import`]
+#[derive(Debug)]
+struct DryRun;
+
+impl Display for DryRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dry run")
+    }
+}
+
+impl std::error::Error for DryRun {}
+
+/// How many times a transaction is started before giving up, if the database is locked by another rlist command
+const BUSY_RETRIES: u32 = 3;
+
+/// Runs `sql`, retrying a few times if the database is locked by another rlist command for longer than the busy timeout
+fn execute_retrying_if_busy(conn: &Connection, sql: &str) -> Result<()> {
+    let mut attempt = 1;
+    loop {
+        match conn.execute_batch(sql) {
+            Err(rusqlite::Error::SqliteFailure(err, _))
+                if err.code == rusqlite::ErrorCode::DatabaseBusy =>
+            {
+                if attempt == BUSY_RETRIES {
+                    return Err(anyhow::anyhow!(
+                        "The reading list is being changed by another rlist command: try again later"
+                    ));
+                }
+                std::thread::sleep(std::time::Duration::from_millis(100 * attempt as u64));
+                attempt += 1;
+            }
+            res => return Ok(res?),
+        }
+    }
+}
+
+/// Marks the renames of `topics` (the id, the name and the number of entries of every topic) that must be merged into another topic,
+/// since they end up with the same name as an existing topic or as another rename
+fn mark_topic_merges(topics: &[(i64, String, i64)], renames: &mut [TopicRename]) {
+    let mut targets: HashMap<&str, usize> = HashMap::new();
+    for r in renames.iter() {
+        *targets.entry(r.to.as_str()).or_default() += 1;
+    }
+    let merges = renames
+        .iter()
+        .map(|r| {
+            // A topic that is not renamed keeps its name, so it collides with every rename that targets it
+            let target_is_kept = topics
+                .iter()
+                .any(|(id, name, _)| name == &r.to && !renames.iter().any(|o| o.topic_id == *id));
+            target_is_kept || targets[r.to.as_str()] > 1
+        })
+        .collect::<Vec<_>>();
+    renames
+        .iter_mut()
+        .zip(merges)
+        .for_each(|(r, merge)| r.merge = merge);
+}
+
+/// Returns the kind of operation of a removal
+fn removal_kind(permanent: bool) -> OperationKind {
+    if permanent {
+        OperationKind::Remove
+    } else {
+        OperationKind::Trash
+    }
+}
+
+/// Deletes the snapshot files of `entries`, which were removed from the reading list.
+/// Failures only produce a warning, as the entries are already gone
+fn remove_snapshots(entries: &[Entry]) {
+    for snapshot in entries.iter().filter_map(|e| e.snapshot.as_ref()) {
+        match std::fs::remove_file(snapshot) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => eprintln!(
+                "{}: could not delete the snapshot {snapshot}: {err}",
+                "Warning".bold().yellow()
+            ),
+            _ => {}
+        }
+    }
+}
+
+/// Prints an SQL statement that was run on a single line, with its bound values and how long it took, for [`RList::trace_sql`].
+/// Called by SQLite with the arguments of a `sqlite3_trace_v2` event
+unsafe extern "C" fn print_statement(
+    event: c_uint,
+    _context: *mut c_void,
+    stmt: *mut c_void,
+    nanos: *mut c_void,
+) -> c_int {
+    if event != ffi::SQLITE_TRACE_PROFILE as c_uint {
+        return 0;
+    }
+    // SAFETY: for the profile event, SQLite passes the statement that is done and a pointer to how long it took in nanoseconds
+    let (sql, elapsed) = unsafe {
+        let stmt = stmt as *mut ffi::sqlite3_stmt;
+        let expanded = ffi::sqlite3_expanded_sql(stmt);
+        let sql = match expanded.is_null() {
+            true => CStr::from_ptr(ffi::sqlite3_sql(stmt))
+                .to_string_lossy()
+                .into_owned(),
+            false => CStr::from_ptr(expanded).to_string_lossy().into_owned(),
+        };
+        ffi::sqlite3_free(expanded as *mut c_void);
+        (sql, Duration::from_nanos(*(nanos as *const i64) as u64))
+    };
+    let sql = sql.split_whitespace().collect::<Vec<_>>().join(" ");
+    // Panicking here would abort rlist, so failing to write to stderr is ignored
+    let _ = writeln!(
+        std::io::stderr(),
+        "{} ({} ms): {sql}",
+        "SQL".bold().dimmed(),
+        elapsed.as_millis()
+    );
+    0
+}
+
+/// Returns the fields of `entry` as they are serialized, by name, with its topics sorted
+fn entry_fields(entry: &Entry) -> serde_yaml::Mapping {
+    let mut entry = entry.clone();
+    entry.topics.sort();
+    match serde_yaml::to_value(entry) {
+        Ok(serde_yaml::Value::Mapping(fields)) => fields,
+        _ => unreachable!("entries are serialized as mappings"),
+    }
+}
+
+/// Returns the names of the fields whose value differs between `a` and `b`, ignoring `id` and `modified`.
+/// The order of the topics does not matter
+fn changed_fields(a: &Entry, b: &Entry) -> Vec<String> {
+    let (a, b) = (entry_fields(a), entry_fields(b));
+    let mut changed = a
+        .keys()
+        .chain(b.keys())
+        .filter_map(|key| key.as_str())
+        .filter(|key| !matches!(*key, "id" | "modified"))
+        .filter(|key| a.get(key) != b.get(key))
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+/// The error of an undo (or redo) that would revert a change made to the entry with name = `name` since the operation
+fn changed_since(name: &str, field: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "The `{field}` field of {} was changed after this operation, which cannot be reverted without losing that change",
+        name.entry_name()
+    )
+}
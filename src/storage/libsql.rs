@@ -0,0 +1,243 @@
+use std::cell::Cell;
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde_json::{json, Value};
+
+use super::Storage;
+use crate::{entry::Entry, web::agent};
+
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS rlist_entries (
+    name TEXT PRIMARY KEY,
+    entry TEXT NOT NULL
+);";
+
+/// The table holding the version of the entries, which is increased every time they are saved
+const CREATE_VERSION_TABLE: &str = "CREATE TABLE IF NOT EXISTS rlist_version (
+    version INTEGER NOT NULL
+);";
+
+const SELECT_VERSION: &str = "SELECT version FROM rlist_version;";
+
+/// Keeps the entries of the reading list in a remote libsql database (e.g. on Turso), which is reached through its HTTP API.
+/// As with [`super::FileStorage`], the trash, the queue, the history of the entries and the operations that can be undone are not saved.
+/// Saving fails if the entries were saved by someone else since they were loaded, instead of overwriting their changes
+pub struct LibsqlStorage {
+    /// The url of the database, e.g. `libsql://my-db-me.turso.io`
+    url: String,
+    auth_token: Option<String>,
+    /// The version of the entries that were loaded (or last saved)
+    version: Cell<i64>,
+}
+
+impl LibsqlStorage {
+    pub fn new(url: impl Into<String>, auth_token: Option<String>) -> Self {
+        Self {
+            url: url.into(),
+            auth_token,
+            version: Cell::new(0),
+        }
+    }
+
+    /// Returns the endpoint of the HTTP API of the database
+    fn pipeline_url(&self) -> String {
+        let url = self.url.trim_end_matches('/');
+        let url = match url.strip_prefix("libsql://") {
+            Some(host) => format!("https://{host}"),
+            None => url.to_string(),
+        };
+        format!("{url}/v2/pipeline")
+    }
+
+    /// Runs `requests` (see the Hrana protocol) on the database, in order. Returns the response to each of them
+    fn pipeline(&self, requests: Vec<Value>) -> Result<Vec<Value>> {
+        self.pipeline_on_stream(None, requests, true)
+            .map(|(results, _baton)| results)
+    }
+
+    /// Runs `requests` on the stream identified by `baton` (a new one if `None`), which keeps its connection, and so its
+    /// open transaction, between calls. Returns the response to each request, and the baton to continue the stream with
+    /// unless it is `close`d
+    fn pipeline_on_stream(
+        &self,
+        baton: Option<String>,
+        requests: Vec<Value>,
+        close: bool,
+    ) -> Result<(Vec<Value>, Option<String>)> {
+        let mut request = agent()
+            .post(&self.pipeline_url())
+            .set("Content-Type", "application/json");
+        if let Some(token) = self.auth_token.as_deref() {
+            request = request.set("Authorization", &format!("Bearer {token}"));
+        }
+
+        let mut requests = requests;
+        if close {
+            requests.push(json!({"type": "close"}));
+        }
+        let body = json!({ "baton": baton, "requests": &requests }).to_string();
+        let response = match request.send_string(&body) {
+            Ok(response) => response,
+            Err(ureq::Error::Status(code, response)) => {
+                return Err(anyhow::anyhow!(
+                    "The libsql database answered with {code}: {}",
+                    response.into_string().unwrap_or_default()
+                ))
+            }
+            Err(err) => return Err(err).context("Could not connect to the libsql database"),
+        };
+        let response: Value = serde_json::from_str(&response.into_string()?)?;
+
+        let mut results = response["results"].as_array().cloned().unwrap_or_default();
+        if results.len() != requests.len() {
+            return Err(anyhow::anyhow!(
+                "Unexpected response from the libsql database: {response}"
+            ));
+        }
+        if close {
+            results.pop(); // the response to `close`
+        }
+        let baton = response["baton"].as_str().map(str::to_string);
+        results
+            .into_iter()
+            .map(|result| match result["type"].as_str() {
+                Some("ok") => Ok(result["response"]["result"].clone()),
+                _ => Err(anyhow::anyhow!(
+                    "The libsql database returned an error: {}",
+                    result["error"]["message"]
+                        .as_str()
+                        .unwrap_or("unknown error")
+                )),
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(|results| (results, baton))
+    }
+}
+
+/// Returns the version in the response to [`SELECT_VERSION`], which is 0 before the entries are first saved
+fn version_of(result: &Value) -> Result<i64> {
+    match result["rows"][0][0]["value"].as_str() {
+        Some(version) => Ok(version.parse()?),
+        None => Ok(0),
+    }
+}
+
+/// Returns the request executing `sql`, with `args` bound to its positional parameters
+fn execute(sql: &str, args: &[&str]) -> Value {
+    json!({
+        "type": "execute",
+        "stmt": {
+            "sql": sql,
+            "args": args.iter().map(|a| json!({"type": "text", "value": a})).collect::<Vec<_>>(),
+        },
+    })
+}
+
+impl Storage for LibsqlStorage {
+    fn connect(&self) -> Result<Connection> {
+        self.pipeline(vec![
+            execute(CREATE_TABLE, &[]),
+            execute(CREATE_VERSION_TABLE, &[]),
+        ])?;
+        Ok(Connection::open_in_memory()?)
+    }
+
+    fn load(&self) -> Result<Option<Vec<Entry>>> {
+        // Both are read in the same transaction, so that the version is the one of the entries
+        let results = self.pipeline(vec![
+            execute("BEGIN;", &[]),
+            execute("SELECT entry FROM rlist_entries ORDER BY name;", &[]),
+            execute(SELECT_VERSION, &[]),
+            execute("COMMIT;", &[]),
+        ])?;
+        self.version.set(version_of(&results[2])?);
+        results[1]["rows"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|row| {
+                let entry = row[0]["value"].as_str().unwrap_or_default();
+                serde_json::from_str(entry)
+                    .context("Could not read an entry of the libsql database")
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+
+    fn save(&self, entries: &[Entry]) -> Result<()> {
+        let entries = entries
+            .iter()
+            .map(|e| Ok((e.name.as_str(), serde_json::to_string(e)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // The database is locked for writing before the version is checked, so that nobody can save in between
+        let (results, baton) = self.pipeline_on_stream(
+            None,
+            vec![
+                execute("BEGIN IMMEDIATE;", &[]),
+                execute(SELECT_VERSION, &[]),
+            ],
+            false,
+        )?;
+        if version_of(&results[1])? != self.version.get() {
+            self.pipeline_on_stream(baton, vec![execute("ROLLBACK;", &[])], true)?;
+            return Err(anyhow::anyhow!(
+                "The reading list in the libsql database was changed (e.g. by rlist on another machine) after it was loaded. \
+                Run the command again to apply it to the current reading list"
+            ));
+        }
+        let version = (self.version.get() + 1).to_string();
+
+        // The statements are run as a batch, where each step only runs if the previous one succeeded,
+        // so that the entries are either all replaced or left untouched
+        let mut stmts = vec![execute("DELETE FROM rlist_entries;", &[])];
+        stmts.extend(entries.iter().map(|(name, entry)| {
+            execute(
+                "INSERT INTO rlist_entries (name, entry) VALUES (?, ?);",
+                &[name, entry],
+            )
+        }));
+        stmts.push(execute("DELETE FROM rlist_version;", &[]));
+        stmts.push(execute(
+            "INSERT INTO rlist_version (version) VALUES (?);",
+            &[&version],
+        ));
+        stmts.push(execute("COMMIT;", &[]));
+        let commit = stmts.len() - 1;
+
+        let mut steps = stmts
+            .into_iter()
+            .enumerate()
+            .map(|(i, request)| {
+                let mut step = json!({ "stmt": request["stmt"] });
+                if i > 0 {
+                    step["condition"] = json!({"type": "ok", "step": i - 1});
+                }
+                step
+            })
+            .collect::<Vec<_>>();
+        steps.push(json!({
+            "stmt": {"sql": "ROLLBACK;"},
+            "condition": {"type": "not", "cond": {"type": "ok", "step": commit}},
+        }));
+
+        let (results, _baton) = self.pipeline_on_stream(
+            baton,
+            vec![json!({"type": "batch", "batch": {"steps": steps}})],
+            true,
+        )?;
+        if let Some(error) = results[0]["step_errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|e| !e.is_null())
+        {
+            return Err(anyhow::anyhow!(
+                "Could not save the reading list to the libsql database: {}",
+                error["message"].as_str().unwrap_or("unknown error")
+            ));
+        }
+        self.version.set(self.version.get() + 1);
+        Ok(())
+    }
+}
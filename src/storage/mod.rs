@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::entry::Entry;
+
+mod libsql;
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "sqlcipher")]
+mod sqlcipher;
+
+pub use self::libsql::LibsqlStorage;
+#[cfg(feature = "postgres")]
+pub use self::postgres::PostgresStorage;
+#[cfg(feature = "sqlcipher")]
+pub use self::sqlcipher::EncryptedSqliteStorage;
+
+/// How long a connection to a database file waits for the other rlist commands using it to release their locks
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where a reading list is kept.
+/// rlist always works on an SQLite connection: the backends that do not keep the reading list in an SQLite database
+/// load it into an in-memory one when it is opened, and save it back after every change
+pub trait Storage {
+    /// Opens the connection on which the reading list is read and changed. Its tables are created afterwards, by [`crate::RList::open`]
+    fn connect(&self) -> Result<Connection>;
+
+    /// Returns the entries to fill the reading list with once its tables are created,
+    /// or `None` if the connection already contains them
+    fn load(&self) -> Result<Option<Vec<Entry>>> {
+        Ok(None)
+    }
+
+    /// Saves `entries`, the whole content of the reading list, after a change to it was committed.
+    /// Only called if [`Storage::load`] returned some entries
+    fn save(&self, _entries: &[Entry]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The kinds of storage that can be selected with the `storage` config option
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    /// An SQLite database
+    #[default]
+    Sqlite,
+    /// A JSON or YAML file, depending on its extension
+    File,
+    /// A PostgreSQL database, only available if rlist is built with the `postgres` feature
+    Postgres,
+    /// A remote libsql database, such as the ones hosted on Turso
+    Libsql,
+}
+
+impl StorageKind {
+    /// Returns the name of the file the reading list is kept in by default
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            StorageKind::Sqlite | StorageKind::Postgres | StorageKind::Libsql => "rlist.sqlite",
+            StorageKind::File => "rlist.yml",
+        }
+    }
+
+    /// Returns whether the reading list is kept in a remote database, reached through the `db_url` config option
+    pub fn is_remote(&self) -> bool {
+        matches!(self, StorageKind::Postgres | StorageKind::Libsql)
+    }
+}
+
+/// Keeps the reading list in an SQLite database
+pub struct SqliteStorage {
+    path: PathBuf,
+}
+
+impl SqliteStorage {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn connect(&self) -> Result<Connection> {
+        create_parent_dir(&self.path)?;
+        let conn = Connection::open(&self.path)?;
+        configure_file_connection(&conn)?;
+        Ok(conn)
+    }
+}
+
+/// Keeps the entries of the reading list in a plain file: a JSON file if its extension is `.json`, a YAML file otherwise.
+/// The trash, the queue, the history of the entries and the operations that can be undone are not saved in the file,
+/// so they only last as long as the reading list is open
+pub struct FileStorage {
+    path: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    fn is_json(&self) -> bool {
+        self.path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+    }
+}
+
+impl Storage for FileStorage {
+    fn connect(&self) -> Result<Connection> {
+        Ok(Connection::open_in_memory()?)
+    }
+
+    fn load(&self) -> Result<Option<Vec<Entry>>> {
+        if !self.path.exists() {
+            return Ok(Some(Vec::new()));
+        }
+        let content = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Could not read {}", self.path.display()))?;
+        let entries = if self.is_json() {
+            serde_json::from_str(&content)?
+        } else {
+            serde_yaml::from_str::<Option<Vec<Entry>>>(&content)?.unwrap_or_default()
+        };
+        Ok(Some(entries))
+    }
+
+    fn save(&self, entries: &[Entry]) -> Result<()> {
+        let content = if self.is_json() {
+            serde_json::to_string_pretty(entries)?
+        } else {
+            serde_yaml::to_string(entries)?
+        };
+
+        // The file is replaced all at once, so that it is never left half written
+        create_parent_dir(&self.path)?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Could not save the reading list to {}", self.path.display()))
+    }
+}
+
+/// Returns the storage of a reading list kept in the local file at `path`: a [`FileStorage`] if its extension is the one
+/// of a JSON or YAML file, and an [`SqliteStorage`] otherwise
+pub fn local_storage(path: impl AsRef<Path>) -> Box<dyn Storage> {
+    match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+        Some("json" | "yml" | "yaml") => Box::new(FileStorage::new(path)),
+        _ => Box::new(SqliteStorage::new(path)),
+    }
+}
+
+/// Creates the directories needed to create the file at `path`
+fn create_parent_dir(path: &Path) -> Result<()> {
+    std::fs::create_dir_all(path.parent().ok_or(anyhow::anyhow!(
+        "Could not create directories needed to create the reading list"
+    ))?)?;
+    Ok(())
+}
+
+/// Sets up a connection to a database file so that several rlist commands (e.g. a script and an interactive session)
+/// can use the database at the same time: readers do not block the writer, and a locked database is waited for instead of failing
+fn configure_file_connection(conn: &Connection) -> Result<()> {
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+
+use super::{configure_file_connection, create_parent_dir, Storage};
+
+/// The service under which the passphrases are saved in the keyring of the OS
+const KEYRING_SERVICE: &str = "rlist";
+
+/// Keeps the reading list in an SQLite database encrypted with SQLCipher.
+/// The passphrase is read from the keyring of the OS if it was saved there, and is prompted for otherwise
+pub struct EncryptedSqliteStorage {
+    path: PathBuf,
+    /// Whether to save the passphrase in the keyring after it is prompted for, so that it is only asked once
+    remember_passphrase: bool,
+}
+
+impl EncryptedSqliteStorage {
+    pub fn new(path: impl AsRef<Path>, remember_passphrase: bool) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            remember_passphrase,
+        }
+    }
+
+    /// Returns the entry of the keyring holding the passphrase of the database
+    fn keyring_entry(&self) -> Result<keyring::Entry> {
+        Ok(keyring::Entry::new(
+            KEYRING_SERVICE,
+            &self.path.to_string_lossy(),
+        )?)
+    }
+
+    /// Returns the passphrase saved in the keyring, if any
+    fn saved_passphrase(&self) -> Option<String> {
+        self.keyring_entry().ok()?.get_password().ok()
+    }
+
+    /// Asks the user for the passphrase. If the database does not exist yet, the passphrase has to be typed twice
+    fn prompt_passphrase(&self) -> Result<String> {
+        let prompt = format!("Passphrase of {}: ", self.path.display());
+        let passphrase =
+            rpassword::prompt_password(prompt).context("Could not read the passphrase")?;
+        if passphrase.is_empty() {
+            return Err(anyhow::anyhow!("The passphrase must not be empty"));
+        }
+        if !self.path.exists() {
+            let confirmation = rpassword::prompt_password("Repeat the passphrase: ")
+                .context("Could not read the passphrase")?;
+            if confirmation != passphrase {
+                return Err(anyhow::anyhow!("The passphrases do not match"));
+            }
+        }
+        Ok(passphrase)
+    }
+}
+
+/// Opens the database at `path` with `passphrase`. Fails if the passphrase is wrong
+fn open(path: &Path, passphrase: &str) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "key", passphrase)?;
+    // The key is only checked when the database is first read
+    conn.query_row("SELECT count(*) FROM sqlite_master;", [], |_| Ok(()))
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Could not open {}: the passphrase is wrong, or the database is not encrypted",
+                path.display()
+            )
+        })?;
+    configure_file_connection(&conn)?;
+    Ok(conn)
+}
+
+impl Storage for EncryptedSqliteStorage {
+    fn connect(&self) -> Result<Connection> {
+        create_parent_dir(&self.path)?;
+
+        if let Some(passphrase) = self.saved_passphrase() {
+            if let Ok(conn) = open(&self.path, &passphrase) {
+                return Ok(conn);
+            }
+            eprintln!("The passphrase saved in the keyring is wrong");
+        }
+
+        let passphrase = self.prompt_passphrase()?;
+        let conn = open(&self.path, &passphrase)?;
+        if self.remember_passphrase {
+            self.keyring_entry()?
+                .set_password(&passphrase)
+                .context("Could not save the passphrase in the keyring")?;
+        }
+        Ok(conn)
+    }
+}
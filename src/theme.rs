@@ -0,0 +1,107 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use colored::{Color, ColoredString, Colorize};
+use serde::Deserialize;
+
+use crate::utils::COLORS;
+
+/// A color of the theme, written in the config either as the name of a terminal color (e.g. `blue` or `bright red`)
+/// or as an hex RGB color (e.g. `#ffa500`)
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(try_from = "String")]
+pub struct ThemeColor(pub Color);
+
+impl TryFrom<String> for ThemeColor {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if let Some(hex) = s.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(Self(Color::TrueColor { r, g, b })),
+                _ => Err(anyhow::anyhow!(
+                    "`{s}` is not a valid hex color: use the `#rrggbb` format"
+                )),
+            };
+        }
+        s.parse::<Color>()
+            .map(Self)
+            .map_err(|()| anyhow::anyhow!("`{s}` is not the name of a color, nor a hex color"))
+    }
+}
+
+/// The colors used to print the entries, set with the `theme` config option
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Theme {
+    /// The color of the names of the entries, which are also printed in bold
+    pub name: ThemeColor,
+    /// The color of the urls, which are also underlined
+    pub url: ThemeColor,
+    /// The color of the names of the authors
+    pub author: ThemeColor,
+    /// The background colors of the topics. Each topic always gets the same color, picked by hashing its name
+    pub topics: Vec<ThemeColor>,
+    /// The background colors pinned to some topics, which take precedence over `topics`. Set with the `topic_colors` config option
+    #[serde(skip)]
+    pub topic_colors: HashMap<String, ThemeColor>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: ThemeColor(Color::TrueColor {
+                r: 255,
+                g: 165,
+                b: 0,
+            }),
+            url: ThemeColor(Color::BrightBlue),
+            author: ThemeColor(Color::Green),
+            topics: COLORS
+                .iter()
+                .map(|&(r, g, b)| ThemeColor(Color::TrueColor { r, g, b }))
+                .collect(),
+            topic_colors: HashMap::new(),
+        }
+    }
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Sets the theme used to print the entries and the topics for the rest of the program.
+/// Only the first call has an effect: the theme cannot be changed once it is set (or once something was printed with the default one)
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// Returns the theme set with [`set_theme`], or the default one
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+/// Styles text with the colors of the current [`theme`]
+pub trait Themed {
+    /// Styles the name of an entry
+    fn entry_name(self) -> ColoredString;
+    /// Styles a url
+    fn link(self) -> ColoredString;
+    /// Styles the name of an author
+    fn author_name(self) -> ColoredString;
+}
+
+impl Themed for &str {
+    fn entry_name(self) -> ColoredString {
+        self.bold().color(theme().name.0)
+    }
+
+    fn link(self) -> ColoredString {
+        self.color(theme().url.0).underline()
+    }
+
+    fn author_name(self) -> ColoredString {
+        self.color(theme().author.0)
+    }
+}
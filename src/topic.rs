@@ -3,18 +3,37 @@ use std::{collections::hash_map::DefaultHasher, hash::Hasher};
 use colored::Colorize;
 use std::hash::Hash;
 
-use crate::utils::COLORS;
+use crate::{theme::theme, utils::normalize_name};
 
-pub(crate) struct Topic {}
+/// The separator between the levels of nested topics: `programming/rust` is a subtopic of `programming`
+pub const TOPIC_SEPARATOR: char = '/';
+
+pub struct Topic {}
 
 impl Topic {
-    pub(crate) fn pretty_print<T>(topic: T) -> String
+    /// Normalizes each level of `topic` like [`normalize_name`], so that e.g. ` programming /  rust` becomes `programming/rust`
+    pub fn normalize(topic: impl AsRef<str>) -> String {
+        topic
+            .as_ref()
+            .split(TOPIC_SEPARATOR)
+            .map(normalize_name)
+            .collect::<Vec<_>>()
+            .join(&TOPIC_SEPARATOR.to_string())
+    }
+
+    pub fn pretty_print<T>(topic: T) -> String
     where
         T: AsRef<str> + Hash + Colorize,
     {
-        let mut hasher = DefaultHasher::new();
-        topic.hash(&mut hasher);
-        let c = COLORS[hasher.finish() as usize % COLORS.len()];
-        topic.on_truecolor(c.0, c.1, c.2).to_string()
+        let theme = theme();
+        let c = match theme.topic_colors.get(topic.as_ref()) {
+            Some(c) => c,
+            None => {
+                let mut hasher = DefaultHasher::new();
+                topic.hash(&mut hasher);
+                &theme.topics[hasher.finish() as usize % theme.topics.len()]
+            }
+        };
+        topic.on_color(c.0).to_string()
     }
 }
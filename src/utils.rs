@@ -1,7 +1,11 @@
 use anyhow::Result;
+use std::io::Write;
 
 use dateparser::DateTimeUtc;
 
+use crate::{config::UrlCanonicalization, error::RListError};
+use unicode_normalization::UnicodeNormalization;
+
 pub(crate) const COLORS: [(u8, u8, u8); 20] = [
     (200, 10, 20),
     (125, 30, 20),
@@ -25,68 +29,467 @@ pub(crate) const COLORS: [(u8, u8, u8); 20] = [
     (5, 2, 207),
 ];
 
-pub trait ToSQL {
-    fn to_sql(self) -> String;
+const SQLITE_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Returns the given date `dt` to the format used by the db
+pub(crate) fn dt_to_string(dt: &DateTimeUtc) -> String {
+    chrono::DateTime::<chrono::Local>::from(dt.0)
+        .format(SQLITE_DATETIME_FORMAT)
+        .to_string()
+}
+
+/// Returns the hostname of `url`, without the leading `www.`. Returns `None` if `url` is not a valid url or has no host
+pub(crate) fn domain_of(url: impl AsRef<str>) -> Option<String> {
+    let url = url::Url::parse(url.as_ref()).ok()?;
+    let host = url.host_str()?.to_lowercase();
+    Some(
+        host.strip_prefix("www.")
+            .map(|h| h.to_string())
+            .unwrap_or(host),
+    )
 }
 
-impl<'a> ToSQL for Option<&'a str> {
-    fn to_sql(self) -> String {
-        match self {
-            Some(v) => v.to_string(),
-            None => "NULL".to_string(),
+/// Query parameters that only track where a link was shared, and never change the content of the page
+const TRACKING_PARAMS: [&str; 11] = [
+    "fbclid", "gclid", "dclid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_hsenc",
+    "_hsmkt", "ref_src",
+];
+
+/// Normalizes `url` as configured in `opts`. Urls that cannot be parsed are returned as they are
+pub fn canonicalize_url(url: &str, opts: &UrlCanonicalization) -> String {
+    let Ok(mut parsed) = url::Url::parse(url.trim()) else {
+        return url.to_string();
+    };
+
+    if opts.strip_fragment {
+        parsed.set_fragment(None);
+    }
+    if opts.strip_tracking_params && parsed.query().is_some() {
+        let kept = parsed
+            .query_pairs()
+            .filter(|(k, _)| !k.starts_with("utm_") && !TRACKING_PARAMS.contains(&k.as_ref()))
+            .map(|(k, v)| (k.into_owned(), v.into_owned()))
+            .collect::<Vec<_>>();
+        if kept.is_empty() {
+            parsed.set_query(None);
+        } else {
+            parsed.query_pairs_mut().clear().extend_pairs(kept);
+        }
+    }
+
+    if opts.strip_trailing_slash && parsed.has_host() {
+        let path = parsed.path().trim_end_matches('/').to_string();
+        if path.is_empty() {
+            // The root path is always serialized as `/`, so its slash can only be dropped when it ends the url
+            if parsed.query().is_none() && parsed.fragment().is_none() {
+                return parsed.to_string().trim_end_matches('/').to_string();
+            }
+        } else {
+            parsed.set_path(&path);
         }
     }
+    parsed.to_string()
 }
 
+/// Checks that `url` is a valid url, returning it trimmed. Bare domains (e.g. `example.com/article`) are given the `https://` scheme
+pub fn validate_url(url: &str) -> Result<String> {
+    let url = url.trim();
+    let invalid = || {
+        anyhow::Error::from(RListError::invalid_input(
+            "url",
+            url,
+            format!("Invalid url \"{url}\": expected a url such as https://example.com/article"),
+        ))
+    };
+
+    let (url, parsed) = match url::Url::parse(url) {
+        Ok(parsed) => (url.to_string(), parsed),
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            // Only a host (with at least a dot, to rule out plain words) followed by an optional path is taken as a bare domain
+            let host = url.split(['/', '?', '#']).next().unwrap_or_default();
+            if !host.contains('.') || host.contains(char::is_whitespace) {
+                return Err(invalid());
+            }
+            let url = format!("https://{url}");
+            let parsed = url::Url::parse(&url).map_err(|_| invalid())?;
+            (url, parsed)
+        }
+        Err(_) => return Err(invalid()),
+    };
 
-pub(crate) fn opt_from_sql<T, R>(repr: R) -> Option<T>
-where
-    T: From<String>,
-    R: AsRef<str>,
-{
-    match repr.as_ref() {
-        "NULL" => None,
-        o => Some(o.to_string().into()),
+    if matches!(parsed.scheme(), "http" | "https")
+        && !parsed
+            .host_str()
+            .is_some_and(|h| h.contains('.') || h == "localhost")
+        && !matches!(parsed.host(), Some(url::Host::Ipv4(_) | url::Host::Ipv6(_)))
+    {
+        return Err(invalid());
     }
+    Ok(url)
 }
 
-const SQLITE_DATETIME_FORMAT: &'static str = "%Y-%m-%d %H:%M:%S";
+/// Parses a duration such as `12h`, `3d`, `2w`, `6m` (months, counted as 30 days) or `1y` (counted as 365 days).
+/// The units can also be spelled out, as in `3 days` or `1 month`
+pub fn parse_duration(s: impl AsRef<str>) -> Result<chrono::Duration> {
+    let s = s.as_ref().trim();
+    let invalid = || {
+        anyhow::Error::from(RListError::invalid_input(
+            "duration",
+            s,
+            format!("Invalid duration \"{s}\": expected a number followed by one of h, d, w, m, y (e.g. `2w`)"),
+        ))
+    };
 
-/// Returns the given date `dt` to the format used by the db
-pub(crate) fn dt_to_string(dt: DateTimeUtc) -> String {
-    chrono::DateTime::<chrono::Local>::from(dt.0)
+    let unit_start = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (amount, unit) = s.split_at(unit_start);
+    let amount = amount.parse::<i64>().map_err(|_| invalid())?;
+
+    match unit.trim_start().to_lowercase().as_str() {
+        "min" | "minute" | "minutes" => Ok(chrono::Duration::minutes(amount)),
+        "h" | "hour" | "hours" => Ok(chrono::Duration::hours(amount)),
+        "d" | "day" | "days" => Ok(chrono::Duration::days(amount)),
+        "w" | "week" | "weeks" => Ok(chrono::Duration::weeks(amount)),
+        "m" | "month" | "months" => Ok(chrono::Duration::days(30 * amount)),
+        "y" | "year" | "years" => Ok(chrono::Duration::days(365 * amount)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Returns the current datetime, in the format used by the db
+pub fn now() -> String {
+    chrono::Local::now()
         .format(SQLITE_DATETIME_FORMAT)
         .to_string()
 }
 
-pub(crate) fn sql_string_to_dt(s: impl AsRef<str>) -> Result<chrono::NaiveDateTime> {
-    Ok(chrono::NaiveDateTime::parse_from_str(s.as_ref(), SQLITE_DATETIME_FORMAT)?)
+/// Returns the datetime `duration` ago, in the format used by the db
+pub fn datetime_before_now(duration: chrono::Duration) -> String {
+    (chrono::Local::now() - duration)
+        .format(SQLITE_DATETIME_FORMAT)
+        .to_string()
 }
 
-pub(crate) fn get_conflicting_column_name(err: &sqlite::Error) -> Option<String> {
-    if let Some(19) = err.code {
-        if let Some(ref msg) = err.message {
-            if msg.starts_with("UNIQUE constraint failed: ") {
-                let col = &msg["UNIQUE constraint failed: ".len()-1..].trim();
-                return Some(col.to_string());
-            }
+/// Parses either a duration (see [`parse_duration`]), which is added to the current time, a day name
+/// (`today`, `tomorrow`, `monday`, `fri`, ... meaning the end of that day) or a datetime.
+/// Returns the resulting datetime in the format used by the db
+pub fn parse_future_datetime(s: impl AsRef<str>) -> Result<String> {
+    if let Some(day) = parse_day_name(s.as_ref()) {
+        return Ok(day
+            .and_hms_opt(23, 59, 59)
+            .expect("valid time")
+            .format(SQLITE_DATETIME_FORMAT)
+            .to_string());
+    }
+    match parse_duration(s.as_ref()) {
+        Ok(duration) => Ok((chrono::Local::now() + duration)
+            .format(SQLITE_DATETIME_FORMAT)
+            .to_string()),
+        Err(_) => {
+            let dt = s.as_ref().parse::<DateTimeUtc>().map_err(|_| {
+                RListError::invalid_input(
+                    "date",
+                    s.as_ref(),
+                    format!(
+                        "Could not parse \"{}\" as a duration (e.g. `2w`) or as a datetime",
+                        s.as_ref()
+                    ),
+                )
+            })?;
+            Ok(dt_to_string(&dt))
+        }
+    }
+}
+
+/// Returns the date named by `s`: `today`, `tomorrow` or the next occurrence (today included) of a day of the week
+fn parse_day_name(s: &str) -> Option<chrono::NaiveDate> {
+    use chrono::Datelike;
+
+    let today = chrono::Local::now().date_naive();
+    let weekday = match s.trim().to_lowercase().as_str() {
+        "today" => return Some(today),
+        "tomorrow" => return today.succ_opt(),
+        other => parse_weekday(other)?,
+    };
+    let days_ahead =
+        (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    today.checked_add_days(chrono::Days::new(days_ahead as u64))
+}
+
+/// Returns the first and the last day of the period named by `s`: `today`, `yesterday`, `tomorrow`,
+/// the last occurrence (today included) of a day of the week, or the last one before today if it follows `last`
+/// (e.g. `last monday`), or a whole week, month or year (`this week`, `last month`, ...)
+fn parse_day_range(s: &str) -> Option<(chrono::NaiveDate, chrono::NaiveDate)> {
+    use chrono::Datelike;
+
+    let today = chrono::Local::now().date_naive();
+    let s = s.trim().to_lowercase();
+    let (period, before_today) = match s.as_str() {
+        "today" => return Some((today, today)),
+        "yesterday" => return today.pred_opt().map(|day| (day, day)),
+        "tomorrow" => return today.succ_opt().map(|day| (day, day)),
+        other => match (other.strip_prefix("last "), other.strip_prefix("this ")) {
+            (Some(period), _) => (period.trim(), true),
+            (_, Some(period)) => (period.trim(), false),
+            _ => (other, false),
+        },
+    };
+    match period {
+        "week" => {
+            let monday = today.checked_sub_days(chrono::Days::new(
+                today.weekday().num_days_from_monday() as u64,
+            ))?;
+            let monday = if before_today {
+                monday.checked_sub_days(chrono::Days::new(7))?
+            } else {
+                monday
+            };
+            Some((monday, monday.checked_add_days(chrono::Days::new(6))?))
+        }
+        "month" => {
+            let first = today.with_day(1)?;
+            let first = if before_today {
+                first.checked_sub_months(chrono::Months::new(1))?
+            } else {
+                first
+            };
+            let next = first.checked_add_months(chrono::Months::new(1))?;
+            Some((first, next.pred_opt()?))
+        }
+        "year" => {
+            let year = if before_today {
+                today.year() - 1
+            } else {
+                today.year()
+            };
+            Some((
+                chrono::NaiveDate::from_ymd_opt(year, 1, 1)?,
+                chrono::NaiveDate::from_ymd_opt(year, 12, 31)?,
+            ))
+        }
+        day => {
+            let weekday = parse_weekday(day)?;
+            let days_behind =
+                (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+            let days_behind = if before_today && days_behind == 0 {
+                7
+            } else {
+                days_behind
+            };
+            let day = today.checked_sub_days(chrono::Days::new(days_behind as u64))?;
+            Some((day, day))
+        }
+    }
+}
+
+/// Parses the name of a day of the week, either in full or abbreviated (e.g. `monday` or `mon`)
+fn parse_weekday(s: &str) -> Option<chrono::Weekday> {
+    match s {
+        "mon" | "monday" => Some(chrono::Weekday::Mon),
+        "tue" | "tuesday" => Some(chrono::Weekday::Tue),
+        "wed" | "wednesday" => Some(chrono::Weekday::Wed),
+        "thu" | "thursday" => Some(chrono::Weekday::Thu),
+        "fri" | "friday" => Some(chrono::Weekday::Fri),
+        "sat" | "saturday" => Some(chrono::Weekday::Sat),
+        "sun" | "sunday" => Some(chrono::Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Parses either a day name (`today`, `yesterday`, `monday`, `last fri`, ... meaning the start of that day, in local time),
+/// a week, month or year (`this week`, `last month`, ... meaning its first day), a duration (see [`parse_duration`]),
+/// optionally followed by `ago`, which is subtracted from the current time, or a datetime.
+/// `30d` and `30 days ago` both mean "30 days ago"
+pub fn parse_past_datetime(s: impl AsRef<str>) -> Result<DateTimeUtc> {
+    parse_date_bound(s.as_ref(), false)
+}
+
+/// Like [`parse_past_datetime`], but days and periods mean their last second, as needed by the upper bound of a range:
+/// `--to today` includes the entries of today, and `--to "last month"` the ones of the whole last month
+pub fn parse_past_datetime_end(s: impl AsRef<str>) -> Result<DateTimeUtc> {
+    parse_date_bound(s.as_ref(), true)
+}
+
+fn parse_date_bound(s: &str, end: bool) -> Result<DateTimeUtc> {
+    use chrono::TimeZone;
+
+    let s = s.trim();
+    if let Some((first, last)) = parse_day_range(s) {
+        let bound = if end {
+            last.and_hms_opt(23, 59, 59).expect("valid time")
+        } else {
+            first.and_hms_opt(0, 0, 0).expect("valid time")
+        };
+        // Midnight can be skipped by a daylight saving time change
+        let bound = chrono::Local
+            .from_local_datetime(&bound)
+            .earliest()
+            .unwrap_or_else(|| chrono::Local.from_utc_datetime(&bound));
+        return Ok(DateTimeUtc(bound.with_timezone(&chrono::Utc)));
+    }
+    let duration = s.strip_suffix("ago").unwrap_or(s);
+    match parse_duration(duration) {
+        Ok(duration) => Ok(DateTimeUtc(chrono::Utc::now() - duration)),
+        Err(_) => s.parse::<DateTimeUtc>().map_err(|_| {
+            RListError::invalid_input(
+                "date",
+                s,
+                format!("Could not parse \"{s}\" as a day (e.g. `yesterday` or `last monday`), a period (e.g. `last month`), a duration (e.g. `2w` or `3 days ago`) or as a datetime"),
+            )
+            .into()
+        }),
+    }
+}
+
+/// Parses a `key=value` pair, as accepted by `--meta`. The key must not be empty, while the value can be
+pub fn parse_key_value(s: &str) -> Result<(String, String)> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(RListError::invalid_input(
+            "metadata",
+            s,
+            format!("Invalid metadata \"{s}\": expected a `key=value` pair"),
+        )
+        .into()),
+    }
+}
+
+/// Parses either a `key=value` pair (see [`parse_key_value`]) or a bare `key`
+pub fn parse_key_maybe_value(s: &str) -> Result<(String, Option<String>)> {
+    if s.contains('=') {
+        parse_key_value(s).map(|(key, value)| (key, Some(value)))
+    } else if s.trim().is_empty() {
+        Err(RListError::invalid_input("metadata", s, "Metadata keys cannot be empty").into())
+    } else {
+        Ok((s.trim().to_string(), None))
+    }
+}
+
+/// Normalizes the name of an entry: trims it, collapses the runs of whitespace inside of it into single spaces
+/// and converts it to Unicode NFC, so that names that look the same are also stored the same
+pub fn normalize_name(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .nfc()
+        .collect()
+}
+
+/// The prefix of the short ids of the entries, which sets them apart from the names of the entries
+pub const SHORT_ID_PREFIX: char = '@';
+
+/// Returns the short id of the entry with id = `entry_id`: its id in base 36, such as `@1z`
+pub fn short_id(entry_id: i64) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut n = entry_id.unsigned_abs();
+    let mut digits = Vec::new();
+    loop {
+        digits.push(DIGITS[(n % 36) as usize] as char);
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    std::iter::once(SHORT_ID_PREFIX)
+        .chain(digits.into_iter().rev())
+        .collect()
+}
+
+/// Returns the id of the entry whose short id (see [`short_id`]) is `s`, or `None` if `s` is not a short id
+pub fn parse_short_id(s: &str) -> Option<i64> {
+    let digits = s.trim().strip_prefix(SHORT_ID_PREFIX)?;
+    if digits.is_empty() {
+        return None;
+    }
+    i64::from_str_radix(digits, 36).ok()
+}
+
+/// Escapes the wildcards of a SQL `LIKE` pattern (`%` and `_`) in `s`, so that it is matched literally by `LIKE ... ESCAPE '\'`
+pub fn escape_like(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+pub fn sql_string_to_dt(s: impl AsRef<str>) -> Result<chrono::NaiveDateTime> {
+    Ok(chrono::NaiveDateTime::parse_from_str(
+        s.as_ref(),
+        SQLITE_DATETIME_FORMAT,
+    )?)
+}
+
+pub(crate) fn get_conflicting_column_name(err: &rusqlite::Error) -> Option<String> {
+    if let rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error {
+            code: rusqlite::ErrorCode::ConstraintViolation,
+            ..
+        },
+        Some(msg),
+    ) = err
+    {
+        if let Some(col) = msg.strip_prefix("UNIQUE constraint failed: ") {
+            return Some(col.trim().to_string());
         }
     }
     None
 }
 
+/// Asks the user a yes/no question on stdout and returns whether the answer was affirmative.
+/// Anything other than `y`/`yes` (case insensitive) counts as a no.
+pub fn confirm(question: impl AsRef<str>) -> Result<bool> {
+    print!("{} [y/N] ", question.as_ref());
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Opens `content` in the editor set in `$VISUAL` or `$EDITOR` (`vi` by default) and returns the content once the editor is closed.
+/// The content is written to a temporary file named `rlist-<name>`, so that `name` can give it an extension the editor recognizes
+pub fn edit_in_editor(content: &str, name: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut args = editor.split_whitespace();
+    let program = args
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The editor set in $VISUAL or $EDITOR is empty"))?;
+
+    let path = std::env::temp_dir().join(format!("rlist-{}-{name}", std::process::id()));
+    std::fs::write(&path, content)?;
+    let status = std::process::Command::new(program)
+        .args(args)
+        .arg(&path)
+        .status()
+        .map_err(|err| anyhow::anyhow!("Could not start the editor {program}: {err}"));
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    if !status?.success() {
+        return Err(anyhow::anyhow!("The editor exited with an error"));
+    }
+    Ok(edited?)
+}
 
 // adapted from https://github.com/chronotope/chrono/issues/342
 /// Returns whether the input is a valid strftime format string
 pub(crate) fn format_string_is_valid(s: impl AsRef<str>) -> bool {
-    !chrono::format::StrftimeItems::new(s.as_ref()).any(|item| matches!(item, chrono::format::Item::Error))
+    !chrono::format::StrftimeItems::new(s.as_ref())
+        .any(|item| matches!(item, chrono::format::Item::Error))
 }
 
 #[macro_export]
 macro_rules! read_sql_response {
-    ($stmt:expr, $($col_name:ident => $t:ty),+) => {
+    ($row:expr, $($col_name:ident => $t:ty),+) => {
         $(
-            let $col_name = $stmt.read::<$t, _>(stringify!($col_name))?;
+            let $col_name = $row.get::<_, $t>(stringify!($col_name))?;
         )+
     };
-}
\ No newline at end of file
+}
@@ -0,0 +1,259 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use scraper::{ElementRef, Html, Selector};
+
+/// How long to wait for a page before giving up
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long to wait for the Wayback Machine to archive a page, which can take a while
+const ARCHIVE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Returns the HTTP agent used for every request made by rlist
+pub fn agent() -> ureq::Agent {
+    agent_with_timeout(TIMEOUT)
+}
+
+fn agent_with_timeout(timeout: Duration) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(timeout)
+        .user_agent(concat!("rlist/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// Downloads the document at `url`, returning its content as text
+pub fn fetch_text(url: &str) -> Result<String> {
+    agent()
+        .get(url)
+        .call()
+        .with_context(|| format!("Could not download {url}"))?
+        .into_string()
+        .with_context(|| format!("Could not read the content of {url}"))
+}
+
+/// Asks the Wayback Machine to save a snapshot of `url`. Returns the url of the snapshot
+pub fn archive(url: &str) -> Result<String> {
+    let response = agent_with_timeout(ARCHIVE_TIMEOUT)
+        .get(&format!("https://web.archive.org/save/{url}"))
+        .call()
+        .context("Could not archive the page")?;
+
+    // The snapshot is either where the request was redirected to, or where `Content-Location` points to
+    if response
+        .get_url()
+        .starts_with("https://web.archive.org/web/")
+    {
+        return Ok(response.get_url().to_string());
+    }
+    match response.header("content-location") {
+        Some(location) if location.starts_with("/web/") => {
+            Ok(format!("https://web.archive.org{location}"))
+        }
+        _ => Err(anyhow::anyhow!(
+            "The Wayback Machine did not return the location of the snapshot"
+        )),
+    }
+}
+
+/// The details about the content of a page that can be stored in its entry
+pub struct PageDetails {
+    pub author: Option<String>,
+    pub site_name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// A downloaded HTML page
+pub struct Page {
+    html: Html,
+}
+
+impl Page {
+    /// Downloads the page at `url`
+    pub fn fetch(url: impl AsRef<str>) -> Result<Self> {
+        let response = agent()
+            .get(url.as_ref())
+            .call()
+            .context("Could not download the page")?;
+        let body = response
+            .into_string()
+            .with_context(|| format!("Could not read the content of {}", url.as_ref()))?;
+
+        Ok(Self {
+            html: Html::parse_document(&body),
+        })
+    }
+
+    /// Returns the title of the page, preferring `og:title` over `<title>`
+    pub fn title(&self) -> Option<String> {
+        self.meta("og:title").or_else(|| {
+            let selector = Selector::parse("title").expect("valid selector");
+            self.html
+                .select(&selector)
+                .next()
+                .map(|t| normalize_text(&t.text().collect::<String>()))
+                .filter(|t| !t.is_empty())
+        })
+    }
+
+    /// Extracts the author, the site name and the description of the page from its OpenGraph (or plain) `<meta>` tags
+    pub fn details(&self) -> PageDetails {
+        // `article:author` is often the url of the author's profile rather than their name
+        let author = self
+            .meta("author")
+            .or_else(|| self.meta("article:author"))
+            .filter(|a| !a.starts_with("http://") && !a.starts_with("https://"));
+
+        PageDetails {
+            author,
+            site_name: self.meta("og:site_name"),
+            description: self
+                .meta("og:description")
+                .or_else(|| self.meta("description")),
+        }
+    }
+
+    /// Extracts the readable text of the page: the headings, paragraphs, lists, quotes and code blocks
+    /// of its `<article>` (or `<main>`, or `<body>`), leaving out navigation menus, sidebars and footers.
+    /// Headings are prefixed with `#`, list items with `-` and quotes with `>`
+    pub fn readable_text(&self) -> String {
+        let Some(root) = ["article", "main", "body"].iter().find_map(|tag| {
+            let selector = Selector::parse(tag).expect("valid selector");
+            self.html.select(&selector).next()
+        }) else {
+            return String::new();
+        };
+
+        let blocks = Selector::parse("h1, h2, h3, h4, h5, h6, p, li, pre, blockquote")
+            .expect("valid selector");
+        let mut paragraphs = Vec::new();
+        for block in root.select(&blocks) {
+            let mut ancestors = block
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .take_while(|a| a.id() != root.id());
+            // Blocks nested in other blocks are skipped, as their text is part of the outer block
+            if ancestors.any(|a| {
+                matches!(
+                    a.value().name(),
+                    "nav" | "aside" | "footer" | "form" | "p" | "li" | "pre" | "blockquote"
+                )
+            }) {
+                continue;
+            }
+
+            let name = block.value().name();
+            let text = if name == "pre" {
+                block.text().collect::<String>().trim_end().to_string()
+            } else {
+                normalize_text(&block.text().collect::<String>())
+            };
+            if text.is_empty() {
+                continue;
+            }
+
+            paragraphs.push(match name {
+                "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                    format!(
+                        "{} {text}",
+                        "#".repeat((name.as_bytes()[1] - b'0') as usize)
+                    )
+                }
+                "li" => format!("- {text}"),
+                "blockquote" => format!("> {text}"),
+                _ => text,
+            });
+        }
+        paragraphs.join("\n\n")
+    }
+
+    /// Returns the content of the `<meta>` tag whose `property` or `name` is `name`, if it is not empty
+    pub fn meta(&self, name: &str) -> Option<String> {
+        let selector = Selector::parse("meta[content]").expect("valid selector");
+        self.html
+            .select(&selector)
+            .find(|m| {
+                let el = m.value();
+                el.attr("property").or_else(|| el.attr("name")) == Some(name)
+            })
+            .and_then(|m| m.value().attr("content"))
+            .map(normalize_text)
+            .filter(|c| !c.is_empty())
+    }
+}
+
+/// Collapses all of the whitespace in `s` into single spaces
+fn normalize_text(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The result of checking whether a link is still reachable
+pub enum LinkStatus {
+    Ok,
+    /// The link works, but it redirects to this url
+    Redirected(String),
+    /// The link is broken: the reason is either an HTTP status or a network error
+    Broken(String),
+}
+
+/// Checks whether `url` is reachable. A `HEAD` request is sent first, falling back to `GET` for servers that do not support it
+pub fn check_link(agent: &ureq::Agent, url: &str) -> LinkStatus {
+    let response = match agent.head(url).call() {
+        Err(ureq::Error::Status(405 | 501, _)) => agent.get(url).call(),
+        other => other,
+    };
+
+    match response {
+        Ok(response) => {
+            let same_url = match (url::Url::parse(url), url::Url::parse(response.get_url())) {
+                (Ok(from), Ok(to)) => from == to,
+                _ => url == response.get_url(),
+            };
+            if same_url {
+                LinkStatus::Ok
+            } else {
+                LinkStatus::Redirected(response.get_url().to_string())
+            }
+        }
+        Err(ureq::Error::Status(code, response)) => {
+            LinkStatus::Broken(format!("{code} {}", response.status_text()))
+        }
+        Err(ureq::Error::Transport(transport)) => LinkStatus::Broken(match transport.message() {
+            Some(message) => format!("{}: {message}", transport.kind()),
+            None => transport.kind().to_string(),
+        }),
+    }
+}
+
+/// Checks all of the `urls` using up to `jobs` threads. The results are in the same order as `urls`
+pub fn check_links(urls: &[String], jobs: usize) -> Vec<LinkStatus> {
+    let agent = agent();
+    let next = AtomicUsize::new(0);
+
+    let mut results = std::thread::scope(|s| {
+        let workers = (0..jobs.clamp(1, urls.len().max(1)))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut checked = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(url) = urls.get(i) else {
+                            break checked;
+                        };
+                        checked.push((i, check_link(&agent, url)));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().expect("link checker thread panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|(i, _status)| *i);
+    results.into_iter().map(|(_i, status)| status).collect()
+}